@@ -0,0 +1,41 @@
+use serde::Deserialize;
+
+/// Response from the Plex server's list accounts endpoint
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlexAccounts {
+    /// Accounts known to the server (the owner and any home or shared users)
+    #[serde(rename = "Account", default)]
+    pub account: Vec<PlexAccount>,
+}
+
+/// A local account of the Plex server
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlexAccount {
+    /// ID of the account, used as `accountID` when filtering watch history
+    pub id: u64,
+    /// Name of the account, which matches the plex.tv username of the user
+    #[serde(default)]
+    pub name: String,
+}
+
+/// Response from the Plex server's `/myplex/account` endpoint
+///
+/// Unlike most endpoints, this response is not wrapped in a `MediaContainer`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlexMyPlexResponse {
+    /// plex.tv account the token belongs to
+    #[serde(rename = "MyPlex")]
+    pub my_plex: PlexMyPlexAccount,
+}
+
+/// plex.tv account the token belongs to, as seen by the Plex server
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlexMyPlexAccount {
+    /// plex.tv username of the account
+    #[serde(default)]
+    pub username: String,
+    /// Whether the server is signed in to plex.tv (e.g. "ok")
+    #[serde(default)]
+    pub sign_in_state: String,
+}
@@ -0,0 +1,892 @@
+use std::sync::{Arc, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::header::CONTENT_TYPE;
+use serde::Deserialize;
+use url::Url;
+
+use super::{
+    account_id_or_owner, check_token, find_account_id, middleware_headers, no_account_error,
+    parse_base_url, parse_response, redirect_policy, send_with_retry_after, server_capabilities,
+    CircuitBreaker, CircuitOpenError, ConnectionSettings, Endpoint, MediaContainer, Middleware,
+    ProxyAuth, Query, ResponseCache, ResponseCapture, TokenRejectedError, DEFAULT_PAGE_RETRIES,
+    PAGE_SIZE,
+};
+use crate::account::{PlexAccounts, PlexMyPlexAccount, PlexMyPlexResponse};
+use crate::device::PlexDevices;
+use crate::identity::{PlexServerIdentity, ServerCapabilities};
+use crate::library::{PlexLibraryItems, PlexLibrarySection};
+use crate::media_item::PlexMediaItem;
+use crate::watch_history::{PlexWatchHistory, PlexWatchHistoryItem};
+
+/// Represents a Plex client that can communicate with a Plex Media Server
+///
+/// This struct holds the necessary information to communicate with a Plex Media Server:
+/// - `base_url`: The base URL of your Plex server (e.g., "http://192.168.1.100:32400")
+/// - `token`: Your Plex authentication token
+/// - `client`: An HTTP client for making requests
+/// - `language`: Optional language used for localized metadata
+/// - `page_retries`: How many times a failed watch history page is retried
+/// - `account_id`: Account whose watch history is requested, resolved on first use
+/// - `proxy_auth`: Optional credentials for a reverse proxy in front of the server
+/// - `response_cache`: Optional directory responses are saved to or replayed from
+/// - `response_capture`: Optional directory raw responses are written to for bug reports
+/// - `circuit_breaker`: Optional breaker that stops requests after repeated failures
+/// - `middleware`: Hooks called around every request, in the order they were added
+///
+/// `PlexClient` is `Clone`, `Send` and `Sync`. Cloning is cheap: the
+/// underlying reqwest client is reference counted and shares its connection
+/// pool, so a clone can be handed to each worker thread to fan requests out
+/// without wrapping the client in `Arc<Mutex<_>>`.
+///
+/// # Example
+///
+/// ```no_run
+/// use plex_client::client::PlexClient;
+///
+/// # let (url, token) = (String::new(), String::new());
+/// let client = PlexClient::new(url, token)?;
+/// let handles: Vec<_> = ["101", "102"]
+///     .into_iter()
+///     .map(|rating_key| {
+///         let client = client.clone();
+///         std::thread::spawn(move || client.get_media_item_metadata(rating_key.to_string()))
+///     })
+///     .collect();
+/// for handle in handles {
+///     let item = handle.join().unwrap()?;
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Clone)]
+pub struct PlexClient {
+    /// Base URL of the Plex Media Server, normalized with a trailing slash
+    base_url: Url,
+    /// Plex authentication token
+    token: String,
+    /// HTTP client for making requests
+    client: Client,
+    /// Language sent with every request (e.g., "en-US")
+    language: Option<String>,
+    /// Number of times a failed watch history page is retried
+    page_retries: u32,
+    /// Account whose watch history is requested, resolved on first use
+    account_id: OnceLock<String>,
+    /// Credentials for a reverse proxy in front of the server
+    proxy_auth: Option<ProxyAuth>,
+    /// Features of the server, recorded by `check_server_version`
+    capabilities: OnceLock<ServerCapabilities>,
+    /// Directory responses are saved to, or replayed from when offline
+    response_cache: Option<ResponseCache>,
+    /// Directory raw responses are written to, with secrets redacted
+    response_capture: Option<ResponseCapture>,
+    /// Breaker that stops requests after repeated failures, shared between clones
+    circuit_breaker: Option<CircuitBreaker>,
+    /// Hooks called around every request, shared between clones
+    middleware: Vec<Arc<dyn Middleware>>,
+}
+
+impl PlexClient {
+    /// Creates a new PlexClient with the given server URL and authentication token
+    ///
+    /// # Arguments
+    ///
+    /// * `base_url` - The base URL of your Plex Media Server
+    ///   Example: "http://192.168.1.100:32400" or "https://your-server.plex.direct:32400"
+    /// * `token` - Your Plex authentication token
+    ///
+    /// The URL may include a base path (e.g. "https://example.com/plex") and
+    /// an IPv6 literal host (e.g. "http://[::1]:32400"); a trailing slash is ignored.
+    ///
+    /// # Errors
+    ///
+    /// Fails when `base_url` is not a valid http(s) URL.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use plex_client::client::PlexClient;
+    ///
+    /// let client = PlexClient::new(
+    ///     "http://192.168.1.100:32400".to_string(),
+    ///     "your-token-here".to_string(),
+    /// )?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn new(base_url: String, token: String) -> Result<Self> {
+        // Parse the URL once so requests only need to join endpoints onto it
+        let base_url = parse_base_url(&base_url)?;
+
+        // Create an HTTP client with default settings
+        // The `blocking` feature of reqwest gives us a synchronous client
+        let client = http_client(&ConnectionSettings::default());
+
+        Ok(Self {
+            base_url,
+            token,
+            client,
+            language: None,
+            page_retries: DEFAULT_PAGE_RETRIES,
+            account_id: OnceLock::new(),
+            proxy_auth: None,
+            capabilities: OnceLock::new(),
+            response_cache: None,
+            response_capture: None,
+            circuit_breaker: None,
+            middleware: Vec::new(),
+        })
+    }
+
+    /// Sets the language used for localized titles and fields
+    ///
+    /// The language is forwarded to the server as the `X-Plex-Language` header
+    /// on every request.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use plex_client::client::PlexClient;
+    ///
+    /// let client = PlexClient::new(
+    ///     "http://192.168.1.100:32400".to_string(),
+    ///     "your-token-here".to_string(),
+    /// )?
+    /// .with_language("en-US");
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// Sets the connection pool and keep-alive settings of the HTTP client
+    ///
+    /// The HTTP client is rebuilt, so call this before cloning the client.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the HTTP client cannot be created (e.g. no TLS backend is available).
+    pub fn with_connection_settings(mut self, settings: ConnectionSettings) -> Self {
+        self.client = http_client(&settings);
+        self
+    }
+
+    /// Sets credentials for a reverse proxy in front of the server
+    ///
+    /// They are sent in the `Authorization` header of every request, in
+    /// addition to the Plex token.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use plex_client::client::{PlexClient, ProxyAuth};
+    ///
+    /// let client = PlexClient::new(
+    ///     "https://plex.example.com".to_string(),
+    ///     "your-token-here".to_string(),
+    /// )?
+    /// .with_proxy_auth(ProxyAuth::Basic {
+    ///     username: "harriet".to_string(),
+    ///     password: "secret".to_string(),
+    /// });
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn with_proxy_auth(mut self, proxy_auth: ProxyAuth) -> Self {
+        self.proxy_auth = Some(proxy_auth);
+        self
+    }
+
+    /// Saves every response to a directory, or replays saved responses instead of requesting the server
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use plex_client::client::{PlexClient, ResponseCache};
+    ///
+    /// // Serve requests from the responses saved by an earlier run
+    /// let client = PlexClient::new(
+    ///     "http://192.168.1.100:32400".to_string(),
+    ///     "your-token-here".to_string(),
+    /// )?
+    /// .with_response_cache(ResponseCache::replay("responses"));
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn with_response_cache(mut self, response_cache: ResponseCache) -> Self {
+        self.response_cache = Some(response_cache);
+        self
+    }
+
+    /// Writes every raw response body to a directory, to attach to bug reports
+    ///
+    /// The client's token is redacted from the saved bodies, as are any
+    /// secrets added to the capture with `ResponseCapture::with_secret`.
+    pub fn with_response_capture(mut self, response_capture: ResponseCapture) -> Self {
+        self.response_capture = Some(response_capture.with_secret(self.token.clone()));
+        self
+    }
+
+    /// Stops sending requests for a while after repeated consecutive failures
+    ///
+    /// Requests that get no response or a 5xx response count as failures.
+    /// See [`CircuitBreaker`] for how the circuit opens and closes.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use plex_client::client::{CircuitBreaker, PlexClient};
+    ///
+    /// // Pause for a minute after 5 failed requests in a row
+    /// let client = PlexClient::new(
+    ///     "http://192.168.1.100:32400".to_string(),
+    ///     "your-token-here".to_string(),
+    /// )?
+    /// .with_circuit_breaker(CircuitBreaker::new(5, Duration::from_secs(60)));
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn with_circuit_breaker(mut self, circuit_breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
+    /// Adds hooks called around every request sent to the server
+    ///
+    /// See [`Middleware`] for an example. Pass an `Arc` to keep a handle on
+    /// the middleware, e.g. to read the metrics it collects.
+    pub fn with_middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Sets how many times a failed watch history page is retried
+    ///
+    /// A page whose request fails is retried with a growing delay before the
+    /// error is yielded by `WatchHistoryIterator`. Defaults to 2; use 0 to
+    /// surface errors immediately.
+    pub fn with_page_retries(mut self, retries: u32) -> Self {
+        self.page_retries = retries;
+        self
+    }
+
+    /// Sets the account whose watch history is requested
+    ///
+    /// By default the account is resolved from the token with
+    /// `resolve_account_id` before the first watch history request.
+    pub fn with_account_id(mut self, account_id: impl Into<String>) -> Self {
+        self.account_id = OnceLock::from(account_id.into());
+        self
+    }
+
+    /// Finds the server account of the user the token belongs to
+    ///
+    /// The plex.tv username reported by `/myplex/account` is looked up among
+    /// the server's `/accounts`. This fails for tokens that cannot list the
+    /// accounts (e.g. those of shared users) or when the server is not
+    /// signed in to plex.tv.
+    pub fn resolve_account_id(&self) -> Result<String> {
+        let my_plex = self.get_myplex_account()?;
+        let accounts = self.get_accounts()?;
+        find_account_id(&accounts, &my_plex).ok_or_else(|| no_account_error(&my_plex))
+    }
+
+    /// Returns the account whose watch history is requested
+    ///
+    /// The account is resolved once, like `resolve_account_id`, falling back
+    /// with a warning to account 1 (the owner on most servers) when no
+    /// account matches the token's user. Failing to get the accounts is an
+    /// error.
+    pub fn account_id(&self) -> Result<&str> {
+        if let Some(account_id) = self.account_id.get() {
+            return Ok(account_id);
+        }
+        let my_plex = self.get_myplex_account()?;
+        let accounts = self.get_accounts()?;
+        Ok(self
+            .account_id
+            .get_or_init(|| account_id_or_owner(&accounts, &my_plex)))
+    }
+
+    /// Gets the local accounts of the server
+    pub fn get_accounts(&self) -> Result<PlexAccounts> {
+        let container: MediaContainer<PlexAccounts> = self
+            .get_media_container(Endpoint::Accounts, &Query::new())
+            .context("Failed to get server accounts")?;
+        Ok(container.into_inner())
+    }
+
+    /// Gets the devices (players) that played media from the server
+    pub fn get_devices(&self) -> Result<PlexDevices> {
+        let container: MediaContainer<PlexDevices> = self
+            .get_media_container(Endpoint::Devices, &Query::new())
+            .context("Failed to get server devices")?;
+        Ok(container.into_inner())
+    }
+
+    /// Gets the plex.tv account the token belongs to
+    pub fn get_myplex_account(&self) -> Result<PlexMyPlexAccount> {
+        let response: PlexMyPlexResponse = self
+            .get_json(Endpoint::MyPlexAccount, &Query::new())
+            .context("Failed to get plex.tv account")?;
+        Ok(response.my_plex)
+    }
+
+    /// Returns an iterator over watch history items with automatic pagination
+    ///
+    /// This method returns an iterator that automatically handles pagination,
+    /// fetching 100 items per request. The iterator yields only `PlexWatchHistoryItem`
+    /// values, not the metadata wrapper.
+    ///
+    /// # Arguments
+    ///
+    /// * `library_section_id` - The library section ID to filter watch history by
+    ///
+    /// # Returns
+    ///
+    /// An iterator that yields `PlexWatchHistoryItem` values. The iterator
+    /// automatically fetches additional pages as needed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use plex_client::client::PlexClient;
+    ///
+    /// # let (url, token) = (String::new(), String::new());
+    /// let client = PlexClient::new(url, token)?;
+    ///
+    /// for item in client.watch_history_iter("1") {
+    ///     let item = item?;
+    ///     println!("Watched: {} at {:?}", item.title, item.viewed_at);
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn watch_history_iter(&self, library_section_id: &str) -> WatchHistoryIterator<'_> {
+        WatchHistoryIterator::new(
+            self,
+            HistoryScope::LibrarySection(library_section_id.to_string()),
+        )
+    }
+
+    /// Returns an iterator over the viewings of a single item
+    ///
+    /// The server filters the history by rating key, so only the pages with
+    /// the item's own viewings are fetched, rather than the whole history.
+    ///
+    /// # Arguments
+    ///
+    /// * `rating_key` - The rating key of the item (e.g., "12345")
+    pub fn item_watch_history_iter(&self, rating_key: &str) -> WatchHistoryIterator<'_> {
+        WatchHistoryIterator::new(self, HistoryScope::Item(rating_key.to_string()))
+    }
+
+    pub fn get_media_item_metadata(&self, rating_key: String) -> Result<PlexMediaItem> {
+        let container: MediaContainer<PlexMediaItem> = self
+            .get_media_container(Endpoint::Metadata(&rating_key), &Query::new())
+            .context("Failed to get media item metadata")?;
+        Ok(container.into_inner())
+    }
+
+    pub fn get_library_sections(&self) -> Result<PlexLibrarySection> {
+        let container: MediaContainer<PlexLibrarySection> = self
+            .get_media_container(Endpoint::LibrarySections, &Query::new())
+            .context("Failed to get library sections")?;
+        Ok(container.into_inner())
+    }
+
+    /// Gets every movie in a library section, watched or not
+    ///
+    /// # Arguments
+    ///
+    /// * `library_section_id` - The library section ID to list
+    pub fn get_library_items(&self, library_section_id: &str) -> Result<PlexLibraryItems> {
+        let mut query = Query::new().param("type", "1");
+        if self.capabilities().include_guids {
+            query = query.param("includeGuids", "1");
+        }
+        let container: MediaContainer<PlexLibraryItems> = self
+            .get_media_container(Endpoint::LibrarySectionItems(library_section_id), &query)
+            .context("Failed to get library items")?;
+        Ok(container.into_inner())
+    }
+
+    /// Searches the movies of a library section with the server's own search
+    ///
+    /// The server matches the query against titles (including original and
+    /// sort titles), so it can find films whose history entry is titled
+    /// differently from the query.
+    ///
+    /// # Arguments
+    ///
+    /// * `library_section_id` - The library section ID to search
+    /// * `query` - The text to search for (e.g., "blade runner")
+    pub fn search_library(
+        &self,
+        library_section_id: &str,
+        query: &str,
+    ) -> Result<PlexLibraryItems> {
+        let mut query = Query::new().param("type", "1").param("query", query);
+        if self.capabilities().include_guids {
+            query = query.param("includeGuids", "1");
+        }
+        let container: MediaContainer<PlexLibraryItems> = self
+            .get_media_container(Endpoint::LibrarySectionSearch(library_section_id), &query)
+            .context("Failed to search library")?;
+        Ok(container.into_inner())
+    }
+
+    /// Gets the identity of the Plex server (machine identifier and version)
+    pub fn get_server_identity(&self) -> Result<PlexServerIdentity> {
+        let container: MediaContainer<PlexServerIdentity> = self
+            .get_media_container(Endpoint::Identity, &Query::new())
+            .context("Failed to get server identity")?;
+        Ok(container.into_inner())
+    }
+
+    /// Checks that the server is supported and records its features
+    ///
+    /// The server identity is fetched and its version compared with
+    /// `MINIMUM_SERVER_VERSION`, failing with an explanation when the server
+    /// is older. Requests then adapt to the features of that version (e.g.
+    /// `includeGuids` on library listings). Without this check the server is
+    /// assumed to be current.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use plex_client::client::PlexClient;
+    ///
+    /// # let (url, token) = (String::new(), String::new());
+    /// let client = PlexClient::new(url, token)?;
+    /// let identity = client.check_server_version()?;
+    /// println!("Connected to Plex Media Server {}", identity.version);
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn check_server_version(&self) -> Result<PlexServerIdentity> {
+        let identity = self.get_server_identity()?;
+        let capabilities = server_capabilities(&identity)?;
+        // A concurrent check of the same server records the same features
+        let _ = self.capabilities.set(capabilities);
+        Ok(identity)
+    }
+
+    /// Returns the features of the server recorded by `check_server_version`
+    ///
+    /// Those of a current server are returned when the version was not checked.
+    pub fn capabilities(&self) -> ServerCapabilities {
+        self.capabilities.get().copied().unwrap_or_default()
+    }
+
+    /// Gets the base URL of the Plex server
+    ///
+    /// The URL is normalized, without a trailing slash (e.g. "http://192.168.1.100:32400").
+    pub fn base_url(&self) -> &str {
+        self.base_url.as_str().trim_end_matches('/')
+    }
+
+    /// Makes a generic API request that returns a MediaContainer response
+    ///
+    /// This is a helper method for making Plex API requests that return
+    /// responses wrapped in a MediaContainer. The generic type `T` allows
+    /// you to specify the inner response type.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The API endpoint (e.g., `Endpoint::LibrarySections`)
+    /// * `query` - Query parameters (e.g., `Query::new().param("sort", "title")`)
+    ///
+    /// # Type Parameters
+    ///
+    /// * `T` - The type of the inner MediaContainer data
+    ///
+    /// Note: The entire response (including the MediaContainer wrapper) is deserialized
+    /// as `MediaContainer<T>`. The type `T` represents the inner data structure.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(MediaContainer<T>)` - The parsed response wrapped in MediaContainer
+    /// * `Err` - If the request fails or parsing fails
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use plex_client::client::{Endpoint, MediaContainer, PlexClient, Query};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// #[serde(rename_all = "PascalCase")]
+    /// struct LibrarySection {
+    ///     pub key: String,
+    ///     pub title: String,
+    /// }
+    ///
+    /// # let (url, token) = (String::new(), String::new());
+    /// let client = PlexClient::new(url, token)?;
+    ///
+    /// // Without query parameters
+    /// let response: MediaContainer<LibrarySection> =
+    ///     client.get_media_container(Endpoint::LibrarySections, &Query::new())?;
+    ///
+    /// // With query parameters
+    /// let query = Query::new().param("limit", "10").param("sort", "title");
+    /// let response: MediaContainer<LibrarySection> =
+    ///     client.get_media_container(Endpoint::LibrarySections, &query)?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn get_media_container<T>(
+        &self,
+        endpoint: Endpoint<'_>,
+        query: &Query,
+    ) -> Result<MediaContainer<T>>
+    where
+        MediaContainer<T>: for<'de> Deserialize<'de>,
+    {
+        self.get_json(endpoint, query)
+    }
+
+    /// Makes a generic API request and parses the JSON response
+    ///
+    /// Used directly for the few endpoints whose response is not wrapped in a
+    /// `MediaContainer`.
+    fn get_json<T>(&self, endpoint: Endpoint<'_>, query: &Query) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        // Build the full URL, percent-encoding path parameters and the query
+        let url = endpoint.url(&self.base_url, query);
+        if let Some(body) = self.replay(&url, None)? {
+            return parse_response(&body, None).context(format!(
+                "Failed to parse saved response from endpoint: {}",
+                endpoint
+            ));
+        }
+        let request = self.request(url.clone());
+
+        // Send the request
+        let response = self
+            .send(&url, request)
+            .context(format!("Failed to send request to endpoint: {}", endpoint))?;
+
+        // Check for HTTP errors
+        check_token(response.status(), &endpoint.to_string())?;
+        let response = response.error_for_status().context(format!(
+            "Plex server returned an error for endpoint: {}",
+            endpoint
+        ))?;
+
+        // Parse the entire JSON response; for MediaContainer responses the
+        // wrapper is part of `T`, so the whole response is deserialized
+        self.parse_body(response, &url, None).context(format!(
+            "Failed to parse response from endpoint: {}",
+            endpoint
+        ))
+    }
+
+    /// Sends a request, through the circuit breaker and middleware
+    fn send(&self, url: &Url, mut request: RequestBuilder) -> Result<Response> {
+        if let Some(breaker) = &self.circuit_breaker {
+            breaker.before_request()?;
+        }
+        if !self.middleware.is_empty() {
+            request = request.headers(middleware_headers(&self.middleware, url));
+        }
+
+        let started = Instant::now();
+        let response = send_with_retry_after(request);
+        let status = response.as_ref().ok().map(Response::status);
+        for middleware in &self.middleware {
+            middleware.on_response(url, status, started.elapsed());
+        }
+        if let Some(breaker) = &self.circuit_breaker {
+            breaker.record(status.is_some_and(|status| !status.is_server_error()));
+        }
+        Ok(response?)
+    }
+
+    /// Builds a GET request carrying the headers shared by every Plex API call
+    fn request(&self, url: Url) -> RequestBuilder {
+        let request = self
+            .client
+            .get(url)
+            .header("X-Plex-Token", &self.token)
+            .header("Accept", "application/json");
+
+        let request = match &self.language {
+            Some(language) => request.header("X-Plex-Language", language),
+            None => request,
+        };
+
+        match &self.proxy_auth {
+            Some(ProxyAuth::Basic { username, password }) => {
+                request.basic_auth(username, Some(password))
+            }
+            Some(ProxyAuth::Bearer(token)) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    /// Returns the saved response of a request when offline
+    ///
+    /// `page` is the offset and size of a page requested through headers.
+    fn replay(&self, url: &Url, page: Option<(u32, u32)>) -> Result<Option<String>> {
+        match &self.response_cache {
+            Some(cache) if cache.is_offline() => cache.load(url, page).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Reads a response body and parses it as JSON, or as XML when the server sent XML
+    ///
+    /// The body is saved to the response cache and capture first, if any, so
+    /// that it can be replayed or shared even when it fails to parse.
+    fn parse_body<T>(&self, response: Response, url: &Url, page: Option<(u32, u32)>) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let body = response.text().context("Failed to read response body")?;
+        if let Some(cache) = &self.response_cache {
+            cache.save(url, page, &body)?;
+        }
+        if let Some(capture) = &self.response_capture {
+            capture.save(url, page, content_type.as_deref(), &body)?;
+        }
+        parse_response(&body, content_type.as_deref())
+    }
+
+    /// Makes a paginated API request for watch history with headers
+    ///
+    /// This is a specialized method for watch history that uses HTTP headers
+    /// for pagination instead of query parameters, as required by the Plex API,
+    /// except on servers too old to honor them.
+    fn get_watch_history_page(
+        &self,
+        offset: u32,
+        page_size: u32,
+        scope: &HistoryScope,
+    ) -> Result<MediaContainer<PlexWatchHistory>> {
+        let (scope_key, scope_value) = scope.param();
+        let mut query = Query::new()
+            .param("sort", "viewedAt:desc")
+            .param(scope_key, scope_value)
+            .param("accountID", self.account_id()?);
+
+        // Convert to strings for headers
+        let offset_str = offset.to_string();
+        let page_size_str = page_size.to_string();
+
+        // Build the request with pagination headers, or pagination query
+        // parameters for older servers that ignore the headers
+        let (url, page) = if self.capabilities().header_pagination {
+            let url = Endpoint::WatchHistory.url(&self.base_url, &query);
+            (url, Some((offset, page_size)))
+        } else {
+            query = query
+                .param("X-Plex-Container-Start", offset_str.as_str())
+                .param("X-Plex-Container-Size", page_size_str.as_str());
+            (Endpoint::WatchHistory.url(&self.base_url, &query), None)
+        };
+        if let Some(body) = self.replay(&url, page)? {
+            return parse_response(&body, None)
+                .context("Failed to parse saved watch history pagination response");
+        }
+        let mut request = self.request(url.clone());
+        if page.is_some() {
+            request = request
+                .header("X-Plex-Container-Start", &offset_str)
+                .header("X-Plex-Container-Size", &page_size_str);
+        }
+
+        // Send the request
+        let response = self
+            .send(&url, request)
+            .context("Failed to send watch history pagination request")?;
+
+        // Check for HTTP errors
+        check_token(response.status(), &Endpoint::WatchHistory.to_string())?;
+        let response = response
+            .error_for_status()
+            .context("Plex server returned an error for watch history pagination request")?;
+
+        // Parse the response
+        let container: MediaContainer<PlexWatchHistory> = self
+            .parse_body(response, &url, page)
+            .context("Failed to parse watch history pagination response")?;
+
+        Ok(container)
+    }
+}
+
+/// Viewings a watch history request is restricted to
+#[derive(Debug, Clone)]
+enum HistoryScope {
+    /// Viewings of the items of a library section, by section ID
+    LibrarySection(String),
+    /// Viewings of a single item, by rating key
+    Item(String),
+}
+
+impl HistoryScope {
+    /// Returns the query parameter restricting the history to the scope
+    fn param(&self) -> (&'static str, &str) {
+        match self {
+            HistoryScope::LibrarySection(section_id) => ("librarySectionID", section_id),
+            HistoryScope::Item(rating_key) => ("metadataItemID", rating_key),
+        }
+    }
+}
+
+/// Creates the HTTP client with the given connection settings
+fn http_client(settings: &ConnectionSettings) -> Client {
+    // History pages are large JSON documents that compress well
+    let mut builder = Client::builder()
+        .gzip(true)
+        .brotli(true)
+        .redirect(redirect_policy(settings))
+        .pool_max_idle_per_host(settings.pool_max_idle_per_host.unwrap_or(usize::MAX))
+        .tcp_keepalive(settings.tcp_keepalive);
+    if let Some(timeout) = settings.pool_idle_timeout {
+        builder = builder.pool_idle_timeout(timeout);
+    }
+    builder.build().expect("Failed to create the HTTP client")
+}
+
+// Sharing the client across threads is part of its public contract
+const _: () = {
+    fn assert_shareable<T: Clone + Send + Sync>() {}
+    let _ = assert_shareable::<PlexClient>;
+};
+
+/// Iterator over watch history items with automatic pagination
+///
+/// This iterator automatically handles pagination by fetching 100 items per request.
+/// It yields only `PlexWatchHistoryItem` values, not the metadata wrapper.
+///
+/// A page that fails to load is retried as configured with
+/// `PlexClient::with_page_retries` before the error is yielded. The iterator
+/// is not poisoned by an error: calling `next` again retries the same page,
+/// so no items are skipped or repeated.
+pub struct WatchHistoryIterator<'a> {
+    client: &'a PlexClient,
+    scope: HistoryScope,
+    current_items: Vec<PlexWatchHistoryItem>,
+    current_index: usize,
+    offset: u32,
+    page_size: u32,
+    is_last_page: bool,
+}
+
+impl<'a> WatchHistoryIterator<'a> {
+    fn new(client: &'a PlexClient, scope: HistoryScope) -> Self {
+        Self {
+            client,
+            scope,
+            current_items: Vec::new(),
+            current_index: 0,
+            offset: 0,
+            page_size: PAGE_SIZE,
+            is_last_page: false,
+        }
+    }
+
+    fn fetch_next_page(&mut self) -> Result<bool> {
+        // If we've already determined this is the last page, don't fetch again
+        if self.is_last_page {
+            return Ok(false);
+        }
+
+        // Fetch the page, retrying transient failures. The offset is only
+        // advanced on success, so a failed page is fetched again on the next call
+        let container: MediaContainer<PlexWatchHistory> =
+            self.fetch_page_with_retries().with_context(|| {
+                format!(
+                    "Failed to fetch watch history page at offset {}",
+                    self.offset
+                )
+            })?;
+
+        let history = container.into_inner();
+
+        // If we got no items, we're done
+        if history.metadata.is_empty() {
+            return Ok(false);
+        }
+
+        // Update current items and reset index
+        self.current_items = history.metadata;
+        self.current_index = 0;
+
+        // Check if we received fewer items than requested - this means it's the last page
+        let items_received = self.current_items.len() as u32;
+        if items_received < self.page_size {
+            self.is_last_page = true;
+        }
+
+        // Update offset for next fetch
+        self.offset += items_received;
+
+        // We successfully fetched a page with items
+        Ok(true)
+    }
+
+    /// Fetches the page at the current offset, retrying with a growing delay
+    fn fetch_page_with_retries(&self) -> Result<MediaContainer<PlexWatchHistory>> {
+        let mut attempt = 0;
+        loop {
+            match self
+                .client
+                .get_watch_history_page(self.offset, self.page_size, &self.scope)
+            {
+                Ok(container) => return Ok(container),
+                // A rejected token will not be accepted on a retry
+                Err(e) if e.is::<TokenRejectedError>() => return Err(e),
+                // Retrying would only fail again until the circuit closes
+                Err(e) if e.is::<CircuitOpenError>() => return Err(e),
+                Err(_) if attempt < self.client.page_retries => {
+                    attempt += 1;
+                    thread::sleep(Duration::from_secs(u64::from(attempt)));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for WatchHistoryIterator<'a> {
+    type Item = Result<PlexWatchHistoryItem>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // If we've exhausted the current page, fetch the next one
+        if self.current_index >= self.current_items.len() {
+            match self.fetch_next_page() {
+                Ok(true) => {
+                    // Successfully fetched a new page, continue
+                }
+                Ok(false) => {
+                    // No more pages available
+                    return None;
+                }
+                Err(e) => {
+                    // Error fetching page
+                    return Some(Err(e));
+                }
+            }
+        }
+
+        // If we still have no items after fetching, we're done
+        if self.current_items.is_empty() {
+            return None;
+        }
+
+        // Get the next item and increment index
+        let item = self.current_items[self.current_index].clone();
+        self.current_index += 1;
+
+        Some(Ok(item))
+    }
+}
@@ -0,0 +1,149 @@
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Stops sending requests to a server after repeated consecutive failures
+///
+/// Every request that fails to get a response, or gets a 5xx response,
+/// counts as a failure; any other response resets the count. Once
+/// `threshold` consecutive requests have failed, the circuit opens for the
+/// cool-down period: requests wait until it is over (or, when failing fast,
+/// fail right away with a [`CircuitOpenError`]) instead of adding load to a
+/// struggling server. The next failure after a cool-down opens it again.
+///
+/// Clones share their state, so every clone of a client is counted together.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+/// use plex_client::client::{CircuitBreaker, CircuitOpenError};
+///
+/// let breaker = CircuitBreaker::new(2, Duration::from_secs(60)).failing_fast();
+/// breaker.record(false);
+/// assert!(breaker.before_request().is_ok());
+///
+/// breaker.record(false);
+/// let error = breaker.before_request().unwrap_err();
+/// assert_eq!(error.failures, 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    /// Consecutive failures that open the circuit
+    threshold: u32,
+    /// How long the circuit stays open
+    cool_down: Duration,
+    /// Whether requests fail while the circuit is open, instead of waiting
+    fail_fast: bool,
+    /// Failures and opening time, shared between clones
+    state: Arc<Mutex<BreakerState>>,
+}
+
+/// Mutable state of a circuit breaker
+#[derive(Debug, Default)]
+struct BreakerState {
+    /// Requests that failed in a row
+    consecutive_failures: u32,
+    /// When the open circuit closes again, if it is open
+    open_until: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    /// Opens the circuit for `cool_down` after `threshold` consecutive failures
+    ///
+    /// A threshold of 0 is treated as 1.
+    pub fn new(threshold: u32, cool_down: Duration) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            cool_down,
+            fail_fast: false,
+            state: Arc::new(Mutex::new(BreakerState::default())),
+        }
+    }
+
+    /// Fails requests while the circuit is open, instead of waiting for the cool-down
+    ///
+    /// Used by long-running callers that would rather give up on the current
+    /// run and try again later, such as a daemon between sync cycles.
+    pub fn failing_fast(mut self) -> Self {
+        self.fail_fast = true;
+        self
+    }
+
+    /// Returns whether the circuit is currently open
+    pub fn is_open(&self) -> bool {
+        self.state()
+            .open_until
+            .is_some_and(|open_until| Instant::now() < open_until)
+    }
+
+    /// Waits for an open circuit to close, or fails when failing fast
+    pub fn before_request(&self) -> Result<(), CircuitOpenError> {
+        let (failures, open_until) = {
+            let state = self.state();
+            match state.open_until {
+                Some(open_until) => (state.consecutive_failures, open_until),
+                None => return Ok(()),
+            }
+        };
+
+        let retry_in = open_until.saturating_duration_since(Instant::now());
+        if retry_in.is_zero() {
+            return Ok(());
+        }
+        if self.fail_fast {
+            return Err(CircuitOpenError { failures, retry_in });
+        }
+        tracing::warn!(
+            failures,
+            retry_in_secs = retry_in.as_secs().max(1),
+            "consecutive requests failed; pausing before contacting the server again"
+        );
+        thread::sleep(retry_in);
+        Ok(())
+    }
+
+    /// Records the outcome of a request
+    pub fn record(&self, success: bool) {
+        let mut state = self.state();
+        if success {
+            *state = BreakerState::default();
+            return;
+        }
+        state.consecutive_failures += 1;
+        if state.consecutive_failures >= self.threshold {
+            state.open_until = Some(Instant::now() + self.cool_down);
+        }
+    }
+
+    /// Locks the state, which stays consistent even if a holder panicked
+    fn state(&self) -> std::sync::MutexGuard<'_, BreakerState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Error returned for requests made while a failing-fast circuit is open
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircuitOpenError {
+    /// Requests that failed in a row before the circuit opened
+    pub failures: u32,
+    /// Time left until requests are sent again
+    pub retry_in: Duration,
+}
+
+impl fmt::Display for CircuitOpenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} consecutive requests to the server failed; not contacting it for another {}s",
+            self.failures,
+            self.retry_in.as_secs().max(1)
+        )
+    }
+}
+
+impl Error for CircuitOpenError {}
@@ -0,0 +1,123 @@
+use std::fmt;
+
+use url::Url;
+
+/// An endpoint of the Plex Media Server API
+///
+/// Path parameters such as rating keys are percent-encoded when the endpoint
+/// is joined onto the server URL, so values containing `/`, `?` or spaces
+/// cannot change which endpoint is requested.
+///
+/// # Example
+///
+/// ```rust
+/// use plex_client::client::{Endpoint, Query};
+/// use url::Url;
+///
+/// let base_url = Url::parse("http://192.168.1.100:32400/")?;
+/// let url = Endpoint::Metadata("12/34").url(&base_url, &Query::new());
+/// assert_eq!(url.as_str(), "http://192.168.1.100:32400/library/metadata/12%2F34");
+///
+/// let query = Query::new().param("title", "Tom & Jerry");
+/// let url = Endpoint::LibrarySectionItems("1").url(&base_url, &query);
+/// assert_eq!(
+///     url.as_str(),
+///     "http://192.168.1.100:32400/library/sections/1/all?title=Tom+%26+Jerry"
+/// );
+/// # Ok::<(), url::ParseError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endpoint<'a> {
+    /// Identity of the server (`/identity`)
+    Identity,
+    /// Local accounts of the server (`/accounts`)
+    Accounts,
+    /// Devices that played media from the server (`/devices`)
+    Devices,
+    /// plex.tv account the token belongs to (`/myplex/account`)
+    MyPlexAccount,
+    /// Library sections of the server (`/library/sections`)
+    LibrarySections,
+    /// Every item of a library section, by section ID (`/library/sections/{id}/all`)
+    LibrarySectionItems(&'a str),
+    /// Search of a library section, by section ID (`/library/sections/{id}/search`)
+    LibrarySectionSearch(&'a str),
+    /// Metadata of a media item, by rating key (`/library/metadata/{rating_key}`)
+    Metadata(&'a str),
+    /// Watch history of the server (`/status/sessions/history/all`)
+    WatchHistory,
+}
+
+impl Endpoint<'_> {
+    /// Returns the path segments of the endpoint, before percent-encoding
+    fn segments(&self) -> Vec<&str> {
+        match self {
+            Endpoint::Identity => vec!["identity"],
+            Endpoint::Accounts => vec!["accounts"],
+            Endpoint::Devices => vec!["devices"],
+            Endpoint::MyPlexAccount => vec!["myplex", "account"],
+            Endpoint::LibrarySections => vec!["library", "sections"],
+            Endpoint::LibrarySectionItems(section_id) => {
+                vec!["library", "sections", section_id, "all"]
+            }
+            Endpoint::LibrarySectionSearch(section_id) => {
+                vec!["library", "sections", section_id, "search"]
+            }
+            Endpoint::Metadata(rating_key) => vec!["library", "metadata", rating_key],
+            Endpoint::WatchHistory => vec!["status", "sessions", "history", "all"],
+        }
+    }
+
+    /// Returns the URL of the endpoint on a server, with the given query
+    ///
+    /// The endpoint is appended to the path of `base_url`, so servers served
+    /// under a base path (e.g. "https://example.com/plex/") are supported.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `base_url` cannot be a base (e.g. "mailto:" URLs), which
+    /// the clients rule out when they are created.
+    pub fn url(&self, base_url: &Url, query: &Query) -> Url {
+        let mut url = base_url.clone();
+        url.path_segments_mut()
+            .expect("server URLs can be a base")
+            .pop_if_empty()
+            .extend(self.segments());
+        if !query.params.is_empty() {
+            url.query_pairs_mut().extend_pairs(&query.params);
+        }
+        url
+    }
+}
+
+impl fmt::Display for Endpoint<'_> {
+    /// Formats the endpoint as its unencoded path (e.g. "/library/sections")
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for segment in self.segments() {
+            write!(f, "/{}", segment)?;
+        }
+        Ok(())
+    }
+}
+
+/// Query string parameters of a Plex API request
+///
+/// Values are percent-encoded when the query is added to an endpoint URL.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query {
+    /// Parameters, in the order they were added
+    params: Vec<(String, String)>,
+}
+
+impl Query {
+    /// Creates an empty query
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a parameter to the query
+    pub fn param(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.params.push((key.into(), value.into()));
+        self
+    }
+}
@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use url::Url;
+
+/// Hooks called around every request a Plex client sends to the server
+///
+/// Added with `PlexClient::with_middleware` or
+/// `AsyncPlexClient::with_middleware`, to log requests, collect metrics or
+/// add authentication headers without changing the client. Middleware runs
+/// in the order it was added, and is shared between clones of the client,
+/// so it may be called from several threads at once.
+///
+/// Responses served from a response cache in replay mode are not requests
+/// and do not call the hooks.
+///
+/// # Example
+///
+/// ```rust
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// use plex_client::client::{Middleware, PlexClient};
+/// use reqwest::header::{HeaderMap, HeaderValue};
+/// use reqwest::StatusCode;
+/// use url::Url;
+///
+/// /// Counts failed requests and tags every request with a client name
+/// #[derive(Default)]
+/// struct Metrics {
+///     failures: AtomicUsize,
+/// }
+///
+/// impl Middleware for Metrics {
+///     fn on_request(&self, _url: &Url, headers: &mut HeaderMap) {
+///         headers.insert("X-Plex-Product", HeaderValue::from_static("my-app"));
+///     }
+///
+///     fn on_response(&self, url: &Url, status: Option<StatusCode>, elapsed: Duration) {
+///         if !status.is_some_and(|status| status.is_success()) {
+///             self.failures.fetch_add(1, Ordering::Relaxed);
+///             eprintln!("{} failed after {:?}", url.path(), elapsed);
+///         }
+///     }
+/// }
+///
+/// let metrics = Arc::new(Metrics::default());
+/// let client = PlexClient::new(
+///     "http://192.168.1.100:32400".to_string(),
+///     "your-token-here".to_string(),
+/// )?
+/// .with_middleware(Arc::clone(&metrics));
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub trait Middleware: Send + Sync {
+    /// Called before a request is sent
+    ///
+    /// Headers inserted into `headers` are added to the request, replacing
+    /// any the client set under the same name (such as `Authorization`).
+    fn on_request(&self, url: &Url, headers: &mut HeaderMap) {
+        let _ = (url, headers);
+    }
+
+    /// Called once a request is answered, or failed without a response
+    ///
+    /// `status` is `None` when no response was received (e.g. the connection
+    /// was refused or timed out), and `elapsed` includes any wait for a
+    /// `Retry-After` header.
+    fn on_response(&self, url: &Url, status: Option<StatusCode>, elapsed: Duration) {
+        let _ = (url, status, elapsed);
+    }
+}
+
+impl<T: Middleware + ?Sized> Middleware for std::sync::Arc<T> {
+    fn on_request(&self, url: &Url, headers: &mut HeaderMap) {
+        (**self).on_request(url, headers);
+    }
+
+    fn on_response(&self, url: &Url, status: Option<StatusCode>, elapsed: Duration) {
+        (**self).on_response(url, status, elapsed);
+    }
+}
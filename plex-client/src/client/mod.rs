@@ -0,0 +1,623 @@
+//! Plex Media Server API clients
+//!
+//! The blocking client (`PlexClient`) is enabled by the default `blocking`
+//! feature. The async client (`AsyncPlexClient`) is enabled by the `async`
+//! feature, for consumers that already run inside a tokio application.
+
+use std::fmt;
+use std::time::Duration;
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+use anyhow::{Context, Result};
+#[cfg(any(feature = "blocking", feature = "async"))]
+use reqwest::StatusCode;
+use serde::Deserialize;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use url::Url;
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+use crate::identity::{ServerCapabilities, MINIMUM_SERVER_VERSION};
+
+#[cfg(feature = "blocking")]
+mod blocking;
+#[cfg(feature = "blocking")]
+mod circuit_breaker;
+mod endpoint;
+#[cfg(any(feature = "blocking", feature = "async"))]
+mod middleware;
+#[cfg(feature = "async")]
+mod nonblocking;
+#[cfg(feature = "blocking")]
+mod response_cache;
+mod xml;
+
+#[cfg(feature = "blocking")]
+pub use blocking::{PlexClient, WatchHistoryIterator};
+#[cfg(feature = "blocking")]
+pub use circuit_breaker::{CircuitBreaker, CircuitOpenError};
+pub use endpoint::{Endpoint, Query};
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub use middleware::Middleware;
+#[cfg(feature = "async")]
+pub use nonblocking::AsyncPlexClient;
+#[cfg(feature = "blocking")]
+pub use response_cache::{ResponseCache, ResponseCapture};
+
+/// Number of watch history items requested per page
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub(crate) const PAGE_SIZE: u32 = 100;
+
+/// Number of times a failed watch history page is retried by default
+#[cfg(feature = "blocking")]
+pub(crate) const DEFAULT_PAGE_RETRIES: u32 = 2;
+
+/// Account whose watch history is requested when the token's account cannot be resolved
+///
+/// This is the ID of the server owner on most servers.
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub(crate) const ACCOUNT_ID: &str = "1";
+
+/// Times a request answered with 429 Too Many Requests is sent again
+#[cfg(any(feature = "blocking", feature = "async"))]
+const RATE_LIMIT_RETRIES: u32 = 3;
+
+/// Delay before resending a 429 response that has no usable Retry-After header
+#[cfg(any(feature = "blocking", feature = "async"))]
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(5);
+
+/// Longest Retry-After delay waited for, so a bogus header cannot stall a run
+#[cfg(any(feature = "blocking", feature = "async"))]
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(300);
+
+/// Collects the headers the middleware of a client adds to a request
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn middleware_headers(
+    middleware: &[std::sync::Arc<dyn Middleware>],
+    url: &Url,
+) -> reqwest::header::HeaderMap {
+    let mut headers = reqwest::header::HeaderMap::new();
+    for middleware in middleware {
+        middleware.on_request(url, &mut headers);
+    }
+    headers
+}
+
+/// Parses the value of a Retry-After header into the delay it asks for
+///
+/// The header is either a number of seconds or an HTTP date; a date in
+/// the past asks for no delay. Returns `None` for anything else.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+/// use plex_client::client::parse_retry_after;
+///
+/// assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+/// assert_eq!(
+///     parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+///     Some(Duration::ZERO)
+/// );
+/// assert_eq!(parse_retry_after("soon"), None);
+/// ```
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+            .to_std()
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+/// Returns how long to wait before resending a request answered with 429
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn retry_after(headers: &reqwest::header::HeaderMap) -> Duration {
+    headers
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_retry_after)
+        .unwrap_or(DEFAULT_RETRY_AFTER)
+        .min(MAX_RETRY_AFTER)
+}
+
+/// Number of the next request sent, identifying it in tracing spans
+#[cfg(any(feature = "blocking", feature = "async"))]
+static NEXT_REQUEST_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+
+/// Creates the tracing span of a request, if debug events are enabled
+///
+/// The span records the endpoint, the page offset of paginated requests and,
+/// once the request is done, its status and duration. Only the path of the
+/// URL is recorded, since queries can carry API keys (e.g. Tautulli's), and
+/// headers are never recorded.
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn request_span(url: &Url, headers: &reqwest::header::HeaderMap) -> tracing::Span {
+    if !tracing::enabled!(tracing::Level::DEBUG) {
+        return tracing::Span::none();
+    }
+    let offset = headers
+        .get("X-Plex-Container-Start")
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+        .or_else(|| {
+            url.query_pairs()
+                .find(|(key, _)| key == "X-Plex-Container-Start")
+                .map(|(_, value)| value.into_owned())
+        });
+    tracing::debug_span!(
+        "request",
+        id = NEXT_REQUEST_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed),
+        endpoint = url.path(),
+        offset = offset.as_deref(),
+        status = tracing::field::Empty,
+        duration_ms = tracing::field::Empty,
+    )
+}
+
+/// Records the outcome of a request on its span
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn finish_request_span(
+    span: &tracing::Span,
+    outcome: Result<StatusCode, &reqwest::Error>,
+    elapsed: Duration,
+) {
+    span.record("duration_ms", elapsed.as_millis() as u64);
+    match outcome {
+        Ok(status) => {
+            span.record("status", status.as_u16());
+            tracing::debug!(parent: span, "request finished");
+        }
+        Err(error) => {
+            // The error's own message includes the URL, and with it the query
+            let error = std::error::Error::source(error)
+                .map_or_else(|| "no response".to_string(), ToString::to_string);
+            tracing::debug!(parent: span, %error, "request failed");
+        }
+    }
+}
+
+/// Sends a request, waiting and sending it again while it is rate limited
+///
+/// A 429 Too Many Requests response is retried after the delay of its
+/// Retry-After header, up to a few times, independently of any retries of
+/// failed pages. The last response is returned as is, so a request that is
+/// still rate limited fails like any other error status.
+///
+/// The request, including any rate limited attempts, is traced in a debug
+/// span (see `request_span`).
+#[cfg(feature = "blocking")]
+pub(crate) fn send_with_retry_after(
+    request: reqwest::blocking::RequestBuilder,
+) -> reqwest::Result<reqwest::blocking::Response> {
+    let span = request
+        .try_clone()
+        .and_then(|request| request.build().ok())
+        .map_or_else(tracing::Span::none, |request| {
+            request_span(request.url(), request.headers())
+        });
+    let started = std::time::Instant::now();
+    let response = span.in_scope(|| send_rate_limited(request));
+    finish_request_span(
+        &span,
+        response.as_ref().map(|response| response.status()),
+        started.elapsed(),
+    );
+    response
+}
+
+/// Sends a request, resending it while it is answered with 429
+#[cfg(feature = "blocking")]
+fn send_rate_limited(
+    mut request: reqwest::blocking::RequestBuilder,
+) -> reqwest::Result<reqwest::blocking::Response> {
+    for _ in 0..RATE_LIMIT_RETRIES {
+        // Requests with a streaming body cannot be sent twice
+        let Some(retry) = request.try_clone() else {
+            break;
+        };
+        let response = request.send()?;
+        if response.status() != StatusCode::TOO_MANY_REQUESTS {
+            return Ok(response);
+        }
+        let delay = retry_after(response.headers());
+        tracing::debug!(delay_ms = delay.as_millis() as u64, "rate limited");
+        std::thread::sleep(delay);
+        request = retry;
+    }
+    request.send()
+}
+
+/// Sends a request, waiting and sending it again while it is rate limited
+///
+/// See `send_with_retry_after`; the delay is awaited on the tokio timer.
+#[cfg(feature = "async")]
+pub(crate) async fn send_with_retry_after_async(
+    request: reqwest::RequestBuilder,
+) -> reqwest::Result<reqwest::Response> {
+    use tracing::Instrument;
+
+    let span = request
+        .try_clone()
+        .and_then(|request| request.build().ok())
+        .map_or_else(tracing::Span::none, |request| {
+            request_span(request.url(), request.headers())
+        });
+    let started = std::time::Instant::now();
+    let response = send_rate_limited_async(request)
+        .instrument(span.clone())
+        .await;
+    finish_request_span(
+        &span,
+        response.as_ref().map(|response| response.status()),
+        started.elapsed(),
+    );
+    response
+}
+
+/// Sends a request, resending it while it is answered with 429
+#[cfg(feature = "async")]
+async fn send_rate_limited_async(
+    mut request: reqwest::RequestBuilder,
+) -> reqwest::Result<reqwest::Response> {
+    for _ in 0..RATE_LIMIT_RETRIES {
+        let Some(retry) = request.try_clone() else {
+            break;
+        };
+        let response = request.send().await?;
+        if response.status() != StatusCode::TOO_MANY_REQUESTS {
+            return Ok(response);
+        }
+        let delay = retry_after(response.headers());
+        tracing::debug!(delay_ms = delay.as_millis() as u64, "rate limited");
+        tokio::time::sleep(delay).await;
+        request = retry;
+    }
+    request.send().await
+}
+
+/// Parses a server URL, normalized so endpoints can be joined onto it
+///
+/// The path gets a trailing slash so that a base path (e.g. a server served
+/// under "/plex" by a reverse proxy) is kept when endpoints are joined.
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub(crate) fn parse_base_url(base_url: &str) -> Result<Url> {
+    let mut url = Url::parse(base_url.trim()).with_context(|| {
+        format!(
+            "Invalid server URL '{}' (expected e.g. http://192.168.1.100:32400)",
+            base_url
+        )
+    })?;
+    if url.cannot_be_a_base() || !matches!(url.scheme(), "http" | "https") {
+        anyhow::bail!(
+            "Invalid server URL '{}' (expected e.g. http://192.168.1.100:32400)",
+            base_url
+        );
+    }
+
+    if !url.path().ends_with('/') {
+        let path = format!("{}/", url.path());
+        url.set_path(&path);
+    }
+    url.set_query(None);
+    url.set_fragment(None);
+    Ok(url)
+}
+
+/// Finds the server account of the plex.tv user the token belongs to
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub(crate) fn find_account_id(
+    accounts: &crate::account::PlexAccounts,
+    my_plex: &crate::account::PlexMyPlexAccount,
+) -> Option<String> {
+    accounts
+        .account
+        .iter()
+        .find(|account| {
+            !account.name.is_empty() && account.name.eq_ignore_ascii_case(&my_plex.username)
+        })
+        .map(|account| account.id.to_string())
+}
+
+/// Returns the error of a plex.tv user without a server account
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub(crate) fn no_account_error(my_plex: &crate::account::PlexMyPlexAccount) -> anyhow::Error {
+    anyhow::anyhow!(
+        "No server account matches the plex.tv user '{}'",
+        my_plex.username
+    )
+}
+
+/// Finds the server account of the plex.tv user, falling back to account 1
+///
+/// Account 1 is the owner on most servers. Falling back prints a warning,
+/// since the history exported may then belong to someone else.
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub(crate) fn account_id_or_owner(
+    accounts: &crate::account::PlexAccounts,
+    my_plex: &crate::account::PlexMyPlexAccount,
+) -> String {
+    find_account_id(accounts, my_plex).unwrap_or_else(|| {
+        tracing::warn!(
+            account_id = ACCOUNT_ID,
+            "{}; requesting the history of this account",
+            no_account_error(my_plex)
+        );
+        ACCOUNT_ID.to_string()
+    })
+}
+
+/// Returns the features of a server, failing when it is older than supported
+///
+/// Versions in an unknown format are assumed to be current servers.
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub(crate) fn server_capabilities(
+    identity: &crate::identity::PlexServerIdentity,
+) -> Result<ServerCapabilities> {
+    match identity.server_version() {
+        Some(version) if version < MINIMUM_SERVER_VERSION => anyhow::bail!(
+            "Plex Media Server {} is not supported (the minimum version is {}); please update the server",
+            identity.version,
+            MINIMUM_SERVER_VERSION
+        ),
+        Some(version) => Ok(version.capabilities()),
+        None => Ok(ServerCapabilities::default()),
+    }
+}
+
+/// Connection pool and keep-alive settings of the HTTP client
+///
+/// Unset fields keep reqwest's defaults. Over flaky networks, a short
+/// `pool_idle_timeout` and a TCP keep-alive avoid reusing connections that
+/// died silently while idle.
+///
+/// # Example
+///
+/// ```no_run
+/// # #[cfg(feature = "blocking")]
+/// # {
+/// use std::time::Duration;
+/// use plex_client::client::{ConnectionSettings, PlexClient};
+///
+/// let client = PlexClient::new(
+///     "http://192.168.1.100:32400".to_string(),
+///     "your-token-here".to_string(),
+/// )?
+/// .with_connection_settings(ConnectionSettings {
+///     pool_idle_timeout: Some(Duration::from_secs(15)),
+///     tcp_keepalive: Some(Duration::from_secs(30)),
+///     ..Default::default()
+/// });
+/// # }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConnectionSettings {
+    /// How long an idle connection is kept in the pool before being closed
+    pub pool_idle_timeout: Option<Duration>,
+    /// Maximum number of idle connections kept per host
+    pub pool_max_idle_per_host: Option<usize>,
+    /// Interval of TCP keep-alive probes on open connections
+    pub tcp_keepalive: Option<Duration>,
+    /// Maximum number of redirects followed per request (defaults to 10, 0 disables them)
+    pub max_redirects: Option<usize>,
+    /// Emit an info event for every redirect that is followed, to debug reverse proxy setups
+    ///
+    /// Events are handled by the application's `tracing` subscriber, and
+    /// record the URLs without their query.
+    pub log_redirects: bool,
+}
+
+/// Number of redirects followed per request by default, as in reqwest
+#[cfg(any(feature = "blocking", feature = "async"))]
+const DEFAULT_MAX_REDIRECTS: usize = 10;
+
+/// Builds the redirect policy described by the connection settings
+///
+/// Unlike reqwest's `Policy::none`, which hands the redirect response back,
+/// a disallowed redirect fails the request with an error naming its target.
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub(crate) fn redirect_policy(settings: &ConnectionSettings) -> reqwest::redirect::Policy {
+    let max_redirects = settings.max_redirects.unwrap_or(DEFAULT_MAX_REDIRECTS);
+    let log_redirects = settings.log_redirects;
+
+    reqwest::redirect::Policy::custom(move |attempt| {
+        // The previous URLs include the one originally requested
+        if attempt.previous().len() > max_redirects {
+            let error = match max_redirects {
+                0 => format!(
+                    "redirect to {} not followed (redirects are disabled)",
+                    attempt.url()
+                ),
+                n => format!(
+                    "redirect to {} not followed (more than {} redirects)",
+                    attempt.url(),
+                    n
+                ),
+            };
+            return attempt.error(error);
+        }
+        if log_redirects {
+            if let Some(previous) = attempt.previous().last() {
+                // Queries can carry API keys, so only the rest of the URLs is recorded
+                let without_query = |url: &Url| url[..url::Position::AfterPath].to_string();
+                tracing::info!(
+                    from = %without_query(previous),
+                    to = %without_query(attempt.url()),
+                    "redirected"
+                );
+            }
+        }
+        attempt.follow()
+    })
+}
+
+/// Credentials for a reverse proxy in front of the Plex server
+///
+/// They are sent in the `Authorization` header of every request, in addition
+/// to the `X-Plex-Token` header, for servers fronted by e.g. nginx basic auth
+/// or an Authelia forward-auth endpoint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProxyAuth {
+    /// HTTP basic authentication
+    Basic {
+        /// User name
+        username: String,
+        /// Password
+        password: String,
+    },
+    /// Bearer token
+    Bearer(String),
+}
+
+/// Where to find or regenerate a Plex token
+const TOKEN_HELP_URL: &str =
+    "https://support.plex.tv/articles/204059436-finding-an-authentication-token-x-plex-token/";
+
+/// Error returned when Plex rejects the token (HTTP 401 Unauthorized)
+///
+/// Tokens stop working when they are revoked, e.g. when the user signs out of
+/// all devices or changes their password, so this can also happen in the
+/// middle of a run. Callers can detect it with
+/// `error.downcast_ref::<TokenRejectedError>()`.
+#[derive(Debug, Clone)]
+pub struct TokenRejectedError {
+    /// Endpoint that rejected the token (e.g. "/library/sections")
+    pub endpoint: String,
+}
+
+impl fmt::Display for TokenRejectedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Plex token rejected by server for endpoint {}; it may have expired or been revoked.\n\
+             Regenerate it with the login command or as described here: {}",
+            self.endpoint, TOKEN_HELP_URL
+        )
+    }
+}
+
+impl std::error::Error for TokenRejectedError {}
+
+/// Returns a `TokenRejectedError` when a response status means the token was rejected
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub(crate) fn check_token(status: StatusCode, endpoint: &str) -> Result<(), TokenRejectedError> {
+    if status == StatusCode::UNAUTHORIZED {
+        return Err(TokenRejectedError {
+            endpoint: endpoint.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Parses the body of a Plex API response into `T`
+///
+/// Responses are expected in JSON, since every request asks for it. Some
+/// server versions and endpoints answer in XML regardless; those are detected
+/// by their content type (or, without one, by their first character) and
+/// mapped onto the same structs, with `Video`, `Track` and `Photo` elements
+/// read as `Metadata`.
+///
+/// # Example
+///
+/// ```rust
+/// use plex_client::client::{parse_response, MediaContainer};
+/// use plex_client::library::PlexLibrarySection;
+///
+/// let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+/// <MediaContainer size="1">
+///   <Directory key="1" type="movie" title="Films &amp; Docs">
+///     <Location id="3" path="/data/movies" />
+///   </Directory>
+/// </MediaContainer>"#;
+/// let container: MediaContainer<PlexLibrarySection> =
+///     parse_response(xml, Some("text/xml;charset=utf-8"))?;
+/// let directory = &container.media_container.directory[0];
+/// assert_eq!(directory.title, "Films & Docs");
+/// assert_eq!(directory.location[0].id, 3);
+///
+/// let json = r#"{"MediaContainer": {"Directory": [{"key": "1", "title": "Movies"}]}}"#;
+/// let container: MediaContainer<PlexLibrarySection> =
+///     parse_response(json, Some("application/json"))?;
+/// assert_eq!(container.media_container.directory[0].title, "Movies");
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn parse_response<T>(body: &str, content_type: Option<&str>) -> anyhow::Result<T>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let is_xml = match content_type {
+        Some(content_type) => content_type.contains("xml"),
+        None => body.trim_start().starts_with('<'),
+    };
+
+    if is_xml {
+        let body = xml::attributes_to_elements(body)?;
+        Ok(quick_xml::de::from_str(&body)?)
+    } else {
+        Ok(serde_json::from_str(body)?)
+    }
+}
+
+/// Generic wrapper for Plex API responses
+///
+/// All Plex API responses are wrapped in a `MediaContainer` object.
+/// This generic struct allows you to deserialize different response types
+/// while maintaining type safety.
+///
+/// # Type Parameters
+///
+/// * `T` - The inner type that represents the actual response data
+///
+/// # Example
+///
+/// ```no_run
+/// use plex_client::client::MediaContainer;
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct MyResponse {
+///     pub size: u32,
+///     pub total_size: u32,
+/// }
+///
+/// // JSON: {"MediaContainer": {"size": 10, "total_size": 100}}
+/// # let json = r#"{"MediaContainer": {"size": 10, "total_size": 100}}"#;
+/// let container: MediaContainer<MyResponse> = serde_json::from_str(json)?;
+/// println!("Size: {}", container.media_container.size);
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct MediaContainer<T> {
+    /// The inner MediaContainer data
+    ///
+    /// This field name matches the JSON key "MediaContainer" from Plex API responses.
+    /// The generic type `T` allows this struct to hold different response types.
+    #[serde(rename = "MediaContainer")]
+    pub media_container: T,
+}
+
+impl<T> MediaContainer<T> {
+    /// Creates a new MediaContainer with the given inner data
+    ///
+    /// This is useful for testing or when constructing responses programmatically.
+    pub fn new(media_container: T) -> Self {
+        Self { media_container }
+    }
+
+    /// Extracts the inner MediaContainer data, consuming the wrapper
+    ///
+    /// This is useful when you want to move the data out of the wrapper.
+    pub fn into_inner(self) -> T {
+        self.media_container
+    }
+
+    /// Gets a reference to the inner MediaContainer data
+    pub fn inner(&self) -> &T {
+        &self.media_container
+    }
+}
@@ -0,0 +1,455 @@
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{Client, RequestBuilder, Response};
+use serde::Deserialize;
+use url::Url;
+
+use super::{
+    account_id_or_owner, check_token, find_account_id, middleware_headers, no_account_error,
+    parse_base_url, parse_response, redirect_policy, send_with_retry_after_async,
+    server_capabilities, ConnectionSettings, Endpoint, MediaContainer, Middleware, ProxyAuth,
+    Query, PAGE_SIZE,
+};
+use crate::account::{PlexAccounts, PlexMyPlexAccount, PlexMyPlexResponse};
+use crate::device::PlexDevices;
+use crate::identity::{PlexServerIdentity, ServerCapabilities};
+use crate::library::{PlexLibraryItems, PlexLibrarySection};
+use crate::media_item::PlexMediaItem;
+use crate::watch_history::{PlexWatchHistory, PlexWatchHistoryItem};
+
+/// Async counterpart of `PlexClient`, for use inside a tokio application
+///
+/// This struct holds the same information as the blocking client, but uses
+/// reqwest's async client so no blocking HTTP stack is compiled in when the
+/// `blocking` feature is disabled.
+///
+/// Like `PlexClient`, it is cheap to clone and is `Send + Sync`, so clones
+/// can be moved into spawned tasks.
+#[derive(Clone)]
+pub struct AsyncPlexClient {
+    /// Base URL of the Plex Media Server, normalized with a trailing slash
+    base_url: Url,
+    /// Plex authentication token
+    token: String,
+    /// HTTP client for making requests
+    client: Client,
+    /// Language sent with every request (e.g., "en-US")
+    language: Option<String>,
+    /// Account whose watch history is requested, resolved on first use
+    account_id: OnceLock<String>,
+    /// Credentials for a reverse proxy in front of the server
+    proxy_auth: Option<ProxyAuth>,
+    /// Features of the server, recorded by `check_server_version`
+    capabilities: OnceLock<ServerCapabilities>,
+    /// Hooks called around every request, shared between clones
+    middleware: Vec<Arc<dyn Middleware>>,
+}
+
+impl AsyncPlexClient {
+    /// Creates a new AsyncPlexClient with the given server URL and authentication token
+    ///
+    /// Fails when `base_url` is not a valid http(s) URL; see `PlexClient::new`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use plex_client::client::AsyncPlexClient;
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let client = AsyncPlexClient::new(
+    ///     "http://192.168.1.100:32400".to_string(),
+    ///     "your-token-here".to_string(),
+    /// )?;
+    /// for item in client.get_watch_history("1").await? {
+    ///     println!("Watched: {} at {:?}", item.title, item.viewed_at);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new(base_url: String, token: String) -> Result<Self> {
+        Ok(Self {
+            base_url: parse_base_url(&base_url)?,
+            token,
+            client: http_client(&ConnectionSettings::default()),
+            language: None,
+            account_id: OnceLock::new(),
+            proxy_auth: None,
+            capabilities: OnceLock::new(),
+            middleware: Vec::new(),
+        })
+    }
+
+    /// Sets the language used for localized titles and fields
+    ///
+    /// The language is forwarded to the server as the `X-Plex-Language` header
+    /// on every request.
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// Sets the connection pool and keep-alive settings of the HTTP client
+    ///
+    /// See `PlexClient::with_connection_settings` for details.
+    pub fn with_connection_settings(mut self, settings: ConnectionSettings) -> Self {
+        self.client = http_client(&settings);
+        self
+    }
+
+    /// Sets credentials for a reverse proxy in front of the server
+    ///
+    /// See `PlexClient::with_proxy_auth` for details.
+    pub fn with_proxy_auth(mut self, proxy_auth: ProxyAuth) -> Self {
+        self.proxy_auth = Some(proxy_auth);
+        self
+    }
+
+    /// Adds hooks called around every request sent to the server
+    ///
+    /// See `PlexClient::with_middleware` for details.
+    pub fn with_middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Sets the account whose watch history is requested
+    ///
+    /// See `PlexClient::with_account_id` for details.
+    pub fn with_account_id(mut self, account_id: impl Into<String>) -> Self {
+        self.account_id = OnceLock::from(account_id.into());
+        self
+    }
+
+    /// Finds the server account of the user the token belongs to
+    ///
+    /// See `PlexClient::resolve_account_id` for details.
+    pub async fn resolve_account_id(&self) -> Result<String> {
+        let my_plex = self.get_myplex_account().await?;
+        let accounts = self.get_accounts().await?;
+        find_account_id(&accounts, &my_plex).ok_or_else(|| no_account_error(&my_plex))
+    }
+
+    /// Returns the account whose watch history is requested
+    ///
+    /// See `PlexClient::account_id` for details.
+    pub async fn account_id(&self) -> Result<&str> {
+        if let Some(account_id) = self.account_id.get() {
+            return Ok(account_id);
+        }
+        let my_plex = self.get_myplex_account().await?;
+        let accounts = self.get_accounts().await?;
+        // Another task may have resolved it meanwhile; both agree
+        Ok(self
+            .account_id
+            .get_or_init(|| account_id_or_owner(&accounts, &my_plex)))
+    }
+
+    /// Gets the local accounts of the server
+    pub async fn get_accounts(&self) -> Result<PlexAccounts> {
+        let container: MediaContainer<PlexAccounts> = self
+            .get_media_container(Endpoint::Accounts, &Query::new())
+            .await
+            .context("Failed to get server accounts")?;
+        Ok(container.into_inner())
+    }
+
+    /// Gets the devices (players) that played media from the server
+    pub async fn get_devices(&self) -> Result<PlexDevices> {
+        let container: MediaContainer<PlexDevices> = self
+            .get_media_container(Endpoint::Devices, &Query::new())
+            .await
+            .context("Failed to get server devices")?;
+        Ok(container.into_inner())
+    }
+
+    /// Gets the plex.tv account the token belongs to
+    pub async fn get_myplex_account(&self) -> Result<PlexMyPlexAccount> {
+        let response: PlexMyPlexResponse = self
+            .get_json(Endpoint::MyPlexAccount, &Query::new())
+            .await
+            .context("Failed to get plex.tv account")?;
+        Ok(response.my_plex)
+    }
+
+    /// Fetches the complete watch history of a library section, following pagination
+    pub async fn get_watch_history(
+        &self,
+        library_section_id: &str,
+    ) -> Result<Vec<PlexWatchHistoryItem>> {
+        let mut items = Vec::new();
+
+        loop {
+            let page = self
+                .get_watch_history_page(items.len() as u32, PAGE_SIZE, library_section_id)
+                .await
+                .context("Failed to fetch watch history page")?
+                .into_inner();
+
+            let received = page.metadata.len() as u32;
+            items.extend(page.metadata);
+
+            // Fewer items than requested means this was the last page
+            if received < PAGE_SIZE {
+                return Ok(items);
+            }
+        }
+    }
+
+    pub async fn get_media_item_metadata(&self, rating_key: String) -> Result<PlexMediaItem> {
+        let container: MediaContainer<PlexMediaItem> = self
+            .get_media_container(Endpoint::Metadata(&rating_key), &Query::new())
+            .await
+            .context("Failed to get media item metadata")?;
+        Ok(container.into_inner())
+    }
+
+    pub async fn get_library_sections(&self) -> Result<PlexLibrarySection> {
+        let container: MediaContainer<PlexLibrarySection> = self
+            .get_media_container(Endpoint::LibrarySections, &Query::new())
+            .await
+            .context("Failed to get library sections")?;
+        Ok(container.into_inner())
+    }
+
+    /// Gets every movie in a library section, watched or not
+    ///
+    /// # Arguments
+    ///
+    /// * `library_section_id` - The library section ID to list
+    pub async fn get_library_items(&self, library_section_id: &str) -> Result<PlexLibraryItems> {
+        let mut query = Query::new().param("type", "1");
+        if self.capabilities().include_guids {
+            query = query.param("includeGuids", "1");
+        }
+        let container: MediaContainer<PlexLibraryItems> = self
+            .get_media_container(Endpoint::LibrarySectionItems(library_section_id), &query)
+            .await
+            .context("Failed to get library items")?;
+        Ok(container.into_inner())
+    }
+
+    /// Searches the movies of a library section with the server's own search
+    ///
+    /// The server matches the query against titles (including original and
+    /// sort titles), so it can find films whose history entry is titled
+    /// differently from the query.
+    ///
+    /// # Arguments
+    ///
+    /// * `library_section_id` - The library section ID to search
+    /// * `query` - The text to search for (e.g., "blade runner")
+    pub async fn search_library(
+        &self,
+        library_section_id: &str,
+        query: &str,
+    ) -> Result<PlexLibraryItems> {
+        let mut query = Query::new().param("type", "1").param("query", query);
+        if self.capabilities().include_guids {
+            query = query.param("includeGuids", "1");
+        }
+        let container: MediaContainer<PlexLibraryItems> = self
+            .get_media_container(Endpoint::LibrarySectionSearch(library_section_id), &query)
+            .await
+            .context("Failed to search library")?;
+        Ok(container.into_inner())
+    }
+
+    /// Gets the identity of the Plex server (machine identifier and version)
+    pub async fn get_server_identity(&self) -> Result<PlexServerIdentity> {
+        let container: MediaContainer<PlexServerIdentity> = self
+            .get_media_container(Endpoint::Identity, &Query::new())
+            .await
+            .context("Failed to get server identity")?;
+        Ok(container.into_inner())
+    }
+
+    /// Checks that the server is supported and records its features
+    ///
+    /// See `PlexClient::check_server_version` for details.
+    pub async fn check_server_version(&self) -> Result<PlexServerIdentity> {
+        let identity = self.get_server_identity().await?;
+        let capabilities = server_capabilities(&identity)?;
+        // A concurrent check of the same server records the same features
+        let _ = self.capabilities.set(capabilities);
+        Ok(identity)
+    }
+
+    /// Returns the features of the server recorded by `check_server_version`
+    ///
+    /// Those of a current server are returned when the version was not checked.
+    pub fn capabilities(&self) -> ServerCapabilities {
+        self.capabilities.get().copied().unwrap_or_default()
+    }
+
+    /// Gets the base URL of the Plex server
+    ///
+    /// The URL is normalized, without a trailing slash (e.g. "http://192.168.1.100:32400").
+    pub fn base_url(&self) -> &str {
+        self.base_url.as_str().trim_end_matches('/')
+    }
+
+    /// Makes a generic API request that returns a MediaContainer response
+    ///
+    /// See `PlexClient::get_media_container` for details.
+    pub async fn get_media_container<T>(
+        &self,
+        endpoint: Endpoint<'_>,
+        query: &Query,
+    ) -> Result<MediaContainer<T>>
+    where
+        MediaContainer<T>: for<'de> Deserialize<'de>,
+    {
+        self.get_json(endpoint, query).await
+    }
+
+    /// Makes a generic API request and parses the JSON response
+    async fn get_json<T>(&self, endpoint: Endpoint<'_>, query: &Query) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let url = endpoint.url(&self.base_url, query);
+        let request = self.request(url.clone());
+
+        let response = self
+            .send(&url, request)
+            .await
+            .context(format!("Failed to send request to endpoint: {}", endpoint))?;
+
+        check_token(response.status(), &endpoint.to_string())?;
+        let response = response.error_for_status().context(format!(
+            "Plex server returned an error for endpoint: {}",
+            endpoint
+        ))?;
+
+        parse_body(response).await.context(format!(
+            "Failed to parse response from endpoint: {}",
+            endpoint
+        ))
+    }
+
+    /// Makes a paginated API request for watch history with headers
+    ///
+    /// Pagination uses the `X-Plex-Container-Start`/`X-Plex-Container-Size`
+    /// headers, as required by the Plex API, or query parameters of the same
+    /// name on servers too old to honor the headers.
+    pub async fn get_watch_history_page(
+        &self,
+        offset: u32,
+        page_size: u32,
+        library_section_id: &str,
+    ) -> Result<MediaContainer<PlexWatchHistory>> {
+        let mut query = Query::new()
+            .param("sort", "viewedAt:desc")
+            .param("librarySectionID", library_section_id)
+            .param("accountID", self.account_id().await?);
+
+        let (url, request) = if self.capabilities().header_pagination {
+            let url = Endpoint::WatchHistory.url(&self.base_url, &query);
+            let request = self
+                .request(url.clone())
+                .header("X-Plex-Container-Start", offset.to_string())
+                .header("X-Plex-Container-Size", page_size.to_string());
+            (url, request)
+        } else {
+            query = query
+                .param("X-Plex-Container-Start", offset.to_string())
+                .param("X-Plex-Container-Size", page_size.to_string());
+            let url = Endpoint::WatchHistory.url(&self.base_url, &query);
+            (url.clone(), self.request(url))
+        };
+
+        let response = self
+            .send(&url, request)
+            .await
+            .context("Failed to send watch history pagination request")?;
+
+        check_token(response.status(), &Endpoint::WatchHistory.to_string())?;
+        let response = response
+            .error_for_status()
+            .context("Plex server returned an error for watch history pagination request")?;
+
+        let container: MediaContainer<PlexWatchHistory> = parse_body(response)
+            .await
+            .context("Failed to parse watch history pagination response")?;
+
+        Ok(container)
+    }
+
+    /// Sends a request through the middleware
+    async fn send(&self, url: &Url, mut request: RequestBuilder) -> reqwest::Result<Response> {
+        if !self.middleware.is_empty() {
+            request = request.headers(middleware_headers(&self.middleware, url));
+        }
+
+        let started = Instant::now();
+        let response = send_with_retry_after_async(request).await;
+        let status = response.as_ref().ok().map(Response::status);
+        for middleware in &self.middleware {
+            middleware.on_response(url, status, started.elapsed());
+        }
+        response
+    }
+
+    /// Builds a GET request carrying the headers shared by every Plex API call
+    fn request(&self, url: Url) -> RequestBuilder {
+        let request = self
+            .client
+            .get(url)
+            .header("X-Plex-Token", &self.token)
+            .header("Accept", "application/json");
+
+        let request = match &self.language {
+            Some(language) => request.header("X-Plex-Language", language),
+            None => request,
+        };
+
+        match &self.proxy_auth {
+            Some(ProxyAuth::Basic { username, password }) => {
+                request.basic_auth(username, Some(password))
+            }
+            Some(ProxyAuth::Bearer(token)) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+}
+
+/// Reads a response body and parses it as JSON, or as XML when the server sent XML
+async fn parse_body<T>(response: Response) -> Result<T>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let body = response
+        .text()
+        .await
+        .context("Failed to read response body")?;
+    parse_response(&body, content_type.as_deref())
+}
+
+/// Creates the HTTP client with the given connection settings
+fn http_client(settings: &ConnectionSettings) -> Client {
+    // History pages are large JSON documents that compress well
+    let mut builder = Client::builder()
+        .gzip(true)
+        .brotli(true)
+        .redirect(redirect_policy(settings))
+        .pool_max_idle_per_host(settings.pool_max_idle_per_host.unwrap_or(usize::MAX))
+        .tcp_keepalive(settings.tcp_keepalive);
+    if let Some(timeout) = settings.pool_idle_timeout {
+        builder = builder.pool_idle_timeout(timeout);
+    }
+    builder.build().expect("Failed to create the HTTP client")
+}
+
+// Sharing the client across tasks is part of its public contract
+const _: () = {
+    fn assert_shareable<T: Clone + Send + Sync>() {}
+    let _ = assert_shareable::<AsyncPlexClient>;
+};
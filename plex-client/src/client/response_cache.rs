@@ -0,0 +1,303 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use url::Url;
+
+/// API responses saved to a directory, to run again without the server
+///
+/// While recording, every response body is saved under a file name derived
+/// from the request URL (and, for header-paginated history, the page), so a
+/// later run making the same requests can be served from the directory in
+/// replay mode without contacting the server. The token is sent as a header
+/// and is never part of a file name.
+///
+/// # Example
+///
+/// ```rust
+/// use plex_client::client::ResponseCache;
+/// use url::Url;
+///
+/// let dir = std::env::temp_dir().join("response-cache-example");
+/// let url = Url::parse("http://192.168.1.100:32400/library/sections")?;
+///
+/// ResponseCache::record(&dir).save(&url, None, r#"{"MediaContainer": {}}"#)?;
+///
+/// let cache = ResponseCache::replay(&dir);
+/// assert!(cache.is_offline());
+/// assert_eq!(cache.load(&url, None)?, r#"{"MediaContainer": {}}"#);
+/// assert!(cache.load(&url, Some((100, 100))).is_err());
+/// # std::fs::remove_dir_all(&dir)?;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    /// Directory the responses are saved in
+    dir: PathBuf,
+    /// Whether responses are served from the directory instead of the server
+    offline: bool,
+    /// Files of a raw response capture, by request, when replaying one
+    captured: Option<Arc<HashMap<String, PathBuf>>>,
+}
+
+impl ResponseCache {
+    /// Saves every response to a directory, while still requesting the server
+    pub fn record(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            offline: false,
+            captured: None,
+        }
+    }
+
+    /// Serves every request from responses saved in a directory, without contacting the server
+    pub fn replay(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            offline: true,
+            captured: None,
+        }
+    }
+
+    /// Serves every request from a directory written by [`ResponseCapture`]
+    ///
+    /// Captured files are matched to requests by the name after their number,
+    /// which does not depend on the server the responses came from. When the
+    /// same request was captured more than once, the latest response is used.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use plex_client::client::{ResponseCache, ResponseCapture};
+    /// use url::Url;
+    ///
+    /// let dir = std::env::temp_dir().join("response-capture-replay-example");
+    /// let url = Url::parse("http://192.168.1.100:32400/library/sections")?;
+    /// ResponseCapture::new(&dir).save(&url, None, None, r#"{"MediaContainer": {}}"#)?;
+    ///
+    /// let cache = ResponseCache::replay_capture(&dir)?;
+    /// let url = Url::parse("http://replay.invalid/library/sections")?;
+    /// assert_eq!(cache.load(&url, None)?, r#"{"MediaContainer": {}}"#);
+    /// # std::fs::remove_dir_all(&dir)?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn replay_capture(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        let entries = fs::read_dir(&dir)
+            .with_context(|| format!("Failed to read raw responses: {}", dir.display()))?;
+
+        let mut captured: HashMap<String, (u32, PathBuf)> = HashMap::new();
+        for entry in entries {
+            let path = entry?.path();
+            let Some((number, stem)) = captured_request(&path) else {
+                continue;
+            };
+            if captured
+                .get(&stem)
+                .is_none_or(|(latest, _)| *latest < number)
+            {
+                captured.insert(stem, (number, path));
+            }
+        }
+        if captured.is_empty() {
+            anyhow::bail!("No raw responses found in {}", dir.display());
+        }
+
+        Ok(Self {
+            dir,
+            offline: true,
+            captured: Some(Arc::new(
+                captured
+                    .into_iter()
+                    .map(|(stem, (_, path))| (stem, path))
+                    .collect(),
+            )),
+        })
+    }
+
+    /// Returns whether requests are served from the directory instead of the server
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    /// Reads the saved response of a request
+    ///
+    /// `page` is the offset and size of a page requested through headers.
+    pub fn load(&self, url: &Url, page: Option<(u32, u32)>) -> Result<String> {
+        if let Some(captured) = &self.captured {
+            let path = captured.get(&capture_stem(url, page)).with_context(|| {
+                format!(
+                    "No captured response for {} in {}",
+                    url.path(),
+                    self.dir.display()
+                )
+            })?;
+            return fs::read_to_string(path)
+                .with_context(|| format!("Failed to read raw response: {}", path.display()));
+        }
+
+        let path = self.path(url, page);
+        fs::read_to_string(&path).with_context(|| {
+            format!(
+                "No saved response for {} in {}; run once online with the same flags to record it",
+                url.path(),
+                self.dir.display()
+            )
+        })
+    }
+
+    /// Saves the response of a request, replacing an earlier one
+    ///
+    /// `page` is the offset and size of a page requested through headers.
+    pub fn save(&self, url: &Url, page: Option<(u32, u32)>, body: &str) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create response cache: {}", self.dir.display()))?;
+        let path = self.path(url, page);
+        fs::write(&path, body)
+            .with_context(|| format!("Failed to save response: {}", path.display()))
+    }
+
+    /// Returns the file a response is saved in
+    fn path(&self, url: &Url, page: Option<(u32, u32)>) -> PathBuf {
+        let key = &url[url::Position::BeforeHost..];
+        self.dir.join(format!("{}.response", file_stem(key, page)))
+    }
+}
+
+/// Raw response bodies written to a directory, to attach to bug reports
+///
+/// Every response is written as it was received, before it is parsed, so
+/// responses that fail to deserialize can be shared. Files are numbered in
+/// the order the responses arrived and named after the request (its path and
+/// query, without the server), with a `.json` or `.xml` extension. Secrets (the client's token is always one)
+/// are replaced with `****` in the saved bodies.
+///
+/// # Example
+///
+/// ```rust
+/// use plex_client::client::ResponseCapture;
+/// use url::Url;
+///
+/// let dir = std::env::temp_dir().join("response-capture-example");
+/// let capture = ResponseCapture::new(&dir).with_secret("abc123");
+/// let url = Url::parse("http://192.168.1.100:32400/myplex/account")?;
+///
+/// let path = capture.save(&url, None, Some("application/json"), r#"{"authToken": "abc123"}"#)?;
+/// assert!(path.ends_with("0001-_myplex_account.json"));
+/// assert_eq!(std::fs::read_to_string(&path)?, r#"{"authToken": "****"}"#);
+/// # std::fs::remove_dir_all(&dir)?;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct ResponseCapture {
+    /// Directory the responses are written to
+    dir: PathBuf,
+    /// Secrets replaced in the saved bodies
+    secrets: Vec<String>,
+    /// Number of responses written so far, shared between clones
+    count: Arc<AtomicUsize>,
+}
+
+impl ResponseCapture {
+    /// Writes every response to a directory
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            secrets: Vec::new(),
+            count: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Adds a secret to replace in the saved bodies (e.g. a proxy password)
+    pub fn with_secret(mut self, secret: impl Into<String>) -> Self {
+        let secret = secret.into();
+        if !secret.is_empty() && !self.secrets.contains(&secret) {
+            self.secrets.push(secret);
+            // Longest first, so overlapping secrets are fully replaced
+            self.secrets.sort_by_key(|s| std::cmp::Reverse(s.len()));
+        }
+        self
+    }
+
+    /// Writes the body of a response, returning the path of the file
+    ///
+    /// `page` is the offset and size of a page requested through headers.
+    pub fn save(
+        &self,
+        url: &Url,
+        page: Option<(u32, u32)>,
+        content_type: Option<&str>,
+        body: &str,
+    ) -> Result<PathBuf> {
+        fs::create_dir_all(&self.dir).with_context(|| {
+            format!(
+                "Failed to create raw response directory: {}",
+                self.dir.display()
+            )
+        })?;
+
+        let number = self.count.fetch_add(1, Ordering::Relaxed) + 1;
+        let extension = match content_type {
+            Some(content_type) if content_type.contains("xml") => "xml",
+            Some(_) => "json",
+            None if body.trim_start().starts_with('<') => "xml",
+            None => "json",
+        };
+        let path = self.dir.join(format!(
+            "{:04}-{}.{}",
+            number,
+            capture_stem(url, page),
+            extension
+        ));
+
+        let body = self.secrets.iter().fold(body.to_string(), |body, secret| {
+            body.replace(secret, "****")
+        });
+        fs::write(&path, body)
+            .with_context(|| format!("Failed to save raw response: {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+/// Returns the name of a captured response, without number and extension
+///
+/// Only the path and query are used, so captures do not reveal the address
+/// of the server and can be replayed against any URL.
+fn capture_stem(url: &Url, page: Option<(u32, u32)>) -> String {
+    file_stem(&url[url::Position::BeforePath..], page)
+}
+
+/// Returns the number and request name of a captured response file
+///
+/// Captured files are named "<number>-<name>.json" or "<number>-<name>.xml".
+fn captured_request(path: &Path) -> Option<(u32, String)> {
+    if !matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("json" | "xml")
+    ) {
+        return None;
+    }
+    let (number, stem) = path.file_stem()?.to_str()?.split_once('-')?;
+    Some((number.parse().ok()?, stem.to_string()))
+}
+
+/// Returns a file name for the response of a request, without extension
+///
+/// `key` identifies the endpoint and query (and, for the response cache,
+/// the server); characters that are not safe in file names are replaced
+/// with `_`.
+fn file_stem(key: &str, page: Option<(u32, u32)>) -> String {
+    let mut key = key.to_string();
+    if let Some((offset, size)) = page {
+        key.push_str(&format!("#{}-{}", offset, size));
+    }
+    key.chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '-' => c,
+            _ => '_',
+        })
+        .collect()
+}
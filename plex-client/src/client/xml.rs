@@ -0,0 +1,75 @@
+use anyhow::{Context, Result};
+use quick_xml::events::{BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::{Reader, Writer, XmlVersion};
+
+/// XML elements holding media items, which JSON responses call "Metadata"
+const METADATA_ELEMENTS: [&str; 3] = ["Video", "Track", "Photo"];
+
+/// Rewrites an XML response so it deserializes into the JSON response structs
+///
+/// Plex XML puts fields in attributes (`<Directory key="1" title="Movies">`),
+/// which quick-xml only maps to fields renamed with an "@" prefix. Each
+/// attribute is turned into a child element of the same name instead, and
+/// the document is wrapped in a root element so that its top-level element
+/// (e.g. `MediaContainer`) is matched by name, as the JSON key is. Empty
+/// attributes are dropped, like missing JSON fields.
+pub(crate) fn attributes_to_elements(xml: &str) -> Result<String> {
+    let mut reader = Reader::from_str(xml);
+    let mut writer = Writer::new(Vec::new());
+
+    writer.write_event(Event::Start(BytesStart::new("response")))?;
+    loop {
+        match reader.read_event().context("Invalid XML")? {
+            Event::Start(element) => {
+                write_element(&mut writer, &element)?;
+            }
+            Event::Empty(element) => {
+                let name = write_element(&mut writer, &element)?;
+                writer.write_event(Event::End(BytesEnd::new(name)))?;
+            }
+            Event::End(element) => {
+                let name = element_name(element.name().into_inner());
+                writer.write_event(Event::End(BytesEnd::new(name)))?;
+            }
+            Event::Eof => break,
+            // Declarations, comments and whitespace between elements carry no fields
+            _ => {}
+        }
+    }
+    writer.write_event(Event::End(BytesEnd::new("response")))?;
+
+    Ok(String::from_utf8(writer.into_inner())?)
+}
+
+/// Writes the start of an element, with its attributes as child elements
+///
+/// Returns the name the element was written with.
+fn write_element<'a>(writer: &mut Writer<Vec<u8>>, element: &'a BytesStart) -> Result<&'a str> {
+    let name = element_name(element.name().into_inner());
+    writer.write_event(Event::Start(BytesStart::new(name)))?;
+
+    for attribute in element.attributes() {
+        let attribute = attribute.context("Invalid XML attribute")?;
+        let value = attribute
+            .normalized_value(XmlVersion::Implicit1_0)
+            .context("Invalid XML attribute")?;
+        if value.is_empty() {
+            continue;
+        }
+        let key = attribute.key.into_inner();
+        writer.write_event(Event::Start(BytesStart::new(key)))?;
+        writer.write_event(Event::Text(BytesText::new(&value)))?;
+        writer.write_event(Event::End(BytesEnd::new(key)))?;
+    }
+
+    Ok(name)
+}
+
+/// Returns the JSON name of an XML element
+fn element_name(name: &str) -> &str {
+    if METADATA_ELEMENTS.contains(&name) {
+        "Metadata"
+    } else {
+        name
+    }
+}
@@ -0,0 +1,187 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::de::{self, Visitor};
+use serde::Deserializer;
+
+/// Timestamps at or above this value are taken to be in milliseconds
+///
+/// As seconds, it would be in the year 5138.
+const MILLISECONDS_THRESHOLD: i64 = 100_000_000_000;
+
+/// Custom deserializer that converts a Unix timestamp to a UTC timestamp
+///
+/// This function is used to deserialize Plex API timestamps (Unix epoch in seconds)
+/// into a `DateTime<Utc>`; formatting it is left to the output. Some proxies
+/// and older servers send the timestamp as a string, or in milliseconds, so
+/// numbers and numeric strings are both accepted, and values too large to be
+/// seconds are read as milliseconds.
+///
+/// # Arguments
+///
+/// * `deserializer` - The Serde deserializer
+///
+/// # Returns
+///
+/// * `Ok(DateTime<Utc>)` - The time of the timestamp
+/// * `Err` - If the timestamp is invalid or deserialization fails
+///
+/// # Example
+///
+/// ```rust
+/// use chrono::{DateTime, Utc};
+/// use serde::Deserialize;
+/// use plex_client::deserializers::deserialize_viewed_at;
+///
+/// #[derive(Deserialize)]
+/// struct MyStruct {
+///     #[serde(deserialize_with = "deserialize_viewed_at")]
+///     pub viewed_at: DateTime<Utc>,
+/// }
+///
+/// for json in [r#"1705276800"#, r#""1705276800""#, r#"1705276800000"#, r#""1705276800000""#] {
+///     let item: MyStruct = serde_json::from_str(&format!(r#"{{"viewed_at": {}}}"#, json)).unwrap();
+///     assert_eq!(item.viewed_at.format("%Y-%m-%d").to_string(), "2024-01-15");
+/// }
+/// ```
+pub fn deserialize_viewed_at<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer
+        .deserialize_any(TimestampVisitor {
+            out_of_range_as_epoch: false,
+        })?
+        .ok_or_else(|| de::Error::custom("Missing timestamp"))
+}
+
+/// Custom deserializer like `deserialize_viewed_at`, for timestamps that may be missing
+///
+/// A missing, `null` or empty string timestamp deserializes to `None`. Use it
+/// together with `#[serde(default)]` so that an absent field is accepted as well.
+/// Numbers too far from the present to be a time at all, which some servers
+/// send for corrupt history rows, deserialize to the Unix epoch rather than
+/// failing the whole response, like the 0 sent for unknown times.
+///
+/// # Example
+///
+/// ```rust
+/// use chrono::{DateTime, Utc};
+/// use serde::Deserialize;
+/// use plex_client::deserializers::deserialize_optional_viewed_at;
+///
+/// #[derive(Deserialize)]
+/// struct MyStruct {
+///     #[serde(default, deserialize_with = "deserialize_optional_viewed_at")]
+///     pub viewed_at: Option<DateTime<Utc>>,
+/// }
+///
+/// let item: MyStruct = serde_json::from_str(r#"{"viewed_at": null}"#).unwrap();
+/// assert_eq!(item.viewed_at, None);
+/// let item: MyStruct = serde_json::from_str(r#"{"viewed_at": "1705276800"}"#).unwrap();
+/// assert!(item.viewed_at.is_some());
+/// let item: MyStruct = serde_json::from_str(r#"{"viewed_at": -9223372036854775808}"#).unwrap();
+/// assert_eq!(item.viewed_at, Some(DateTime::UNIX_EPOCH));
+/// ```
+pub fn deserialize_optional_viewed_at<'de, D>(
+    deserializer: D,
+) -> Result<Option<DateTime<Utc>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(TimestampVisitor {
+        out_of_range_as_epoch: true,
+    })
+}
+
+/// Reads a Unix timestamp in seconds or milliseconds, as a number or a string
+struct TimestampVisitor {
+    /// Whether numbers outside chrono's range are read as the Unix epoch instead of failing
+    out_of_range_as_epoch: bool,
+}
+
+impl TimestampVisitor {
+    /// Handles a number that is not a representable time
+    fn out_of_range<E: de::Error>(
+        &self,
+        value: impl fmt::Display,
+    ) -> Result<Option<DateTime<Utc>>, E> {
+        if self.out_of_range_as_epoch {
+            Ok(Some(DateTime::UNIX_EPOCH))
+        } else {
+            Err(E::custom(format!("Invalid timestamp: {}", value)))
+        }
+    }
+
+    /// Converts seconds, or milliseconds above the threshold, to a UTC timestamp
+    fn time_from_number<E: de::Error>(&self, value: f64) -> Result<Option<DateTime<Utc>>, E> {
+        let seconds = if value.abs() >= MILLISECONDS_THRESHOLD as f64 {
+            value / 1000.0
+        } else {
+            value
+        };
+        let whole = seconds.floor();
+        let nanos = ((seconds - whole) * 1e9).round().min(999_999_999.0) as u32;
+        match DateTime::from_timestamp(whole as i64, nanos) {
+            Some(time) => Ok(Some(time)),
+            None => self.out_of_range(value),
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for TimestampVisitor {
+    type Value = Option<DateTime<Utc>>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a Unix timestamp as a number or a string")
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+        let (seconds, millis) = if value.unsigned_abs() >= MILLISECONDS_THRESHOLD as u64 {
+            (value.div_euclid(1000), value.rem_euclid(1000))
+        } else {
+            (value, 0)
+        };
+        match DateTime::from_timestamp(seconds, millis as u32 * 1_000_000) {
+            Some(time) => Ok(Some(time)),
+            None => self.out_of_range(value),
+        }
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+        match i64::try_from(value) {
+            Ok(value) => self.visit_i64(value),
+            Err(_) => self.out_of_range(value),
+        }
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Self::Value, E> {
+        self.time_from_number(value)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Ok(None);
+        }
+        match value.parse::<i64>() {
+            Ok(value) => self.visit_i64(value),
+            Err(_) => value
+                .parse::<f64>()
+                .map_err(|_| E::custom(format!("Invalid timestamp: {:?}", value)))
+                .and_then(|value| self.time_from_number(value)),
+        }
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
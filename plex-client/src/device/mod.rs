@@ -0,0 +1,21 @@
+use serde::Deserialize;
+
+/// Response from the Plex server's list devices endpoint
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlexDevices {
+    /// Devices that have played media from the server
+    #[serde(rename = "Device", default)]
+    pub device: Vec<PlexDevice>,
+}
+
+/// A device (player) known to the Plex server
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlexDevice {
+    /// ID of the device, used as `deviceID` in watch history
+    pub id: u64,
+    /// Name of the device (e.g. "Living Room TV")
+    #[serde(default)]
+    pub name: String,
+    /// Platform of the device (e.g. "Roku", "Android")
+    pub platform: Option<String>,
+}
@@ -0,0 +1,320 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use reqwest::blocking::{Client, RequestBuilder};
+use serde::Deserialize;
+
+use crate::client::{check_token, send_with_retry_after, MediaContainer};
+use crate::deserializers::deserialize_optional_viewed_at;
+use crate::media_item::{PlexMediaItem, PlexMediaItemGuidItem, PlexMediaItemMetadata};
+use crate::watch_history::PlexWatchHistoryItem;
+
+/// Base URL of the Plex Discover (metadata provider) API
+const DISCOVER_URL: &str = "https://discover.provider.plex.tv";
+
+/// Base URL of the Plex metadata provider, which knows the items matched by the Plex agent
+const METADATA_PROVIDER_URL: &str = "https://metadata.provider.plex.tv";
+
+/// Library section ID given to the items of the Discover watch history
+pub const STREAMING_LIBRARY_ID: &str = "streaming";
+
+/// Number of history entries requested per page
+const PAGE_SIZE: u32 = 100;
+
+/// A movie or show known to Plex Discover
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlexDiscoverItem {
+    /// Key of the item on Plex Discover, used by the watchlist endpoints
+    pub rating_key: String,
+    /// Type of the item (e.g. "movie", "show")
+    #[serde(rename(deserialize = "type"), default)]
+    pub item_type: String,
+    /// Title of the item
+    #[serde(default)]
+    pub title: String,
+    /// Release year of the item
+    pub year: Option<u32>,
+}
+
+/// A single search hit
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct PlexDiscoverSearchResult {
+    /// Item that matched the query
+    metadata: PlexDiscoverItem,
+}
+
+/// Search hits from one search provider
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct PlexDiscoverSearchResults {
+    /// Hits, best first
+    #[serde(default)]
+    search_result: Vec<PlexDiscoverSearchResult>,
+}
+
+/// Response from the Plex Discover search endpoint
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct PlexDiscoverSearch {
+    /// Hits grouped by search provider
+    #[serde(default)]
+    search_results: Vec<PlexDiscoverSearchResults>,
+}
+
+/// Entry of the account's watch history on Plex Discover
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlexDiscoverHistoryEntry {
+    /// Key of the item on Plex Discover
+    rating_key: Option<String>,
+    /// Type of the item (e.g. "movie", "episode")
+    #[serde(rename = "type", default)]
+    item_type: String,
+    /// Title of the item
+    #[serde(default)]
+    title: String,
+    /// When the item was watched
+    #[serde(default, deserialize_with = "deserialize_optional_viewed_at")]
+    viewed_at: Option<DateTime<Utc>>,
+    /// Key of the history entry
+    history_key: Option<String>,
+}
+
+/// Page of the account's watch history on Plex Discover
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct PlexDiscoverHistory {
+    /// Entries of this page, newest first
+    #[serde(default)]
+    metadata: Vec<PlexDiscoverHistoryEntry>,
+    /// Number of entries across all pages
+    #[serde(rename = "totalSize")]
+    total_size: Option<u32>,
+}
+
+/// Client for the Plex Discover API
+///
+/// Unlike `PlexClient`, which talks to a single Plex Media Server, this client
+/// talks to Plex's online metadata service and acts on the account the token
+/// belongs to, e.g. its watchlist.
+pub struct PlexDiscoverClient {
+    /// Base URL of the API
+    base_url: String,
+    /// Base URL of the metadata provider
+    metadata_url: String,
+    /// Plex authentication token of the account
+    token: String,
+    /// HTTP client for making requests
+    client: Client,
+}
+
+impl PlexDiscoverClient {
+    /// Creates a new PlexDiscoverClient
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - Plex authentication token of the account
+    pub fn new(token: String) -> Self {
+        Self {
+            base_url: DISCOVER_URL.to_string(),
+            metadata_url: METADATA_PROVIDER_URL.to_string(),
+            token,
+            client: Client::new(),
+        }
+    }
+
+    /// Sends requests to another base URL, such as a proxy or a test server
+    ///
+    /// Requests to the metadata provider go to the same URL.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self.metadata_url = self.base_url.clone();
+        self
+    }
+
+    /// Searches Plex Discover for movies matching a title
+    pub fn search_movies(&self, query: &str) -> Result<Vec<PlexDiscoverItem>> {
+        let request = self
+            .client
+            .get(format!("{}/library/search", self.base_url))
+            .query(&[
+                ("query", query),
+                ("searchTypes", "movies"),
+                ("searchProviders", "discover"),
+                ("limit", "10"),
+            ]);
+
+        let response = send_with_retry_after(self.request(request))
+            .context("Failed to send request to Plex Discover")?;
+        check_token(response.status(), "/library/search")?;
+        let container: MediaContainer<PlexDiscoverSearch> = response
+            .error_for_status()
+            .context("Failed to search Plex Discover")?
+            .json()
+            .context("Failed to parse response from Plex Discover")?;
+
+        Ok(container
+            .into_inner()
+            .search_results
+            .into_iter()
+            .flat_map(|results| results.search_result)
+            .map(|result| result.metadata)
+            .filter(|item| item.item_type == "movie")
+            .collect())
+    }
+
+    /// Adds an item to the account's watchlist
+    pub fn add_to_watchlist(&self, rating_key: &str) -> Result<()> {
+        let request = self
+            .client
+            .put(format!("{}/actions/addToWatchlist", self.base_url))
+            .query(&[("ratingKey", rating_key)]);
+
+        let response = send_with_retry_after(self.request(request))
+            .context("Failed to send request to Plex Discover")?;
+        check_token(response.status(), "/actions/addToWatchlist")?;
+        response
+            .error_for_status()
+            .context("Failed to add item to the Plex watchlist")?;
+        Ok(())
+    }
+
+    /// Returns an iterator over the movies the account watched on Plex, newest first
+    ///
+    /// This is the history Plex keeps for the account itself, which includes
+    /// films streamed through Plex's own apps rather than from a server.
+    /// Items have no library, so their library section ID is `STREAMING_LIBRARY_ID`.
+    pub fn watch_history_iter(&self) -> DiscoverHistoryIterator<'_> {
+        DiscoverHistoryIterator {
+            client: self,
+            current_items: Vec::new(),
+            offset: 0,
+            is_last_page: false,
+        }
+    }
+
+    /// Gets the metadata of an item by its Plex Discover key, including its GUIDs
+    pub fn get_media_item_metadata(&self, rating_key: &str) -> Result<PlexMediaItemMetadata> {
+        self.fetch_metadata(&self.base_url, rating_key)?
+            .with_context(|| format!("Item {} not found on Plex Discover", rating_key))
+    }
+
+    /// Resolves a GUID of the Plex agent (e.g. "plex://movie/5d776830880197001ec967c5")
+    /// to the GUIDs of the item on other databases, such as "imdb://tt0113277"
+    ///
+    /// Returns `None` when the metadata provider does not know the item.
+    pub fn resolve_plex_guid(&self, plex_guid: &str) -> Result<Option<Vec<PlexMediaItemGuidItem>>> {
+        let key = plex_guid.rsplit('/').next().unwrap_or(plex_guid);
+        Ok(self
+            .fetch_metadata(&self.metadata_url, key)?
+            .map(|metadata| metadata.guid))
+    }
+
+    /// Fetches the metadata of an item from a metadata endpoint, or `None` when it is unknown
+    fn fetch_metadata(&self, base_url: &str, key: &str) -> Result<Option<PlexMediaItemMetadata>> {
+        let endpoint = format!("/library/metadata/{}", key);
+        let request = self
+            .client
+            .get(format!("{}{}", base_url, endpoint))
+            .query(&[("includeGuids", "1")]);
+
+        let response = send_with_retry_after(self.request(request))
+            .context("Failed to send request to Plex Discover")?;
+        check_token(response.status(), &endpoint)?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let container: MediaContainer<PlexMediaItem> = response
+            .error_for_status()
+            .with_context(|| format!("Failed to get Plex metadata for {}", key))?
+            .json()
+            .context("Failed to parse response from Plex Discover")?;
+
+        let [metadata] = container.into_inner().metadata;
+        Ok(Some(metadata))
+    }
+
+    /// Fetches a page of the account's watch history
+    fn get_history_page(&self, offset: u32) -> Result<PlexDiscoverHistory> {
+        let endpoint = "/library/sections/history/all";
+        let start = offset.to_string();
+        let size = PAGE_SIZE.to_string();
+        let request = self
+            .client
+            .get(format!("{}{}", self.base_url, endpoint))
+            .query(&[
+                ("sort", "viewedAt:desc"),
+                ("X-Plex-Container-Start", start.as_str()),
+                ("X-Plex-Container-Size", size.as_str()),
+            ]);
+
+        let response = send_with_retry_after(self.request(request))
+            .context("Failed to send request to Plex Discover")?;
+        check_token(response.status(), endpoint)?;
+        let container: MediaContainer<PlexDiscoverHistory> = response
+            .error_for_status()
+            .context("Failed to fetch Plex Discover watch history")?
+            .json()
+            .context("Failed to parse response from Plex Discover")?;
+        Ok(container.into_inner())
+    }
+
+    /// Adds the token and JSON headers to a request
+    fn request(&self, request: RequestBuilder) -> RequestBuilder {
+        request
+            .header("X-Plex-Token", &self.token)
+            .header("Accept", "application/json")
+    }
+}
+
+/// Iterator over the account's Plex Discover watch history with automatic pagination
+///
+/// Only movies are yielded.
+pub struct DiscoverHistoryIterator<'a> {
+    client: &'a PlexDiscoverClient,
+    current_items: Vec<PlexWatchHistoryItem>,
+    offset: u32,
+    is_last_page: bool,
+}
+
+impl Iterator for DiscoverHistoryIterator<'_> {
+    type Item = Result<PlexWatchHistoryItem>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.current_items.is_empty() {
+            if self.is_last_page {
+                return None;
+            }
+
+            let page = match self.client.get_history_page(self.offset) {
+                Ok(page) => page,
+                Err(e) => return Some(Err(e)),
+            };
+
+            let received = page.metadata.len() as u32;
+            self.offset += received;
+            self.is_last_page =
+                received < PAGE_SIZE || page.total_size.is_some_and(|total| self.offset >= total);
+
+            // Store the page reversed so items can be popped in order
+            self.current_items = page
+                .metadata
+                .into_iter()
+                .filter(|entry| entry.item_type == "movie")
+                .map(|entry| PlexWatchHistoryItem {
+                    title: entry.title,
+                    rating_key: entry.rating_key,
+                    library_section_id: STREAMING_LIBRARY_ID.to_string(),
+                    viewed_at: entry.viewed_at,
+                    device_id: None,
+                    device: None,
+                    history_key: entry.history_key,
+                })
+                .rev()
+                .collect();
+        }
+
+        self.current_items.pop().map(Ok)
+    }
+}
@@ -0,0 +1,124 @@
+use std::fmt;
+
+use serde::Deserialize;
+
+/// Response from the Plex server's identity endpoint
+///
+/// The identity endpoint does not require authentication, which makes it
+/// useful for checking that a server is reachable before using the token.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlexServerIdentity {
+    /// Unique identifier of the Plex Media Server
+    pub machine_identifier: String,
+    /// Version of the Plex Media Server (e.g. "1.40.2.8395-c67dce28e")
+    pub version: String,
+    /// Whether the server is claimed by a Plex account
+    #[serde(default)]
+    pub claimed: bool,
+}
+
+impl PlexServerIdentity {
+    /// Returns the parsed version of the server, if it has the usual format
+    pub fn server_version(&self) -> Option<ServerVersion> {
+        ServerVersion::parse(&self.version)
+    }
+}
+
+/// Oldest Plex Media Server version the clients support
+pub const MINIMUM_SERVER_VERSION: ServerVersion = ServerVersion::new(1, 16, 0, 0);
+
+/// Version from which library listings return `Guid` items with `includeGuids=1`
+const INCLUDE_GUIDS_VERSION: ServerVersion = ServerVersion::new(1, 21, 0, 0);
+
+/// Version from which watch history honors the pagination headers
+///
+/// Older servers ignore `X-Plex-Container-Start`/`X-Plex-Container-Size`
+/// headers on the history endpoint and only honor them as query parameters.
+const HEADER_PAGINATION_VERSION: ServerVersion = ServerVersion::new(1, 18, 0, 0);
+
+/// Version of a Plex Media Server (e.g. "1.40.2.8395-c67dce28e")
+///
+/// Versions are compared by their numeric parts; the build hash is ignored.
+///
+/// # Example
+///
+/// ```rust
+/// use plex_client::identity::{ServerVersion, MINIMUM_SERVER_VERSION};
+///
+/// let version = ServerVersion::parse("1.40.2.8395-c67dce28e").unwrap();
+/// assert_eq!(version, ServerVersion::new(1, 40, 2, 8395));
+/// assert!(version >= MINIMUM_SERVER_VERSION);
+/// assert!(ServerVersion::parse("1.9.7.4460") < ServerVersion::parse("1.10.0.0"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ServerVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+    pub build: u32,
+}
+
+impl ServerVersion {
+    /// Creates a version from its numeric parts
+    pub const fn new(major: u32, minor: u32, patch: u32, build: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+            build,
+        }
+    }
+
+    /// Parses a version as reported by the identity endpoint
+    ///
+    /// Missing trailing parts count as 0, so "1.20" parses as 1.20.0.0.
+    pub fn parse(version: &str) -> Option<Self> {
+        let numbers = version.split('-').next()?;
+        let mut parts = [0; 4];
+        for (i, part) in numbers.split('.').enumerate() {
+            *parts.get_mut(i)? = part.parse().ok()?;
+        }
+        let [major, minor, patch, build] = parts;
+        Some(Self::new(major, minor, patch, build))
+    }
+
+    /// Returns the API features available on a server of this version
+    pub fn capabilities(&self) -> ServerCapabilities {
+        ServerCapabilities {
+            include_guids: *self >= INCLUDE_GUIDS_VERSION,
+            header_pagination: *self >= HEADER_PAGINATION_VERSION,
+        }
+    }
+}
+
+impl fmt::Display for ServerVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}.{}",
+            self.major, self.minor, self.patch, self.build
+        )
+    }
+}
+
+/// API features that depend on the version of the server
+///
+/// The default assumes a current server, for clients that never detected
+/// the version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServerCapabilities {
+    /// Library listings can include `Guid` items (`includeGuids=1`)
+    pub include_guids: bool,
+    /// Watch history pages are selected with headers rather than query parameters
+    pub header_pagination: bool,
+}
+
+impl Default for ServerCapabilities {
+    fn default() -> Self {
+        Self {
+            include_guids: true,
+            header_pagination: true,
+        }
+    }
+}
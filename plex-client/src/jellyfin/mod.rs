@@ -0,0 +1,385 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use reqwest::blocking::Client;
+use serde::Deserialize;
+
+use crate::client::send_with_retry_after;
+use crate::library::{PlexLibrarySection, PlexLibrarySectionsDirectory};
+use crate::media_item::{PlexMediaItemGuidItem, PlexMediaItemMetadata, PlexMediaItemTag};
+use crate::watch_history::PlexWatchHistoryItem;
+
+/// Number of items requested per page
+const PAGE_SIZE: u32 = 100;
+
+/// Fields requested for every item, beyond the defaults
+const ITEM_FIELDS: &str =
+    "ProviderIds,OriginalTitle,PremiereDate,DateCreated,Studios,Genres,Tags,People,ParentId";
+
+/// User as returned by the `/Users` endpoint
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct JellyfinUser {
+    id: String,
+    name: String,
+}
+
+/// Library as returned by the `/Users/{id}/Views` endpoint
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct JellyfinView {
+    id: String,
+    name: String,
+    #[serde(default)]
+    collection_type: String,
+}
+
+/// Page of items
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct JellyfinItems<T> {
+    items: Vec<T>,
+    #[serde(default)]
+    total_record_count: u32,
+}
+
+/// Per-user data of an item
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct JellyfinUserData {
+    /// Last time the user played the item (ISO 8601)
+    last_played_date: Option<String>,
+    /// Rating given by the user
+    rating: Option<f32>,
+}
+
+/// Name of a studio, person, etc.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct JellyfinNamed {
+    name: String,
+    #[serde(rename = "Type", default)]
+    kind: String,
+}
+
+/// Item as returned by the `/Users/{id}/Items` endpoints
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct JellyfinItem {
+    id: String,
+    #[serde(default)]
+    name: String,
+    original_title: Option<String>,
+    #[serde(rename = "Type", default)]
+    item_type: String,
+    extra_type: Option<String>,
+    production_year: Option<u32>,
+    premiere_date: Option<String>,
+    date_created: Option<String>,
+    /// Runtime in ticks of 100 nanoseconds
+    run_time_ticks: Option<u64>,
+    #[serde(default)]
+    provider_ids: HashMap<String, String>,
+    #[serde(default)]
+    studios: Vec<JellyfinNamed>,
+    #[serde(default)]
+    people: Vec<JellyfinNamed>,
+    #[serde(default)]
+    genres: Vec<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    parent_id: Option<String>,
+    #[serde(default)]
+    user_data: JellyfinUserData,
+}
+
+/// Client for the Jellyfin (and Emby) API
+///
+/// Jellyfin does not keep a full play history; it records the last time each
+/// item was played. Played items are mapped to the same structures as
+/// `PlexClient`, with one history entry per item at its last played date, so
+/// they can be fed to the same export pipeline.
+pub struct JellyfinClient {
+    /// Base URL of the Jellyfin server (e.g., "http://192.168.1.100:8096")
+    base_url: String,
+    /// Jellyfin API key or access token
+    api_key: String,
+    /// ID of the user whose played items are read
+    user_id: String,
+    /// HTTP client for making requests
+    client: Client,
+}
+
+impl JellyfinClient {
+    /// Creates a new JellyfinClient, resolving the user by name or ID
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use plex_client::jellyfin::JellyfinClient;
+    ///
+    /// let client = JellyfinClient::new(
+    ///     "http://192.168.1.100:8096".to_string(),
+    ///     "your-api-key".to_string(),
+    ///     "alice",
+    /// )?;
+    /// for item in client.watch_history_iter("f137a2dd21bbc1b99aa5c0f6bf02a805") {
+    ///     println!("Watched: {}", item?.title);
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn new(base_url: String, api_key: String, user: &str) -> Result<Self> {
+        let mut client = Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+            user_id: String::new(),
+            client: Client::new(),
+        };
+
+        let users: Vec<JellyfinUser> = client
+            .get("/Users", &[])
+            .context("Failed to list Jellyfin users")?;
+        client.user_id = users
+            .iter()
+            .find(|u| u.id == user || u.name.eq_ignore_ascii_case(user))
+            .map(|u| u.id.clone())
+            .with_context(|| {
+                format!(
+                    "Jellyfin user '{}' not found. Available users: {}",
+                    user,
+                    users
+                        .iter()
+                        .map(|u| u.name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })?;
+
+        Ok(client)
+    }
+
+    /// Gets the base URL of the Jellyfin server
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Gets the user's libraries, in the same shape as `PlexClient::get_library_sections`
+    ///
+    /// Jellyfin's "movies" collection type is reported as Plex's "movie" type.
+    pub fn get_library_sections(&self) -> Result<PlexLibrarySection> {
+        let views: JellyfinItems<JellyfinView> = self
+            .get(&format!("/Users/{}/Views", self.user_id), &[])
+            .context("Failed to get Jellyfin libraries")?;
+
+        Ok(PlexLibrarySection {
+            directory: views
+                .items
+                .into_iter()
+                .map(|view| PlexLibrarySectionsDirectory {
+                    key: view.id,
+                    section_type: match view.collection_type.as_str() {
+                        "movies" => "movie".to_string(),
+                        "tvshows" => "show".to_string(),
+                        other => other.to_string(),
+                    },
+                    title: view.name,
+                    location: Vec::new(),
+                })
+                .collect(),
+        })
+    }
+
+    /// Gets the metadata of an item, in the same shape as `PlexClient::get_media_item_metadata`
+    pub fn get_media_item_metadata(&self, item_id: &str) -> Result<PlexMediaItemMetadata> {
+        let item: JellyfinItem = self
+            .get(
+                &format!("/Users/{}/Items/{}", self.user_id, item_id),
+                &[("Fields", ITEM_FIELDS)],
+            )
+            .with_context(|| format!("Failed to get Jellyfin item {}", item_id))?;
+
+        let tags = |values: Vec<String>| {
+            values
+                .into_iter()
+                .map(|tag| PlexMediaItemTag { tag })
+                .collect()
+        };
+
+        // Provider IDs are exposed as Plex-style GUIDs, IMDb first
+        let mut guid = Vec::new();
+        for (provider, scheme) in [("Imdb", "imdb"), ("Tmdb", "tmdb"), ("Tvdb", "tvdb")] {
+            if let Some(id) = item
+                .provider_ids
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(provider))
+                .map(|(_, id)| id)
+            {
+                guid.push(PlexMediaItemGuidItem {
+                    id: format!("{}://{}", scheme, id),
+                });
+            }
+        }
+
+        Ok(PlexMediaItemMetadata {
+            item_type: item.item_type.to_lowercase(),
+            rating_key: Some(item_id.to_string()),
+            subtype: item.extra_type,
+            extra_type: None,
+            title: item.name,
+            original_title: item.original_title,
+            edition_title: None,
+            year: item.production_year,
+            originally_available_at: item
+                .premiere_date
+                .map(|date| date.chars().take(10).collect()),
+            studio: item.studios.into_iter().next().map(|studio| studio.name),
+            duration: item.run_time_ticks.map(|ticks| ticks / 10_000),
+            user_rating: item.user_data.rating,
+            added_at: item.date_created.as_deref().and_then(parse_date),
+            plex_guid: None,
+            guid,
+            director: item
+                .people
+                .into_iter()
+                .filter(|person| person.kind == "Director")
+                .map(|person| PlexMediaItemTag { tag: person.name })
+                .collect(),
+            genre: tags(item.genres),
+            collection: Vec::new(),
+            label: tags(item.tags),
+        })
+    }
+
+    /// Returns an iterator over the played items of a library, most recently played first
+    pub fn watch_history_iter(&self, library_id: &str) -> JellyfinHistoryIterator<'_> {
+        JellyfinHistoryIterator {
+            client: self,
+            library_id: library_id.to_string(),
+            current_items: Vec::new(),
+            offset: 0,
+            is_last_page: false,
+        }
+    }
+
+    /// Fetches a page of played items of a library
+    fn get_played_items_page(
+        &self,
+        offset: u32,
+        library_id: &str,
+    ) -> Result<JellyfinItems<JellyfinItem>> {
+        let start = offset.to_string();
+        let limit = PAGE_SIZE.to_string();
+
+        self.get(
+            &format!("/Users/{}/Items", self.user_id),
+            &[
+                ("ParentId", library_id),
+                ("IsPlayed", "true"),
+                ("Recursive", "true"),
+                ("IncludeItemTypes", "Movie"),
+                ("SortBy", "DatePlayed"),
+                ("SortOrder", "Descending"),
+                ("Fields", ITEM_FIELDS),
+                ("StartIndex", start.as_str()),
+                ("Limit", limit.as_str()),
+            ],
+        )
+        .context("Failed to fetch Jellyfin played items page")
+    }
+
+    /// Makes an authenticated GET request and parses the JSON response
+    fn get<T>(&self, endpoint: &str, query_params: &[(&str, &str)]) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let url = format!("{}{}", self.base_url, endpoint);
+
+        let request = self
+            .client
+            .get(&url)
+            .header("X-Emby-Token", &self.api_key)
+            .header("Accept", "application/json")
+            .query(query_params);
+        let response = send_with_retry_after(request)
+            .with_context(|| format!("Failed to send request to endpoint: {}", endpoint))?;
+
+        let response = response.error_for_status().with_context(|| {
+            format!(
+                "Jellyfin server returned an error for endpoint: {}",
+                endpoint
+            )
+        })?;
+
+        response
+            .json()
+            .with_context(|| format!("Failed to parse response from endpoint: {}", endpoint))
+    }
+}
+
+/// Iterator over played Jellyfin items with automatic pagination
+pub struct JellyfinHistoryIterator<'a> {
+    client: &'a JellyfinClient,
+    library_id: String,
+    current_items: Vec<PlexWatchHistoryItem>,
+    offset: u32,
+    is_last_page: bool,
+}
+
+impl Iterator for JellyfinHistoryIterator<'_> {
+    type Item = Result<PlexWatchHistoryItem>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.current_items.is_empty() {
+            if self.is_last_page {
+                return None;
+            }
+
+            let page = match self
+                .client
+                .get_played_items_page(self.offset, &self.library_id)
+            {
+                Ok(page) => page,
+                Err(e) => return Some(Err(e)),
+            };
+
+            self.offset += page.items.len() as u32;
+            self.is_last_page = page.items.is_empty() || self.offset >= page.total_record_count;
+
+            // Store the page reversed so items can be popped in order
+            let library_id = &self.library_id;
+            self.current_items = page
+                .items
+                .into_iter()
+                .map(|item| PlexWatchHistoryItem {
+                    title: item.name,
+                    rating_key: Some(item.id),
+                    library_section_id: item.parent_id.unwrap_or_else(|| library_id.clone()),
+                    viewed_at: item
+                        .user_data
+                        .last_played_date
+                        .as_deref()
+                        .and_then(parse_date),
+                    device_id: None,
+                    device: None,
+                    history_key: None,
+                })
+                .rev()
+                .collect();
+        }
+
+        self.current_items.pop().map(Ok)
+    }
+}
+
+/// Parses a played or created date, ISO 8601 in UTC (e.g. "2024-01-15T20:31:07.0000000Z")
+///
+/// Dates without a time zone are taken as UTC.
+fn parse_date(date: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(date)
+        .map(|date| date.with_timezone(&Utc))
+        .or_else(|_| {
+            NaiveDateTime::parse_from_str(date, "%Y-%m-%dT%H:%M:%S%.f").map(|date| date.and_utc())
+        })
+        .ok()
+}
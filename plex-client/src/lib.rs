@@ -0,0 +1,81 @@
+//! Client for the Plex Media Server and plex.tv APIs
+//!
+//! This crate contains the reusable parts of `plex-to-letterboxd`: the HTTP
+//! client, the response models and the paginated watch history iterator.
+//! Most consumers only need the `prelude`:
+//!
+//! ```no_run
+//! # #[cfg(feature = "blocking")]
+//! # {
+//! use plex_client::prelude::*;
+//!
+//! let client = PlexClient::new(
+//!     "http://192.168.1.100:32400".to_string(),
+//!     "your-token-here".to_string(),
+//! )?;
+//! for item in client.watch_history_iter("1") {
+//!     let item: PlexWatchHistoryItem = item?;
+//!     println!("Watched: {} at {:?}", item.title, item.viewed_at);
+//! }
+//! # }
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+/// Server account types and structures
+pub mod account;
+/// Plex API client module
+pub mod client;
+/// Utility deserializers for Plex API responses
+pub mod deserializers;
+/// Server device (player) types and structures
+pub mod device;
+/// Plex Discover API client (search, watchlist and streaming history)
+#[cfg(feature = "blocking")]
+pub mod discover;
+
+/// Server identity types and structures
+pub mod identity;
+/// Jellyfin/Emby API client, an alternative source of watch history
+#[cfg(feature = "blocking")]
+pub mod jellyfin;
+/// Library sections types and structures
+pub mod library;
+/// Media item types and structures
+pub mod media_item;
+/// plex.tv account API client (PIN login, the account's servers)
+#[cfg(feature = "blocking")]
+pub mod plex_tv;
+/// Common abstraction over sources of watch history
+#[cfg(feature = "blocking")]
+pub mod source;
+/// Tautulli API client, an alternative source of watch history
+#[cfg(feature = "blocking")]
+pub mod tautulli;
+/// Watch history types and structures
+pub mod watch_history;
+
+/// Commonly used types, for glob importing
+pub mod prelude {
+    pub use crate::account::{PlexAccount, PlexAccounts, PlexMyPlexAccount};
+    #[cfg(feature = "async")]
+    pub use crate::client::AsyncPlexClient;
+    pub use crate::client::{
+        ConnectionSettings, Endpoint, MediaContainer, ProxyAuth, Query, TokenRejectedError,
+    };
+    #[cfg(feature = "blocking")]
+    pub use crate::client::{PlexClient, ResponseCache, ResponseCapture, WatchHistoryIterator};
+    #[cfg(feature = "blocking")]
+    pub use crate::discover::{PlexDiscoverClient, PlexDiscoverItem};
+    pub use crate::identity::{PlexServerIdentity, ServerCapabilities, ServerVersion};
+    #[cfg(feature = "blocking")]
+    pub use crate::jellyfin::JellyfinClient;
+    pub use crate::library::{PlexLibraryItems, PlexLibrarySection, PlexLibrarySectionsDirectory};
+    pub use crate::media_item::{PlexMediaItem, PlexMediaItemMetadata};
+    #[cfg(feature = "blocking")]
+    pub use crate::plex_tv::{PlexTvClient, PlexTvPin, PlexTvServer};
+    #[cfg(feature = "blocking")]
+    pub use crate::source::HistorySource;
+    #[cfg(feature = "blocking")]
+    pub use crate::tautulli::TautulliClient;
+    pub use crate::watch_history::{PlexWatchHistory, PlexWatchHistoryItem};
+}
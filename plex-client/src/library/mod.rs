@@ -0,0 +1,46 @@
+use serde::Deserialize;
+
+use crate::media_item::PlexMediaItemMetadata;
+
+// Location of a library section directory (e.g. Movies, TV Shows, etc.)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlexLibrarySectionDirectoryLocation {
+    pub id: u32,
+}
+
+// Directory for a library section (e.g. Movies, TV Shows, etc.)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlexLibrarySectionsDirectory {
+    // Key of the library section, used as its ID in other endpoints (e.g. "1")
+    pub key: String,
+
+    // Type of the library section (e.g. "movie", "show", "artist")
+    #[serde(rename(deserialize = "type"), default)]
+    pub section_type: String,
+
+    // Title of the directory (e.g. Movies, TV Shows, etc.)
+    pub title: String,
+
+    #[serde(rename(deserialize = "Location"), default)]
+    // Locations of the directory (e.g. Movies, TV Shows, etc.)
+    pub location: Vec<PlexLibrarySectionDirectoryLocation>,
+}
+
+// Response from the Plex server's list library sections endpoint
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PlexLibrarySection {
+    // Directories for the library section (e.g. Movies, TV Shows, etc.)
+    pub directory: Vec<PlexLibrarySectionsDirectory>,
+}
+
+// Response from the Plex server's list library section items endpoint
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PlexLibraryItems {
+    // Metadata of every item in the library section
+    #[serde(default)]
+    pub metadata: Vec<PlexMediaItemMetadata>,
+}
@@ -0,0 +1,82 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::deserializers::deserialize_optional_viewed_at;
+
+/// Response from the Plex server's list media item metadata endpoint
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PlexMediaItem {
+    pub metadata: [PlexMediaItemMetadata; 1],
+}
+
+/// Metadata for a media item
+///
+/// Serializes with the same field names it is deserialized from, so cached
+/// metadata can be read back.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlexMediaItemMetadata {
+    /// Type of the media item (e.g. "movie", "episode", "clip")
+    #[serde(rename = "type", default)]
+    pub item_type: String,
+    /// Key of the media item, used as its ID in other endpoints (e.g. "12345")
+    pub rating_key: Option<String>,
+    /// Subtype of the media item, set for extras (e.g. "trailer", "behindTheScenes")
+    pub subtype: Option<String>,
+    /// Kind of extra, set when the media item is an extra of another item
+    pub extra_type: Option<u32>,
+    /// Title of the media item as shown in the library (may be localized)
+    #[serde(default)]
+    pub title: String,
+    /// Original title of the media item (e.g. the non-localized title of a foreign film)
+    pub original_title: Option<String>,
+    /// Edition of the media item as set in Plex (e.g. "Director's Cut")
+    pub edition_title: Option<String>,
+    /// Release year of the media item
+    pub year: Option<u32>,
+    /// Original release date of the media item (YYYY-MM-DD)
+    pub originally_available_at: Option<String>,
+    /// Studio that produced the media item
+    pub studio: Option<String>,
+    /// Duration of the media item in milliseconds
+    pub duration: Option<u64>,
+    /// Rating given by the user on a 0-10 scale
+    pub user_rating: Option<f32>,
+    /// When the media item was added to the library
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_viewed_at",
+        serialize_with = "chrono::serde::ts_seconds_option::serialize"
+    )]
+    pub added_at: Option<DateTime<Utc>>,
+    /// GUID of the item's match by the Plex agent (e.g. "plex://movie/5d776830880197001ec967c5")
+    #[serde(rename = "guid")]
+    pub plex_guid: Option<String>,
+    #[serde(rename = "Guid", default)]
+    pub guid: Vec<PlexMediaItemGuidItem>,
+    /// Directors of the media item
+    #[serde(rename = "Director", default)]
+    pub director: Vec<PlexMediaItemTag>,
+    /// Genres of the media item
+    #[serde(rename = "Genre", default)]
+    pub genre: Vec<PlexMediaItemTag>,
+    /// Collections the media item belongs to
+    #[serde(rename = "Collection", default)]
+    pub collection: Vec<PlexMediaItemTag>,
+    /// Labels attached to the media item
+    #[serde(rename = "Label", default)]
+    pub label: Vec<PlexMediaItemTag>,
+}
+
+/// GUID item for a media item (contains identifiers like IMDb ID)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PlexMediaItemGuidItem {
+    pub id: String,
+}
+
+/// Tag item for a media item (used for directors, genres, labels, etc.)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PlexMediaItemTag {
+    pub tag: String,
+}
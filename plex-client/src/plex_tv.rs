@@ -0,0 +1,187 @@
+use anyhow::{Context, Result};
+use reqwest::blocking::{Client, RequestBuilder};
+use serde::Deserialize;
+
+use crate::client::send_with_retry_after;
+
+/// Base URL of the plex.tv API
+const PLEX_TV_URL: &str = "https://plex.tv";
+
+/// Product name reported to plex.tv
+const PRODUCT: &str = "plex-to-letterboxd";
+
+/// PIN used to link this application to a Plex account
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlexTvPin {
+    /// ID of the PIN, used to poll for the authentication token
+    pub id: u64,
+    /// Code the user enters (or that is embedded in the auth URL)
+    pub code: String,
+    /// Authentication token, set once the user has approved the PIN
+    pub auth_token: Option<String>,
+}
+
+/// A Plex Media Server the account can access, from plex.tv's resources
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlexTvServer {
+    /// Name of the server (e.g. "Living Room")
+    pub name: String,
+    /// Comma separated roles of the resource; servers include "server"
+    #[serde(default)]
+    pub provides: String,
+    /// Whether the account owns the server, rather than having it shared with it
+    #[serde(default)]
+    pub owned: bool,
+    /// Token for this server, which differs from the account's token on shared servers
+    pub access_token: Option<String>,
+    /// Addresses the server can be reached at
+    #[serde(default)]
+    pub connections: Vec<PlexTvConnection>,
+}
+
+impl PlexTvServer {
+    /// Returns the URL most likely to work: a local address first, relays last
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use plex_client::plex_tv::PlexTvServer;
+    ///
+    /// let json = r#"{
+    ///     "name": "Living Room",
+    ///     "provides": "server",
+    ///     "connections": [
+    ///         {"uri": "https://1-2-3-4.abc.plex.direct:32400", "local": false, "relay": true},
+    ///         {"uri": "http://192.168.1.100:32400", "local": true, "relay": false}
+    ///     ]
+    /// }"#;
+    /// let server: PlexTvServer = serde_json::from_str(json)?;
+    /// assert_eq!(server.preferred_url(), Some("http://192.168.1.100:32400"));
+    /// # Ok::<(), serde_json::Error>(())
+    /// ```
+    pub fn preferred_url(&self) -> Option<&str> {
+        self.connections
+            .iter()
+            .min_by_key(|connection| (connection.relay, !connection.local))
+            .map(|connection| connection.uri.as_str())
+    }
+}
+
+/// An address a server can be reached at
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlexTvConnection {
+    /// URL of the server at this address (e.g. "http://192.168.1.100:32400")
+    pub uri: String,
+    /// Whether the address is on the server's local network
+    #[serde(default)]
+    pub local: bool,
+    /// Whether the address goes through Plex's relay
+    #[serde(default)]
+    pub relay: bool,
+}
+
+/// Client for the plex.tv account API
+///
+/// Unlike `PlexClient`, which talks to a single Plex Media Server, this client
+/// talks to plex.tv itself and is used for account-level operations such as
+/// PIN-based login and finding the account's servers.
+pub struct PlexTvClient {
+    /// Base URL of plex.tv (e.g. "https://plex.tv")
+    base_url: String,
+    /// Identifier of this application instance, reported as `X-Plex-Client-Identifier`
+    client_identifier: String,
+    /// HTTP client for making requests
+    client: Client,
+}
+
+impl PlexTvClient {
+    /// Creates a new PlexTvClient
+    ///
+    /// # Arguments
+    ///
+    /// * `client_identifier` - A stable, unique identifier for this application instance
+    pub fn new(client_identifier: String) -> Self {
+        Self {
+            base_url: PLEX_TV_URL.to_string(),
+            client_identifier,
+            client: Client::new(),
+        }
+    }
+
+    /// Sends requests to another base URL, such as a proxy or a test server
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Creates a new login PIN
+    ///
+    /// The user approves the PIN by opening the URL returned by `auth_url`,
+    /// after which `check_pin` returns the authentication token.
+    pub fn create_pin(&self) -> Result<PlexTvPin> {
+        let request = self
+            .client
+            .post(format!("{}/api/v2/pins", self.base_url))
+            .query(&[("strong", "true")]);
+
+        self.send(request).context("Failed to create plex.tv PIN")
+    }
+
+    /// Checks a login PIN, returning it with `auth_token` set once approved
+    pub fn check_pin(&self, pin: &PlexTvPin) -> Result<PlexTvPin> {
+        let request = self
+            .client
+            .get(format!("{}/api/v2/pins/{}", self.base_url, pin.id))
+            .query(&[("code", pin.code.as_str())]);
+
+        self.send(request).context("Failed to check plex.tv PIN")
+    }
+
+    /// Gets the Plex Media Servers the account owns or has been shared, for picking one without typing its URL
+    pub fn get_servers(&self, token: &str) -> Result<Vec<PlexTvServer>> {
+        let request = self
+            .client
+            .get(format!("{}/api/v2/resources", self.base_url))
+            .query(&[("includeHttps", "1"), ("includeRelay", "1")])
+            .header("X-Plex-Token", token);
+
+        let resources: Vec<PlexTvServer> = self
+            .send(request)
+            .context("Failed to list the account's servers on plex.tv")?;
+        Ok(resources
+            .into_iter()
+            .filter(|resource| resource.provides.split(',').any(|role| role == "server"))
+            .collect())
+    }
+
+    /// Returns the URL the user opens to approve a login PIN
+    pub fn auth_url(&self, pin: &PlexTvPin) -> String {
+        format!(
+            "https://app.plex.tv/auth#?clientID={}&code={}&context%5Bdevice%5D%5Bproduct%5D={}",
+            self.client_identifier, pin.code, PRODUCT
+        )
+    }
+
+    /// Sends a request with the headers plex.tv requires and parses the JSON response
+    fn send<T>(&self, request: RequestBuilder) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let request = request
+            .header("Accept", "application/json")
+            .header("X-Plex-Product", PRODUCT)
+            .header("X-Plex-Client-Identifier", &self.client_identifier);
+        let response =
+            send_with_retry_after(request).context("Failed to send request to plex.tv")?;
+
+        let response = response
+            .error_for_status()
+            .context("plex.tv returned an error")?;
+
+        response
+            .json()
+            .context("Failed to parse response from plex.tv")
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+
+use crate::client::PlexClient;
+use crate::discover::{PlexDiscoverClient, STREAMING_LIBRARY_ID};
+use crate::jellyfin::JellyfinClient;
+use crate::library::{PlexLibrarySection, PlexLibrarySectionsDirectory};
+use crate::media_item::PlexMediaItemMetadata;
+use crate::tautulli::TautulliClient;
+use crate::watch_history::PlexWatchHistoryItem;
+
+/// Iterator over the watch history of a source
+pub type HistoryIter<'a> = Box<dyn Iterator<Item = Result<PlexWatchHistoryItem>> + Send + 'a>;
+
+/// A source of watch history (a Plex server, Tautulli, Jellyfin, ...)
+///
+/// Every source exposes its libraries, history and metadata with the Plex
+/// structures, so exporters can consume any of them through this one trait.
+/// History and metadata are separate steps so that history entries can be
+/// filtered (e.g. by title) before their metadata is requested.
+///
+/// Sources are `Sync` and their history iterators `Send`, so that history
+/// can be paginated on one thread while metadata is requested from others.
+///
+/// # Example
+///
+/// ```no_run
+/// use plex_client::client::PlexClient;
+/// use plex_client::source::HistorySource;
+///
+/// fn count_watched(source: &dyn HistorySource, library_section_id: &str) -> usize {
+///     source.history(library_section_id).filter(|item| item.is_ok()).count()
+/// }
+///
+/// let client = PlexClient::new(
+///     "http://192.168.1.100:32400".to_string(),
+///     "your-token-here".to_string(),
+/// )?;
+/// println!("{} items watched", count_watched(&client, "1"));
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub trait HistorySource: Sync {
+    /// Returns a human-readable name of the source, used in progress and error messages
+    fn name(&self) -> String;
+
+    /// Gets the libraries of the source
+    fn library_sections(&self) -> Result<PlexLibrarySection>;
+
+    /// Returns an iterator over the watch history of a library, newest first
+    fn history<'a>(&'a self, library_section_id: &str) -> HistoryIter<'a>;
+
+    /// Gets the metadata of a history item by its rating key
+    fn metadata(&self, rating_key: &str) -> Result<PlexMediaItemMetadata>;
+
+    /// Returns whether the history is split in libraries, one of which is selected
+    ///
+    /// Sources whose history is not (e.g. the streaming history of an account)
+    /// list a single library, which is used whatever library is selected on
+    /// the other sources.
+    fn has_libraries(&self) -> bool {
+        true
+    }
+}
+
+impl HistorySource for PlexClient {
+    fn name(&self) -> String {
+        self.base_url().to_string()
+    }
+
+    fn library_sections(&self) -> Result<PlexLibrarySection> {
+        self.get_library_sections()
+    }
+
+    /// Lists the history with the names of the devices it was played on
+    ///
+    /// Device names only serve to filter the history, so it is still listed,
+    /// without them, when the server does not list its devices.
+    fn history<'a>(&'a self, library_section_id: &str) -> HistoryIter<'a> {
+        let devices: HashMap<u64, String> = self
+            .get_devices()
+            .map(|devices| {
+                devices
+                    .device
+                    .into_iter()
+                    .map(|device| (device.id, device.name))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Box::new(
+            self.watch_history_iter(library_section_id)
+                .map(move |item| {
+                    item.map(|mut item| {
+                        item.device = item.device_id.and_then(|id| devices.get(&id).cloned());
+                        item
+                    })
+                }),
+        )
+    }
+
+    fn metadata(&self, rating_key: &str) -> Result<PlexMediaItemMetadata> {
+        let [metadata] = self
+            .get_media_item_metadata(rating_key.to_string())?
+            .metadata;
+        Ok(metadata)
+    }
+}
+
+impl HistorySource for TautulliClient {
+    fn name(&self) -> String {
+        format!("Tautulli ({})", self.base_url())
+    }
+
+    fn library_sections(&self) -> Result<PlexLibrarySection> {
+        self.get_library_sections()
+    }
+
+    fn history<'a>(&'a self, library_section_id: &str) -> HistoryIter<'a> {
+        Box::new(self.watch_history_iter(library_section_id))
+    }
+
+    fn metadata(&self, rating_key: &str) -> Result<PlexMediaItemMetadata> {
+        self.get_media_item_metadata(rating_key)
+    }
+}
+
+impl HistorySource for JellyfinClient {
+    fn name(&self) -> String {
+        format!("Jellyfin ({})", self.base_url())
+    }
+
+    fn library_sections(&self) -> Result<PlexLibrarySection> {
+        self.get_library_sections()
+    }
+
+    fn history<'a>(&'a self, library_section_id: &str) -> HistoryIter<'a> {
+        Box::new(self.watch_history_iter(library_section_id))
+    }
+
+    fn metadata(&self, item_id: &str) -> Result<PlexMediaItemMetadata> {
+        self.get_media_item_metadata(item_id)
+    }
+}
+
+impl HistorySource for PlexDiscoverClient {
+    fn name(&self) -> String {
+        "Plex Discover (streaming history)".to_string()
+    }
+
+    fn library_sections(&self) -> Result<PlexLibrarySection> {
+        Ok(PlexLibrarySection {
+            directory: vec![PlexLibrarySectionsDirectory {
+                key: STREAMING_LIBRARY_ID.to_string(),
+                section_type: "movie".to_string(),
+                title: "Plex streaming".to_string(),
+                location: Vec::new(),
+            }],
+        })
+    }
+
+    fn history<'a>(&'a self, _library_section_id: &str) -> HistoryIter<'a> {
+        Box::new(self.watch_history_iter())
+    }
+
+    fn metadata(&self, rating_key: &str) -> Result<PlexMediaItemMetadata> {
+        self.get_media_item_metadata(rating_key)
+    }
+
+    fn has_libraries(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,374 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use reqwest::blocking::Client;
+use serde::{Deserialize, Deserializer};
+
+use crate::client::send_with_retry_after;
+use crate::deserializers::deserialize_optional_viewed_at;
+use crate::library::{PlexLibrarySection, PlexLibrarySectionsDirectory};
+use crate::media_item::{PlexMediaItemGuidItem, PlexMediaItemMetadata, PlexMediaItemTag};
+use crate::watch_history::PlexWatchHistoryItem;
+
+/// Number of history rows requested per page
+const PAGE_SIZE: u32 = 100;
+
+/// Envelope of every Tautulli API response
+#[derive(Debug, Deserialize)]
+struct TautulliResponse<T> {
+    response: TautulliResponseBody<T>,
+}
+
+/// Body of a Tautulli API response
+#[derive(Debug, Deserialize)]
+struct TautulliResponseBody<T> {
+    /// "success" or "error"
+    result: String,
+    /// Error message when `result` is "error"
+    message: Option<String>,
+    /// Payload of the response
+    data: Option<T>,
+}
+
+/// Library as returned by Tautulli's `get_libraries` command
+#[derive(Debug, Deserialize)]
+struct TautulliLibrary {
+    #[serde(deserialize_with = "deserialize_lenient_string")]
+    section_id: String,
+    section_name: String,
+    section_type: String,
+}
+
+/// Page of Tautulli's `get_history` command
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TautulliHistoryPage {
+    /// Number of rows matching the request (across all pages)
+    records_filtered: u32,
+    /// Rows of this page
+    data: Vec<TautulliHistoryRow>,
+}
+
+/// Row of Tautulli's `get_history` command
+#[derive(Debug, Deserialize)]
+struct TautulliHistoryRow {
+    /// Unix timestamp of the viewing
+    date: Option<i64>,
+    /// Title of the item
+    #[serde(default)]
+    full_title: String,
+    /// Name of the device the item was played on (e.g. "Living Room TV")
+    #[serde(default)]
+    player: String,
+    /// Rating key of the item on the Plex server
+    #[serde(default, deserialize_with = "deserialize_lenient_string")]
+    rating_key: String,
+    /// Library section of the item
+    #[serde(default, deserialize_with = "deserialize_lenient_string")]
+    section_id: String,
+    /// 1 for complete viewings, 0.5 for partial ones, 0 otherwise
+    #[serde(default)]
+    watched_status: f32,
+}
+
+/// Metadata as returned by Tautulli's `get_metadata` command
+///
+/// Tautulli returns most numbers as strings (with "" for missing values), so
+/// they are parsed leniently and converted to `PlexMediaItemMetadata`.
+#[derive(Debug, Deserialize)]
+struct TautulliMetadata {
+    #[serde(default)]
+    media_type: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    original_title: String,
+    #[serde(default, deserialize_with = "deserialize_lenient_number")]
+    year: Option<f64>,
+    #[serde(default)]
+    originally_available_at: String,
+    #[serde(default)]
+    studio: String,
+    #[serde(default, deserialize_with = "deserialize_lenient_number")]
+    duration: Option<f64>,
+    #[serde(default, deserialize_with = "deserialize_lenient_number")]
+    user_rating: Option<f64>,
+    #[serde(default, deserialize_with = "deserialize_optional_viewed_at")]
+    added_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    guids: Vec<String>,
+    #[serde(default)]
+    directors: Vec<String>,
+    #[serde(default)]
+    genres: Vec<String>,
+    #[serde(default)]
+    labels: Vec<String>,
+    #[serde(default)]
+    collections: Vec<String>,
+}
+
+/// Client for the Tautulli API
+///
+/// Tautulli usually retains much more watch history than the Plex Media
+/// Server itself. This client maps Tautulli's responses to the same
+/// structures as `PlexClient`, so they can be fed to the same export pipeline.
+pub struct TautulliClient {
+    /// Base URL of Tautulli (e.g., "http://192.168.1.100:8181")
+    base_url: String,
+    /// Tautulli API key
+    api_key: String,
+    /// Only return the history of this Plex user
+    user_id: Option<String>,
+    /// HTTP client for making requests
+    client: Client,
+}
+
+impl TautulliClient {
+    /// Creates a new TautulliClient with the given URL and API key
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use plex_client::tautulli::TautulliClient;
+    ///
+    /// let client = TautulliClient::new(
+    ///     "http://192.168.1.100:8181".to_string(),
+    ///     "your-api-key".to_string(),
+    /// );
+    /// for item in client.watch_history_iter("1") {
+    ///     println!("Watched: {}", item?.title);
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn new(base_url: String, api_key: String) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+            user_id: None,
+            client: Client::new(),
+        }
+    }
+
+    /// Restricts the history to a single Plex user (by Tautulli user ID)
+    pub fn with_user_id(mut self, user_id: impl Into<String>) -> Self {
+        self.user_id = Some(user_id.into());
+        self
+    }
+
+    /// Gets the base URL of Tautulli
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Gets the libraries known to Tautulli, in the same shape as `PlexClient::get_library_sections`
+    pub fn get_library_sections(&self) -> Result<PlexLibrarySection> {
+        let libraries: Vec<TautulliLibrary> = self
+            .command("get_libraries", &[])
+            .context("Failed to get Tautulli libraries")?;
+
+        Ok(PlexLibrarySection {
+            directory: libraries
+                .into_iter()
+                .map(|library| PlexLibrarySectionsDirectory {
+                    key: library.section_id,
+                    section_type: library.section_type,
+                    title: library.section_name,
+                    location: Vec::new(),
+                })
+                .collect(),
+        })
+    }
+
+    /// Gets the metadata of an item, in the same shape as `PlexClient::get_media_item_metadata`
+    pub fn get_media_item_metadata(&self, rating_key: &str) -> Result<PlexMediaItemMetadata> {
+        let metadata: TautulliMetadata = self
+            .command("get_metadata", &[("rating_key", rating_key)])
+            .with_context(|| format!("Failed to get Tautulli metadata for {}", rating_key))?;
+
+        let tags = |values: Vec<String>| {
+            values
+                .into_iter()
+                .map(|tag| PlexMediaItemTag { tag })
+                .collect()
+        };
+        let non_empty = |value: String| (!value.is_empty()).then_some(value);
+
+        Ok(PlexMediaItemMetadata {
+            item_type: metadata.media_type,
+            rating_key: Some(rating_key.to_string()),
+            subtype: None,
+            extra_type: None,
+            title: metadata.title,
+            original_title: non_empty(metadata.original_title),
+            edition_title: None,
+            year: metadata.year.map(|year| year as u32),
+            originally_available_at: non_empty(metadata.originally_available_at),
+            studio: non_empty(metadata.studio),
+            duration: metadata.duration.map(|duration| duration as u64),
+            user_rating: metadata.user_rating.map(|rating| rating as f32),
+            added_at: metadata.added_at,
+            plex_guid: None,
+            guid: metadata
+                .guids
+                .into_iter()
+                .map(|id| PlexMediaItemGuidItem { id })
+                .collect(),
+            director: tags(metadata.directors),
+            genre: tags(metadata.genres),
+            collection: tags(metadata.collections),
+            label: tags(metadata.labels),
+        })
+    }
+
+    /// Returns an iterator over the complete viewings of a library section, newest first
+    pub fn watch_history_iter(&self, library_section_id: &str) -> TautulliHistoryIterator<'_> {
+        TautulliHistoryIterator {
+            client: self,
+            library_section_id: library_section_id.to_string(),
+            current_items: Vec::new(),
+            offset: 0,
+            is_last_page: false,
+        }
+    }
+
+    /// Fetches a page of Tautulli's `get_history` command
+    fn get_history_page(
+        &self,
+        offset: u32,
+        library_section_id: &str,
+    ) -> Result<TautulliHistoryPage> {
+        let start = offset.to_string();
+        let length = PAGE_SIZE.to_string();
+        let mut params = vec![
+            ("section_id", library_section_id),
+            ("start", start.as_str()),
+            ("length", length.as_str()),
+            ("order_column", "date"),
+            ("order_dir", "desc"),
+        ];
+        if let Some(user_id) = &self.user_id {
+            params.push(("user_id", user_id));
+        }
+
+        self.command("get_history", &params)
+            .context("Failed to fetch Tautulli history page")
+    }
+
+    /// Runs a Tautulli API command and returns its data
+    fn command<T>(&self, cmd: &str, params: &[(&str, &str)]) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let url = format!("{}/api/v2", self.base_url);
+
+        let request = self
+            .client
+            .get(&url)
+            .query(&[("apikey", self.api_key.as_str()), ("cmd", cmd)])
+            .query(params);
+        let response = send_with_retry_after(request)
+            // The URL carries the API key, so it is kept out of error messages
+            .map_err(reqwest::Error::without_url)
+            .with_context(|| format!("Failed to send Tautulli command: {}", cmd))?;
+
+        let response = response
+            .error_for_status()
+            .map_err(reqwest::Error::without_url)
+            .with_context(|| format!("Tautulli returned an error for command: {}", cmd))?;
+
+        let body: TautulliResponse<T> = response
+            .json()
+            .map_err(reqwest::Error::without_url)
+            .with_context(|| format!("Failed to parse Tautulli response for command: {}", cmd))?;
+
+        if body.response.result != "success" {
+            anyhow::bail!(
+                "Tautulli command {} failed: {}",
+                cmd,
+                body.response.message.unwrap_or_default()
+            );
+        }
+
+        body.response
+            .data
+            .with_context(|| format!("Tautulli returned no data for command: {}", cmd))
+    }
+}
+
+/// Iterator over Tautulli watch history with automatic pagination
+///
+/// Only complete viewings are yielded; partial plays are skipped.
+pub struct TautulliHistoryIterator<'a> {
+    client: &'a TautulliClient,
+    library_section_id: String,
+    current_items: Vec<PlexWatchHistoryItem>,
+    offset: u32,
+    is_last_page: bool,
+}
+
+impl Iterator for TautulliHistoryIterator<'_> {
+    type Item = Result<PlexWatchHistoryItem>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.current_items.is_empty() {
+            if self.is_last_page {
+                return None;
+            }
+
+            let page = match self
+                .client
+                .get_history_page(self.offset, &self.library_section_id)
+            {
+                Ok(page) => page,
+                Err(e) => return Some(Err(e)),
+            };
+
+            self.offset += page.data.len() as u32;
+            self.is_last_page = page.data.is_empty() || self.offset >= page.records_filtered;
+
+            // Store the page reversed so items can be popped in order
+            self.current_items = page
+                .data
+                .into_iter()
+                .filter(|row| row.watched_status >= 1.0)
+                .map(|row| PlexWatchHistoryItem {
+                    title: row.full_title,
+                    rating_key: (!row.rating_key.is_empty()).then_some(row.rating_key),
+                    library_section_id: row.section_id,
+                    viewed_at: row.date.and_then(|date| DateTime::from_timestamp(date, 0)),
+                    device_id: None,
+                    device: (!row.player.is_empty()).then_some(row.player),
+                    history_key: None,
+                })
+                .rev()
+                .collect();
+        }
+
+        self.current_items.pop().map(Ok)
+    }
+}
+
+/// Deserializes a value Tautulli may return as a string or a number into a string
+fn deserialize_lenient_string<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value: serde_json::Value = Deserialize::deserialize(deserializer)?;
+    Ok(match value {
+        serde_json::Value::String(s) => s,
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    })
+}
+
+/// Deserializes a number Tautulli may return as a string ("" meaning missing)
+fn deserialize_lenient_number<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value: serde_json::Value = Deserialize::deserialize(deserializer)?;
+    Ok(match value {
+        serde_json::Value::Number(n) => n.as_f64(),
+        serde_json::Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    })
+}
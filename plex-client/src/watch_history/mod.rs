@@ -0,0 +1,168 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::deserializers::deserialize_optional_viewed_at;
+
+/// Viewings recorded before this time (2000-01-01, as a Unix timestamp) are taken to be bogus
+pub const EARLIEST_PLAUSIBLE_VIEWING: i64 = 946_684_800;
+
+/// Plays of the same item recorded closer together than this, in seconds, are one sitting
+pub const SAME_SITTING_WINDOW: i64 = 60 * 60;
+
+/// Response from the Plex server's list watch history endpoint
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PlexWatchHistory {
+    pub metadata: Vec<PlexWatchHistoryItem>,
+    /// Number of items in this response
+    #[serde(default)]
+    pub size: u32,
+    /// Total number of items available
+    #[serde(default)]
+    pub total_size: u32,
+}
+
+/// Individual item in the watch history
+///
+/// Serializes with the same field names and Unix timestamp it is deserialized
+/// from, so items can be stored and read back.
+///
+/// # Example
+///
+/// ```rust
+/// use chrono::{TimeZone, Utc};
+/// use plex_client::watch_history::PlexWatchHistoryItem;
+///
+/// let json = r#"{"title": "Heat", "ratingKey": "101", "librarySectionID": "1", "viewedAt": 1705350667}"#;
+/// let item: PlexWatchHistoryItem = serde_json::from_str(json)?;
+/// assert_eq!(item.viewed_at, Some(Utc.with_ymd_and_hms(2024, 1, 15, 20, 31, 7).unwrap()));
+///
+/// let stored: PlexWatchHistoryItem = serde_json::from_str(&serde_json::to_string(&item)?)?;
+/// assert_eq!(stored.viewed_at, item.viewed_at);
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlexWatchHistoryItem {
+    /// The title of the media item
+    pub title: String,
+    pub rating_key: Option<String>,
+    #[serde(rename = "librarySectionID")]
+    pub library_section_id: String,
+    /// When the item was viewed
+    ///
+    /// `None` when the server did not record a usable date for the viewing.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_viewed_at",
+        serialize_with = "chrono::serde::ts_seconds_option::serialize"
+    )]
+    pub viewed_at: Option<DateTime<Utc>>,
+    /// ID of the device the item was played on, as listed by the server's devices
+    #[serde(rename = "deviceID")]
+    pub device_id: Option<u64>,
+    /// Name of the device the item was played on (e.g. "Living Room TV"), when the source knows it
+    #[serde(default)]
+    pub device: Option<String>,
+    /// Key of the history entry (e.g. "/status/sessions/history/1234"), unique to each entry
+    pub history_key: Option<String>,
+}
+
+impl PlexWatchHistoryItem {
+    /// Returns when the item was viewed as the Unix timestamp the server sent, in seconds
+    ///
+    /// Handy for sorting, bucketing viewings by hour or storing them, without
+    /// going through a formatted date.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use plex_client::watch_history::PlexWatchHistoryItem;
+    ///
+    /// let json = r#"{"title": "Heat", "librarySectionID": "1", "viewedAt": "1705350667"}"#;
+    /// let item: PlexWatchHistoryItem = serde_json::from_str(json)?;
+    /// assert_eq!(item.viewed_at_timestamp(), Some(1705350667));
+    /// assert_eq!(item.viewed_date().unwrap().to_string(), "2024-01-15");
+    /// # Ok::<(), serde_json::Error>(())
+    /// ```
+    pub fn viewed_at_timestamp(&self) -> Option<i64> {
+        self.viewed_at.map(|viewed_at| viewed_at.timestamp())
+    }
+
+    /// Returns whether the watch time cannot be right: at or near the Unix epoch, or in the future
+    ///
+    /// Plex records 0 for some viewings, and corrupt rows can carry absurd
+    /// values; these would otherwise be exported as watched in 1970. A
+    /// viewing without any watch time is not invalid, only undated.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use plex_client::watch_history::PlexWatchHistoryItem;
+    ///
+    /// let json = r#"{"title": "Heat", "librarySectionID": "1", "viewedAt": 0}"#;
+    /// let item: PlexWatchHistoryItem = serde_json::from_str(json)?;
+    /// assert!(item.has_invalid_viewed_at());
+    ///
+    /// let json = r#"{"title": "Heat", "librarySectionID": "1", "viewedAt": 1705350667}"#;
+    /// let item: PlexWatchHistoryItem = serde_json::from_str(json)?;
+    /// assert!(!item.has_invalid_viewed_at());
+    /// # Ok::<(), serde_json::Error>(())
+    /// ```
+    pub fn has_invalid_viewed_at(&self) -> bool {
+        // A day of leeway for servers with a fast clock
+        let latest = Utc::now() + chrono::Duration::days(1);
+        self.viewed_at.is_some_and(|viewed_at| {
+            viewed_at.timestamp() < EARLIEST_PLAUSIBLE_VIEWING || viewed_at > latest
+        })
+    }
+
+    /// Returns whether two entries are plays of one sitting: the same item,
+    /// viewed within `SAME_SITTING_WINDOW` of each other
+    ///
+    /// Plex records a play again when a paused or interrupted viewing is
+    /// resumed, under a history key of its own, so one sitting can be listed
+    /// several times a few minutes apart. Entries without a rating key or a
+    /// watch time are never one sitting.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use plex_client::watch_history::PlexWatchHistoryItem;
+    ///
+    /// let entry = |history_key: &str, viewed_at: i64| -> PlexWatchHistoryItem {
+    ///     serde_json::from_value(serde_json::json!({
+    ///         "title": "Heat",
+    ///         "ratingKey": "101",
+    ///         "librarySectionID": "1",
+    ///         "viewedAt": viewed_at,
+    ///         "historyKey": history_key,
+    ///     }))
+    ///     .unwrap()
+    /// };
+    /// let first = entry("/status/sessions/history/1", 1705350667);
+    /// // Resumed ten minutes later
+    /// let resumed = entry("/status/sessions/history/2", 1705351267);
+    /// // Watched again the next day
+    /// let rewatch = entry("/status/sessions/history/3", 1705437067);
+    ///
+    /// assert!(first.same_sitting(&resumed));
+    /// assert!(!first.same_sitting(&rewatch));
+    /// ```
+    pub fn same_sitting(&self, other: &Self) -> bool {
+        let (Some(key), Some(other_key)) = (&self.rating_key, &other.rating_key) else {
+            return false;
+        };
+        match (self.viewed_at_timestamp(), other.viewed_at_timestamp()) {
+            (Some(viewed_at), Some(other_viewed_at)) => {
+                key == other_key && (viewed_at - other_viewed_at).abs() < SAME_SITTING_WINDOW
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the date the item was viewed on, in UTC
+    pub fn viewed_date(&self) -> Option<NaiveDate> {
+        self.viewed_at.map(|viewed_at| viewed_at.date_naive())
+    }
+}
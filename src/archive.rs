@@ -0,0 +1,249 @@
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{NaiveDate, SecondsFormat, Utc};
+use plex_client::library::PlexLibrarySection;
+use plex_client::media_item::PlexMediaItemMetadata;
+use plex_client::source::{HistoryIter, HistorySource};
+use plex_client::watch_history::PlexWatchHistoryItem;
+use rusqlite::{params, Connection};
+
+use crate::stats::Viewing;
+
+/// A SQLite database keeping every viewing fetched from any source
+///
+/// Viewings are upserted as they are fetched, keyed by source, rating key
+/// and watch time, so the archive keeps viewings that the server has since
+/// dropped from its own history. The IMDb and TMDB IDs of an item are
+/// recorded on all its viewings once its metadata is resolved.
+///
+/// # Example
+///
+/// ```rust
+/// use chrono::{TimeZone, Utc};
+/// use plex_client::watch_history::PlexWatchHistoryItem;
+/// use plex_to_letterboxd::archive::HistoryArchive;
+///
+/// let path = std::env::temp_dir().join("history-archive-example.db");
+/// let archive = HistoryArchive::open(&path)?;
+/// let item = PlexWatchHistoryItem {
+///     title: "Heat".to_string(),
+///     rating_key: Some("101".to_string()),
+///     library_section_id: "1".to_string(),
+///     viewed_at: Utc.with_ymd_and_hms(2024, 1, 15, 20, 31, 7).single(),
+///     device_id: None,
+///     device: None,
+///     history_key: None,
+/// };
+/// archive.record_viewing("plex", &item)?;
+/// archive.record_viewing("plex", &item)?;
+/// assert_eq!(archive.len()?, 1);
+/// # std::fs::remove_file(&path)?;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct HistoryArchive {
+    /// Connection to the database
+    connection: Mutex<Connection>,
+}
+
+impl HistoryArchive {
+    /// Opens the archive, creating it if it does not exist yet
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let connection = Connection::open(path)
+            .with_context(|| format!("Failed to open archive: {}", path.display()))?;
+        connection
+            .execute_batch(
+                "CREATE TABLE IF NOT EXISTS viewings (
+                     source TEXT NOT NULL,
+                     rating_key TEXT NOT NULL,
+                     viewed_at TEXT NOT NULL,
+                     viewed_at_time TEXT NOT NULL,
+                     title TEXT NOT NULL,
+                     library_section_id TEXT NOT NULL,
+                     imdb_id TEXT,
+                     tmdb_id TEXT,
+                     year INTEGER,
+                     duration_ms INTEGER,
+                     first_fetched TEXT NOT NULL,
+                     last_fetched TEXT NOT NULL,
+                     PRIMARY KEY (source, rating_key, viewed_at, viewed_at_time)
+                 )",
+            )
+            .with_context(|| format!("Invalid archive: {}", path.display()))?;
+
+        // Archives created before runtimes were recorded lack the column
+        let has_duration: bool = connection.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('viewings') WHERE name = 'duration_ms'",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_duration {
+            connection.execute_batch("ALTER TABLE viewings ADD COLUMN duration_ms INTEGER")?;
+        }
+
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    /// Adds a viewing, or updates its title if it is already archived
+    ///
+    /// Watch times are stored as their UTC date and time of day, and missing
+    /// rating keys and watch times as empty strings.
+    pub fn record_viewing(&self, source: &str, item: &PlexWatchHistoryItem) -> Result<()> {
+        let viewed_at = item.viewed_at;
+        let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+        self.connection()
+            .execute(
+                "INSERT INTO viewings (source, rating_key, viewed_at, viewed_at_time, title,
+                     library_section_id, first_fetched, last_fetched)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?7)
+                 ON CONFLICT (source, rating_key, viewed_at, viewed_at_time) DO UPDATE SET
+                     title = excluded.title,
+                     library_section_id = excluded.library_section_id,
+                     last_fetched = excluded.last_fetched",
+                params![
+                    source,
+                    item.rating_key.as_deref().unwrap_or_default(),
+                    viewed_at
+                        .map(|at| at.format("%Y-%m-%d").to_string())
+                        .unwrap_or_default(),
+                    viewed_at
+                        .map(|at| at.format("%H:%M:%S").to_string())
+                        .unwrap_or_default(),
+                    item.title,
+                    item.library_section_id,
+                    now,
+                ],
+            )
+            .context("Failed to archive viewing")?;
+        Ok(())
+    }
+
+    /// Records the IDs, year and runtime of an item on every archived viewing of it
+    pub fn record_metadata(
+        &self,
+        source: &str,
+        rating_key: &str,
+        metadata: &PlexMediaItemMetadata,
+    ) -> Result<()> {
+        let guid = |prefix: &str| {
+            metadata
+                .guid
+                .iter()
+                .find_map(|guid| guid.id.strip_prefix(prefix))
+        };
+        self.connection()
+            .execute(
+                "UPDATE viewings SET
+                     imdb_id = COALESCE(?3, imdb_id),
+                     tmdb_id = COALESCE(?4, tmdb_id),
+                     year = COALESCE(?5, year),
+                     duration_ms = COALESCE(?6, duration_ms)
+                 WHERE source = ?1 AND rating_key = ?2",
+                params![
+                    source,
+                    rating_key,
+                    guid("imdb://"),
+                    guid("tmdb://"),
+                    metadata.year,
+                    metadata.duration.map(|ms| ms as i64),
+                ],
+            )
+            .context("Failed to archive item IDs")?;
+        Ok(())
+    }
+
+    /// Returns every archived viewing, oldest first
+    pub fn viewings(&self) -> Result<Vec<Viewing>> {
+        let connection = self.connection();
+        let mut statement = connection.prepare(
+            "SELECT title, imdb_id, year, viewed_at, duration_ms FROM viewings
+             ORDER BY viewed_at, viewed_at_time",
+        )?;
+        let viewings = statement
+            .query_map([], |row| {
+                let viewed_at: String = row.get(3)?;
+                let duration_ms: Option<i64> = row.get(4)?;
+                Ok(Viewing {
+                    title: row.get(0)?,
+                    imdb_id: row.get(1)?,
+                    year: row.get(2)?,
+                    watched_date: NaiveDate::parse_from_str(&viewed_at, "%Y-%m-%d").ok(),
+                    runtime: duration_ms.map(|ms| Duration::from_millis(ms.max(0) as u64)),
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()
+            .context("Failed to read archive")?;
+        Ok(viewings)
+    }
+
+    /// Returns the number of archived viewings
+    pub fn len(&self) -> Result<usize> {
+        let count: i64 =
+            self.connection()
+                .query_row("SELECT COUNT(*) FROM viewings", [], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    /// Returns whether no viewing is archived
+    pub fn is_empty(&self) -> Result<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Locks the connection, even if a thread panicked while holding it
+    fn connection(&self) -> MutexGuard<'_, Connection> {
+        self.connection
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// A history source recording its viewings and metadata in a `HistoryArchive`
+pub struct ArchivedSource<'a> {
+    /// Source the history and metadata come from
+    inner: Box<dyn HistorySource + 'a>,
+    /// Archive shared by every source of the run
+    archive: &'a HistoryArchive,
+}
+
+impl<'a> ArchivedSource<'a> {
+    /// Wraps a source with an archive
+    pub fn new(inner: Box<dyn HistorySource + 'a>, archive: &'a HistoryArchive) -> Self {
+        Self { inner, archive }
+    }
+}
+
+impl HistorySource for ArchivedSource<'_> {
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    fn library_sections(&self) -> Result<PlexLibrarySection> {
+        self.inner.library_sections()
+    }
+
+    fn history<'b>(&'b self, library_section_id: &str) -> HistoryIter<'b> {
+        let name = self.inner.name();
+        Box::new(self.inner.history(library_section_id).map(move |item| {
+            let item = item?;
+            self.archive.record_viewing(&name, &item)?;
+            Ok(item)
+        }))
+    }
+
+    fn metadata(&self, rating_key: &str) -> Result<PlexMediaItemMetadata> {
+        let metadata = self.inner.metadata(rating_key)?;
+        self.archive
+            .record_metadata(&self.inner.name(), rating_key, &metadata)?;
+        Ok(metadata)
+    }
+
+    fn has_libraries(&self) -> bool {
+        self.inner.has_libraries()
+    }
+}
@@ -0,0 +1,418 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use plex_client::library::PlexLibrarySection;
+use plex_client::media_item::PlexMediaItemMetadata;
+use plex_client::source::{HistoryIter, HistorySource};
+use serde::{Deserialize, Serialize};
+
+/// A store for cached values, which expire after an optional time to live
+///
+/// The metadata cache and the cache of resolved `plex://` GUIDs go through
+/// this trait, so embedders can keep them in a store of their own (e.g.
+/// Redis) instead of the built-in memory, file and SQLite stores. Values are
+/// strings (the caches store JSON), and a store may be shared by several
+/// threads at once. The raw response cache of the Plex client is a
+/// directory of responses, and does not use this trait.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+/// use plex_to_letterboxd::cache::{Cache, MemoryCache};
+///
+/// let cache = MemoryCache::new();
+/// cache.put("plex/101", "{}", None)?;
+/// cache.put("plex/102", "{}", Some(Duration::ZERO))?;
+///
+/// assert_eq!(cache.get("plex/101")?.as_deref(), Some("{}"));
+/// assert_eq!(cache.get("plex/102")?, None);
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub trait Cache: Send + Sync {
+    /// Returns the value of a key, unless it is missing or expired
+    fn get(&self, key: &str) -> Result<Option<String>>;
+
+    /// Stores the value of a key, replacing an earlier one
+    ///
+    /// The value expires after `ttl`, or never without one.
+    fn put(&self, key: &str, value: &str, ttl: Option<Duration>) -> Result<()>;
+
+    /// Persists values stored since the cache was opened, for stores that buffer them
+    fn flush(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Opens the cache store at a path
+///
+/// Paths ending in `.sqlite`, `.sqlite3` or `.db` are SQLite databases,
+/// which need the `sqlite` feature; any other path is a JSON file.
+pub fn open_cache(path: impl AsRef<Path>) -> Result<Box<dyn Cache>> {
+    let path = path.as_ref();
+    let is_sqlite = matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("sqlite" | "sqlite3" | "db")
+    );
+    if !is_sqlite {
+        return Ok(Box::new(FileCache::open(path)?));
+    }
+
+    #[cfg(feature = "sqlite")]
+    return Ok(Box::new(SqliteCache::open(path)?));
+    #[cfg(not(feature = "sqlite"))]
+    anyhow::bail!(
+        "{} is a SQLite cache, which needs a build with the sqlite feature",
+        path.display()
+    )
+}
+
+/// A cached value and when it expires
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry {
+    /// Cached value
+    value: String,
+    /// Seconds since the Unix epoch the value expires at, if it does
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    expires_at: Option<u64>,
+}
+
+impl Entry {
+    /// Creates an entry expiring after `ttl`
+    fn new(value: &str, ttl: Option<Duration>) -> Self {
+        Self {
+            value: value.to_string(),
+            expires_at: ttl.map(|ttl| now().saturating_add(ttl.as_secs())),
+        }
+    }
+
+    /// Returns whether the entry has expired
+    fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= now())
+    }
+}
+
+/// Cache kept in memory for the duration of a run
+#[derive(Debug, Default)]
+pub struct MemoryCache {
+    /// Entries by key
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+impl MemoryCache {
+    /// Creates an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Cache for MemoryCache {
+    fn get(&self, key: &str) -> Result<Option<String>> {
+        Ok(lock(&self.entries)
+            .get(key)
+            .filter(|entry| !entry.is_expired())
+            .map(|entry| entry.value.clone()))
+    }
+
+    fn put(&self, key: &str, value: &str, ttl: Option<Duration>) -> Result<()> {
+        lock(&self.entries).insert(key.to_string(), Entry::new(value, ttl));
+        Ok(())
+    }
+}
+
+/// Cache persisted as a JSON file between runs
+///
+/// The file is read when the cache is opened and written by `flush` if
+/// values were stored; expired entries are dropped when it is written.
+///
+/// # Example
+///
+/// ```rust
+/// use plex_to_letterboxd::cache::{Cache, FileCache};
+///
+/// let path = std::env::temp_dir().join("file-cache-example.json");
+/// let cache = FileCache::open(&path)?;
+/// cache.put("plex/101", "{}", None)?;
+/// cache.flush()?;
+///
+/// assert_eq!(FileCache::open(&path)?.get("plex/101")?.as_deref(), Some("{}"));
+/// # std::fs::remove_file(&path)?;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct FileCache {
+    /// File the cache is read from and written to
+    path: PathBuf,
+    /// Entries by key, and whether any were stored since the file was read
+    entries: Mutex<(BTreeMap<String, Entry>, bool)>,
+}
+
+/// Layout of the cache file
+#[derive(Serialize, Deserialize)]
+struct CacheFile<E> {
+    /// Version of the layout
+    version: u32,
+    /// Entries by key
+    entries: E,
+}
+
+/// Version of the cache file layout written by `FileCache`
+const CACHE_FILE_VERSION: u32 = 2;
+
+impl FileCache {
+    /// Reads the cache file, or starts an empty cache if it does not exist yet
+    ///
+    /// Metadata cache files written before entries could expire are read too,
+    /// their entries never expiring.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let entries = if path.exists() {
+            let contents = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read cache: {}", path.display()))?;
+            parse_cache_file(&contents)
+                .with_context(|| format!("Invalid cache: {}", path.display()))?
+        } else {
+            BTreeMap::new()
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            entries: Mutex::new((entries, false)),
+        })
+    }
+}
+
+/// Parses a cache file, or a metadata cache file of values by key
+fn parse_cache_file(contents: &str) -> Result<BTreeMap<String, Entry>> {
+    if let Ok(file) = serde_json::from_str::<CacheFile<BTreeMap<String, Entry>>>(contents) {
+        return Ok(file.entries);
+    }
+    let values: BTreeMap<String, serde_json::Value> = serde_json::from_str(contents)?;
+    Ok(values
+        .into_iter()
+        .map(|(key, value)| (key, Entry::new(&value.to_string(), None)))
+        .collect())
+}
+
+impl Cache for FileCache {
+    fn get(&self, key: &str) -> Result<Option<String>> {
+        Ok(lock(&self.entries)
+            .0
+            .get(key)
+            .filter(|entry| !entry.is_expired())
+            .map(|entry| entry.value.clone()))
+    }
+
+    fn put(&self, key: &str, value: &str, ttl: Option<Duration>) -> Result<()> {
+        let (entries, dirty) = &mut *lock(&self.entries);
+        entries.insert(key.to_string(), Entry::new(value, ttl));
+        *dirty = true;
+        Ok(())
+    }
+
+    fn flush(&self) -> Result<()> {
+        let (entries, dirty) = &mut *lock(&self.entries);
+        if !*dirty {
+            return Ok(());
+        }
+        entries.retain(|_, entry| !entry.is_expired());
+        let contents = serde_json::to_string(&CacheFile {
+            version: CACHE_FILE_VERSION,
+            entries: &*entries,
+        })?;
+        std::fs::write(&self.path, contents)
+            .with_context(|| format!("Failed to write cache: {}", self.path.display()))?;
+        *dirty = false;
+        Ok(())
+    }
+}
+
+/// Cache persisted in a SQLite database between runs
+///
+/// Values are written as they are stored, so nothing is lost if a run is
+/// interrupted; expired entries are deleted when the cache is opened.
+#[cfg(feature = "sqlite")]
+#[derive(Debug)]
+pub struct SqliteCache {
+    /// Connection to the database
+    connection: Mutex<rusqlite::Connection>,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteCache {
+    /// Opens the database, creating it if it does not exist yet
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let connection = rusqlite::Connection::open(path)
+            .with_context(|| format!("Failed to open cache: {}", path.display()))?;
+        connection
+            .execute_batch(
+                "CREATE TABLE IF NOT EXISTS cache (
+                     key TEXT PRIMARY KEY,
+                     value TEXT NOT NULL,
+                     expires_at INTEGER
+                 )",
+            )
+            .with_context(|| format!("Invalid cache: {}", path.display()))?;
+        connection.execute("DELETE FROM cache WHERE expires_at <= ?1", [now() as i64])?;
+
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl Cache for SqliteCache {
+    fn get(&self, key: &str) -> Result<Option<String>> {
+        use rusqlite::OptionalExtension;
+
+        Ok(lock(&self.connection)
+            .query_row(
+                "SELECT value FROM cache
+                 WHERE key = ?1 AND (expires_at IS NULL OR expires_at > ?2)",
+                rusqlite::params![key, now() as i64],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    fn put(&self, key: &str, value: &str, ttl: Option<Duration>) -> Result<()> {
+        let entry = Entry::new(value, ttl);
+        lock(&self.connection).execute(
+            "INSERT OR REPLACE INTO cache (key, value, expires_at) VALUES (?1, ?2, ?3)",
+            rusqlite::params![key, entry.value, entry.expires_at.map(|t| t as i64)],
+        )?;
+        Ok(())
+    }
+}
+
+/// Metadata of history items, cached between runs
+///
+/// Entries are keyed by source name and rating key, so the histories of
+/// several servers can share one cache.
+pub struct MetadataCache {
+    /// Store the metadata is kept in, as JSON
+    store: Box<dyn Cache>,
+    /// How long cached metadata is used before it is requested again
+    ttl: Option<Duration>,
+}
+
+impl MetadataCache {
+    /// Caches metadata in a store, for `ttl` or forever without one
+    pub fn new(store: Box<dyn Cache>, ttl: Option<Duration>) -> Self {
+        Self { store, ttl }
+    }
+
+    /// Opens the cache file or database at a path (see `open_cache`)
+    pub fn load(path: impl AsRef<Path>, ttl: Option<Duration>) -> Result<Self> {
+        Ok(Self::new(open_cache(path)?, ttl))
+    }
+
+    /// Returns the cached metadata of an item
+    ///
+    /// Entries that cannot be read (e.g. written by another version) are
+    /// treated as missing, so they are requested again.
+    pub fn get(&self, source: &str, rating_key: &str) -> Result<Option<PlexMediaItemMetadata>> {
+        let value = self.store.get(&format!("{}/{}", source, rating_key))?;
+        Ok(value.and_then(|value| serde_json::from_str(&value).ok()))
+    }
+
+    /// Caches the metadata of an item
+    pub fn insert(
+        &self,
+        source: &str,
+        rating_key: &str,
+        metadata: &PlexMediaItemMetadata,
+    ) -> Result<()> {
+        self.store.put(
+            &format!("{}/{}", source, rating_key),
+            &serde_json::to_string(metadata)?,
+            self.ttl,
+        )
+    }
+
+    /// Persists metadata cached since the cache was opened
+    pub fn save(&self) -> Result<()> {
+        self.store.flush()
+    }
+
+    /// Returns the store the metadata is kept in, for other lookups cached alongside
+    pub fn store(&self) -> &dyn Cache {
+        &*self.store
+    }
+
+    /// Returns how long cached values are used
+    pub fn ttl(&self) -> Option<Duration> {
+        self.ttl
+    }
+}
+
+/// A history source whose metadata lookups go through a `MetadataCache`
+///
+/// Items already in the cache are not requested again, so incremental syncs
+/// only fetch the metadata of newly watched items. Metadata can be resolved
+/// from several threads; no lock is held while uncached metadata is requested.
+pub struct CachedSource<'a> {
+    /// Source the history and uncached metadata come from
+    inner: Box<dyn HistorySource + 'a>,
+    /// Cache shared by every source of the run
+    cache: &'a MetadataCache,
+}
+
+impl<'a> CachedSource<'a> {
+    /// Wraps a source with a cache
+    pub fn new(inner: Box<dyn HistorySource + 'a>, cache: &'a MetadataCache) -> Self {
+        Self { inner, cache }
+    }
+}
+
+impl HistorySource for CachedSource<'_> {
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    fn library_sections(&self) -> Result<PlexLibrarySection> {
+        self.inner.library_sections()
+    }
+
+    fn history<'b>(&'b self, library_section_id: &str) -> HistoryIter<'b> {
+        self.inner.history(library_section_id)
+    }
+
+    fn metadata(&self, rating_key: &str) -> Result<PlexMediaItemMetadata> {
+        let name = self.inner.name();
+        if let Some(metadata) = self.cache.get(&name, rating_key)? {
+            return Ok(metadata);
+        }
+
+        let metadata = self.inner.metadata(rating_key)?;
+        self.cache.insert(&name, rating_key, &metadata)?;
+        Ok(metadata)
+    }
+
+    fn has_libraries(&self) -> bool {
+        self.inner.has_libraries()
+    }
+}
+
+/// Returns the current time, in seconds since the Unix epoch
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Locks a mutex, even if a thread panicked while holding it
+///
+/// Entries are stored whole, so a poisoned cache is still consistent.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
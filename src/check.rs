@@ -0,0 +1,117 @@
+use chrono::{Datelike, NaiveDate};
+use plex_client::media_item::PlexMediaItemMetadata;
+
+use crate::row::ExportRow;
+
+/// Returns the problems that would make Letterboxd reject or mis-import a row
+///
+/// An empty WatchedDate is accepted, since undated viewings and library list
+/// rows are exported without one on purpose. A date in 1970 is reported
+/// because it is what a missing Unix timestamp turns into.
+///
+/// # Example
+///
+/// ```rust
+/// use chrono::NaiveDate;
+/// use plex_to_letterboxd::check::row_issues;
+/// use plex_to_letterboxd::row::ExportRow;
+///
+/// let today = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+/// let row = ExportRow {
+///     title: "Heat".to_string(),
+///     imdb_id: "tt0113277".to_string(),
+///     watched_date: "2024-01-15".to_string(),
+///     rating: Some(9.0),
+///     ..Default::default()
+/// };
+/// assert!(row_issues(&row, today).is_empty());
+///
+/// let row = ExportRow {
+///     imdb_id: "plex://movie/5d776830".to_string(),
+///     watched_date: "1970-01-01".to_string(),
+///     rating: Some(12.0),
+///     ..row
+/// };
+/// assert_eq!(row_issues(&row, today).len(), 3);
+/// ```
+pub fn row_issues(row: &ExportRow, today: NaiveDate) -> Vec<String> {
+    let mut issues = Vec::new();
+
+    if row.imdb_id.is_empty() {
+        // Letterboxd matches rows without an IMDb ID by their tmdbID
+        if row.tmdb_id.is_none() {
+            issues.push("missing imdbID".to_string());
+        }
+    } else if !is_imdb_id(&row.imdb_id) {
+        issues.push(format!("'{}' is not an IMDb ID", row.imdb_id));
+    }
+
+    if !row.watched_date.is_empty() {
+        match NaiveDate::parse_from_str(&row.watched_date, "%Y-%m-%d") {
+            Ok(date) if date > today => {
+                issues.push(format!("WatchedDate {} is in the future", row.watched_date))
+            }
+            Ok(date) if date.year() <= 1970 => issues.push(format!(
+                "WatchedDate {} is likely a missing timestamp",
+                row.watched_date
+            )),
+            Ok(_) => {}
+            Err(_) => issues.push(format!("'{}' is not a valid date", row.watched_date)),
+        }
+    }
+
+    if let Some(rating) = row.rating {
+        if !(0.0..=10.0).contains(&rating) {
+            issues.push(format!("rating {} is outside 0-10", rating));
+        }
+    }
+
+    issues
+}
+
+/// Returns a warning when an item's year disagrees with its release date
+///
+/// The release date comes from the TMDb/IMDb lookup of the metadata agent,
+/// while the year may come from the file name or a manual edit. When they
+/// differ by more than a year, Plex has likely matched the item to the wrong
+/// film; a year apart is common for films premiering at a festival the year
+/// before their release.
+///
+/// # Example
+///
+/// ```rust
+/// use plex_client::media_item::PlexMediaItemMetadata;
+/// use plex_to_letterboxd::check::year_mismatch;
+///
+/// let metadata: PlexMediaItemMetadata = serde_json::from_str(
+///     r#"{"title": "Solaris", "year": 2002, "originallyAvailableAt": "1972-03-20"}"#,
+/// )
+/// .unwrap();
+/// assert_eq!(
+///     year_mismatch(&metadata).as_deref(),
+///     Some("year 2002 differs from release date 1972-03-20 (possible mismatch)")
+/// );
+///
+/// let metadata: PlexMediaItemMetadata = serde_json::from_str(
+///     r#"{"title": "Parasite", "year": 2019, "originallyAvailableAt": "2020-01-10"}"#,
+/// )
+/// .unwrap();
+/// assert_eq!(year_mismatch(&metadata), None);
+/// ```
+pub fn year_mismatch(metadata: &PlexMediaItemMetadata) -> Option<String> {
+    let year = metadata.year?;
+    let released = metadata.originally_available_at.as_deref()?;
+    let released_year = NaiveDate::parse_from_str(released, "%Y-%m-%d").ok()?.year();
+    ((i64::from(year) - i64::from(released_year)).abs() > 1).then(|| {
+        format!(
+            "year {} differs from release date {} (possible mismatch)",
+            year, released
+        )
+    })
+}
+
+/// Returns whether an ID looks like an IMDb title ID ("tt" followed by digits)
+pub(crate) fn is_imdb_id(id: &str) -> bool {
+    id.strip_prefix("tt")
+        .is_some_and(|digits| !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()))
+}
@@ -0,0 +1,49 @@
+use std::io::{self, IsTerminal};
+
+use anyhow::Result;
+use plex_to_letterboxd::report::RunReport;
+use plex_to_letterboxd::style::{paint, SUCCESS};
+use plex_to_letterboxd::tui;
+
+use super::export::{collect_rows, create_exporter, write_rows, ExportArgs};
+use super::GlobalArgs;
+
+/// Runs the `browse` subcommand
+///
+/// Fetches the rows `export` would write, then shows them in a terminal UI.
+/// Only the rows selected there are exported, with the same flags as `export`.
+/// With `--yes`, the terminal UI is skipped and every row is exported.
+pub fn run(global: &GlobalArgs, args: &ExportArgs) -> Result<()> {
+    if !global.yes && (!io::stdin().is_terminal() || !io::stdout().is_terminal()) {
+        anyhow::bail!("browse requires an interactive terminal");
+    }
+
+    let config = global.load_config()?;
+    let exporter = create_exporter(&config, args)?;
+    let mut report = RunReport::default();
+    let rows = collect_rows(global, &config, args, &mut report)?;
+    if rows.is_empty() {
+        println!("Nothing to browse: no rows were found");
+        return Ok(());
+    }
+
+    let selection = if global.yes {
+        println!("Skipping the browser because of --yes; exporting every row");
+        rows
+    } else {
+        let Some(selection) = tui::browse(rows)? else {
+            println!("Nothing was exported");
+            return Ok(());
+        };
+        selection
+    };
+    write_rows(global, args, exporter, selection, &mut report)?;
+    anstream::println!(
+        "{}",
+        paint(
+            SUCCESS,
+            format!("Exported {} selected rows", report.rows_exported)
+        )
+    );
+    Ok(())
+}
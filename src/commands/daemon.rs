@@ -0,0 +1,331 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use chrono::{Local, SecondsFormat, Utc};
+use clap::Args;
+use serde::Serialize;
+
+use plex_to_letterboxd::logging;
+use plex_to_letterboxd::schedule::Schedule;
+use plex_to_letterboxd::style::{paint, ERROR, SUCCESS};
+
+use super::export::{report_outcome, ExportArgs};
+use super::sync::sync;
+use super::GlobalArgs;
+
+/// Flags for the `daemon` subcommand
+#[derive(Args, Debug)]
+pub struct DaemonArgs {
+    #[command(flatten)]
+    pub export: ExportArgs,
+
+    /// Seconds to wait between syncs
+    #[arg(long, default_value_t = 3600)]
+    pub interval: u64,
+
+    /// Cron expression (minute hour day-of-month month day-of-week, local time)
+    /// for when to sync, e.g. "0 4 * * *"; replaces --interval
+    #[arg(long, value_name = "CRON", conflicts_with = "interval")]
+    pub schedule: Option<Schedule>,
+
+    /// Address to serve the health and metrics endpoints on (e.g., "0.0.0.0:8080")
+    #[arg(long, value_name = "ADDR")]
+    pub listen: Option<SocketAddr>,
+}
+
+/// Outcome of the most recent sync, reported by `GET /healthz`
+#[derive(Debug, Clone, Serialize)]
+struct DaemonStatus {
+    /// "starting" before the first sync, then "ok" or "error"
+    status: &'static str,
+    /// When the last sync finished (RFC 3339)
+    last_sync: Option<String>,
+    /// Rows added by the last successful sync
+    rows_added: usize,
+    /// Error of the last sync, if it failed
+    last_error: Option<String>,
+}
+
+/// Counters exposed by `GET /metrics` in the Prometheus text format
+#[derive(Debug, Clone, Default)]
+struct DaemonMetrics {
+    /// Syncs run since the daemon started
+    syncs: u64,
+    /// Syncs that failed (API, network or file errors)
+    sync_errors: u64,
+    /// Rows exported across all syncs
+    rows_exported: u64,
+    /// Items skipped across all syncs
+    rows_skipped: u64,
+    /// Duration of the last sync, in seconds
+    last_sync_duration_seconds: f64,
+    /// Total duration of all syncs, in seconds
+    sync_duration_seconds_sum: f64,
+    /// Unix time at which the last successful sync finished
+    last_success_timestamp: i64,
+    /// Requests to Plex servers that failed, counted by the clients as they are sent
+    api_request_errors: Arc<AtomicU64>,
+}
+
+impl DaemonMetrics {
+    /// Renders the metrics in the Prometheus text exposition format
+    fn render(&self) -> String {
+        let metrics: [(&str, &str, &str, String); 8] = [
+            (
+                "syncs_total",
+                "counter",
+                "Syncs run since the daemon started",
+                self.syncs.to_string(),
+            ),
+            (
+                "sync_errors_total",
+                "counter",
+                "Syncs that failed",
+                self.sync_errors.to_string(),
+            ),
+            (
+                "api_request_errors_total",
+                "counter",
+                "Requests to Plex servers that failed or returned an error status",
+                self.api_request_errors.load(Ordering::Relaxed).to_string(),
+            ),
+            (
+                "rows_exported_total",
+                "counter",
+                "Rows exported across all syncs",
+                self.rows_exported.to_string(),
+            ),
+            (
+                "rows_skipped_total",
+                "counter",
+                "Items skipped across all syncs",
+                self.rows_skipped.to_string(),
+            ),
+            (
+                "last_sync_duration_seconds",
+                "gauge",
+                "Duration of the last sync",
+                self.last_sync_duration_seconds.to_string(),
+            ),
+            (
+                "sync_duration_seconds_sum",
+                "counter",
+                "Total duration of all syncs",
+                self.sync_duration_seconds_sum.to_string(),
+            ),
+            (
+                "last_success_timestamp_seconds",
+                "gauge",
+                "Unix time of the last successful sync",
+                self.last_success_timestamp.to_string(),
+            ),
+        ];
+
+        metrics
+            .iter()
+            .map(|(name, kind, help, value)| {
+                format!(
+                    "# HELP plex_to_letterboxd_{name} {help}\n\
+                     # TYPE plex_to_letterboxd_{name} {kind}\n\
+                     plex_to_letterboxd_{name} {value}\n"
+                )
+            })
+            .collect()
+    }
+}
+
+/// State shared between the sync loop and the HTTP endpoints
+#[derive(Debug, Default)]
+struct DaemonState {
+    /// Outcome of the most recent sync
+    status: DaemonStatus,
+    /// Counters accumulated across syncs
+    metrics: DaemonMetrics,
+}
+
+impl Default for DaemonStatus {
+    fn default() -> Self {
+        Self {
+            status: "starting",
+            last_sync: None,
+            rows_added: 0,
+            last_error: None,
+        }
+    }
+}
+
+/// How long a health or metrics client may take to send its request or read the answer
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Answers a single HTTP request on the health or metrics endpoint
+///
+/// Connections are answered one at a time, so a client that stops sending
+/// or reading is dropped after `CONNECTION_TIMEOUT` rather than blocking
+/// the others.
+fn handle_connection(stream: TcpStream, state: &Mutex<DaemonState>) -> Result<()> {
+    stream.set_read_timeout(Some(CONNECTION_TIMEOUT))?;
+    stream.set_write_timeout(Some(CONNECTION_TIMEOUT))?;
+
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    // Skip the request headers
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let state = state.lock().expect("state lock poisoned");
+    let (code, content_type, body) =
+        match request_line.split_whitespace().take(2).collect::<Vec<_>>()[..] {
+            ["GET", "/healthz"] => {
+                let code = if state.status.status == "error" {
+                    "503 Service Unavailable"
+                } else {
+                    "200 OK"
+                };
+                (
+                    code,
+                    "application/json",
+                    serde_json::to_string(&state.status)?,
+                )
+            }
+            ["GET", "/metrics"] => (
+                "200 OK",
+                "text/plain; version=0.0.4",
+                state.metrics.render(),
+            ),
+            _ => (
+                "404 Not Found",
+                "application/json",
+                r#"{"error":"not found"}"#.to_string(),
+            ),
+        };
+    drop(state);
+
+    let mut stream = &stream;
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        code,
+        content_type,
+        body.len(),
+        body
+    )?;
+    Ok(())
+}
+
+/// Serves the health and metrics endpoints in a background thread
+fn serve(addr: SocketAddr, state: Arc<Mutex<DaemonState>>) -> Result<()> {
+    let listener =
+        TcpListener::bind(addr).with_context(|| format!("Failed to listen on {}", addr))?;
+    println!("Serving health checks on http://{}/healthz", addr);
+    println!("Serving metrics on http://{}/metrics", addr);
+
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if let Err(e) = handle_connection(stream, &state) {
+                eprintln!("HTTP request failed: {:#}", e);
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Runs the `daemon` subcommand
+///
+/// Syncs the output file every `--interval` seconds, or at the times of
+/// `--schedule` (the first sync then waits for the schedule too), until
+/// stopped. A failed sync is reported and retried at the next interval
+/// instead of stopping the daemon; this includes syncs cut short by the
+/// circuit breaker, which fails requests instead of pausing them here.
+pub fn run(global: &GlobalArgs, args: &DaemonArgs) -> Result<()> {
+    if args.export.review {
+        anyhow::bail!("--review cannot be used with daemon, which runs unattended");
+    }
+    if args.export.print_count {
+        anyhow::bail!("--print-count cannot be used with daemon, which never finishes");
+    }
+
+    global.fail_fast_on_open_circuit();
+
+    let state = Arc::new(Mutex::new(DaemonState::default()));
+    state
+        .lock()
+        .expect("state lock poisoned")
+        .metrics
+        .api_request_errors = global.count_request_errors();
+    if let Some(addr) = args.listen {
+        serve(addr, Arc::clone(&state))?;
+    }
+
+    let redactor = global.redactor();
+    if args.schedule.is_some() {
+        thread::sleep(wait_time(args)?);
+    }
+    loop {
+        let started = Instant::now();
+        let started_at = Utc::now();
+        let outcome = logging::run_span().in_scope(|| sync(global, &args.export));
+        let duration = started.elapsed().as_secs_f64();
+        report_outcome(global, &args.export, "daemon", started_at, &outcome);
+        let finished_at = Utc::now();
+
+        let mut state = state.lock().expect("state lock poisoned");
+        let DaemonState { status, metrics } = &mut *state;
+        status.last_sync = Some(finished_at.to_rfc3339_opts(SecondsFormat::Secs, true));
+        metrics.syncs += 1;
+        metrics.last_sync_duration_seconds = duration;
+        metrics.sync_duration_seconds_sum += duration;
+        match outcome {
+            Ok(report) => {
+                anstream::println!(
+                    "\n{} to: {}",
+                    paint(
+                        SUCCESS,
+                        format!("✓ Added {} new rows", report.rows_exported)
+                    ),
+                    args.export
+                        .output_file()
+                        .unwrap_or_else(|_| args.export.output_csv.clone())
+                );
+                status.status = "ok";
+                status.rows_added = report.rows_exported;
+                status.last_error = None;
+                metrics.rows_exported += report.rows_exported as u64;
+                metrics.rows_skipped += report.rows_skipped() as u64;
+                metrics.last_success_timestamp = finished_at.timestamp();
+            }
+            Err(e) => {
+                let error = redactor.redact(&format!("{:#}", e));
+                anstream::eprintln!("\n{} {}", paint(ERROR, "✗ Sync failed:"), error);
+                status.status = "error";
+                status.last_error = Some(error);
+                metrics.sync_errors += 1;
+            }
+        }
+        drop(state);
+
+        thread::sleep(wait_time(args)?);
+    }
+}
+
+/// Returns how long to wait before the next sync
+fn wait_time(args: &DaemonArgs) -> Result<Duration> {
+    let Some(schedule) = &args.schedule else {
+        return Ok(Duration::from_secs(args.interval));
+    };
+
+    let now = Local::now();
+    let next = schedule
+        .next_after(&now)
+        .context("The schedule never runs")?;
+    println!("Next sync at {}", next.format("%Y-%m-%d %H:%M"));
+    Ok((next - now).to_std().unwrap_or_default())
+}
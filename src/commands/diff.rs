@@ -0,0 +1,78 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+use plex_to_letterboxd::diff::{diff_export_files, DiffRow};
+
+/// Flags for the `diff` subcommand
+#[derive(Args, Debug)]
+pub struct DiffArgs {
+    /// Earlier export (e.g., the ".bak" copy kept when an export is replaced)
+    pub old: PathBuf,
+
+    /// Later export to compare with it
+    pub new: PathBuf,
+}
+
+/// Formats a row as "WatchedDate Title (imdbID)"
+fn describe(row: &DiffRow) -> String {
+    let date = if row.watched_date.is_empty() {
+        "(undated)"
+    } else {
+        &row.watched_date
+    };
+    if row.title.is_empty() {
+        format!("{} {}", date, row.imdb_id)
+    } else {
+        format!("{} {} ({})", date, row.title, row.imdb_id)
+    }
+}
+
+/// Runs the `diff` subcommand
+///
+/// Prints the rows added, removed and changed between two exports, matched
+/// by imdbID and WatchedDate, to check what an incremental sync appended or
+/// what changed between runs.
+pub fn run(args: &DiffArgs) -> Result<()> {
+    let diff = diff_export_files(&args.old, &args.new)?;
+
+    if !diff.columns_added.is_empty() {
+        println!(
+            "Columns only in the new export: {}",
+            diff.columns_added.join(", ")
+        );
+    }
+    if !diff.columns_removed.is_empty() {
+        println!(
+            "Columns only in the old export: {}",
+            diff.columns_removed.join(", ")
+        );
+    }
+    for row in &diff.added {
+        println!("+ {}", describe(row));
+    }
+    for row in &diff.removed {
+        println!("- {}", describe(row));
+    }
+    for (row, changes) in &diff.changed {
+        println!("~ {}", describe(row));
+        for change in changes {
+            println!(
+                "    {}: \"{}\" -> \"{}\"",
+                change.column, change.old, change.new
+            );
+        }
+    }
+
+    if diff.is_empty() {
+        println!("No differences between the exports");
+    } else {
+        println!(
+            "{} added, {} removed, {} changed",
+            diff.added.len(),
+            diff.removed.len(),
+            diff.changed.len()
+        );
+    }
+    Ok(())
+}
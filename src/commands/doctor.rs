@@ -0,0 +1,81 @@
+use anyhow::Result;
+use plex_to_letterboxd::redact::Redactor;
+use plex_to_letterboxd::style::{paint, ERROR, SUCCESS};
+
+use super::GlobalArgs;
+
+/// Prints the outcome of a single diagnostic check, returning whether it passed
+fn report<T>(
+    redactor: &Redactor,
+    name: &str,
+    outcome: &Result<T>,
+    detail: impl Fn(&T) -> String,
+) -> bool {
+    match outcome {
+        Ok(value) => anstream::println!(
+            "{} {}: {}",
+            paint(SUCCESS, "✓"),
+            name,
+            redactor.redact(&detail(value))
+        ),
+        Err(e) => anstream::println!(
+            "{} {}: {}",
+            paint(ERROR, "✗"),
+            name,
+            redactor.redact(&format!("{:#}", e))
+        ),
+    }
+    outcome.is_ok()
+}
+
+/// Runs the `doctor` subcommand
+///
+/// Unlike `validate`, which stops at the first problem, `doctor` runs every
+/// check it can and prints a report explaining what is wrong.
+pub fn run(global: &GlobalArgs) -> Result<()> {
+    let redactor = global.redactor();
+    let mut healthy = report(&redactor, "Server URL", &global.base_url(), |url| {
+        url.clone()
+    });
+    healthy &= report(&redactor, "Token", &global.token(), |_| {
+        "provided".to_string()
+    });
+
+    if healthy {
+        let client = global.configure_client(global.base_url()?, global.token()?)?;
+
+        let identity = client.check_server_version();
+        healthy &= report(&redactor, "Server reachable", &identity, |identity| {
+            format!("version {}", identity.version)
+        });
+
+        let sections = client.get_library_sections();
+        healthy &= report(&redactor, "Token accepted", &sections, |sections| {
+            let movie_libraries = sections
+                .directory
+                .iter()
+                .filter(|dir| dir.section_type == "movie")
+                .count();
+            format!(
+                "{} libraries ({} movie libraries)",
+                sections.directory.len(),
+                movie_libraries
+            )
+        });
+
+        if global.account_id.is_empty() {
+            let account_id = client.resolve_account_id();
+            healthy &= report(&redactor, "Account", &account_id, |id| {
+                format!("watch history of account {}", id)
+            });
+        }
+    }
+
+    if !healthy {
+        anyhow::bail!("Doctor found problems with the configuration");
+    }
+
+    println!("\nEverything looks good.");
+
+    Ok(())
+}
@@ -0,0 +1,218 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use clap::Args;
+use plex_client::client::PlexClient;
+use plex_client::library::PlexLibrarySection;
+use plex_client::media_item::PlexMediaItemMetadata;
+use plex_client::source::{HistoryIter, HistorySource};
+use plex_to_letterboxd::console::{self, status};
+use plex_to_letterboxd::report::RunReport;
+use plex_to_letterboxd::row::ExportRow;
+
+use super::export::{
+    add_reviews, create_exporter, for_each_source_row, report_outcome, write_rows, ExportArgs,
+    Mode, Source,
+};
+use super::{select_library, GlobalArgs};
+
+/// Flags for the `export-items` subcommand
+#[derive(Args, Debug)]
+pub struct ExportItemsArgs {
+    /// Rating key of a film to export (e.g., "12345"); can be repeated
+    #[arg(
+        long = "rating-key",
+        value_name = "KEY",
+        required_unless_present_any = ["titles", "from_file"]
+    )]
+    pub rating_keys: Vec<String>,
+
+    /// Title of a film to export, looked up in the library (e.g., "Heat"); can be repeated
+    #[arg(long = "title", value_name = "TITLE")]
+    pub titles: Vec<String>,
+
+    /// File of rating keys to export, one per line (blank lines and lines
+    /// starting with "#" are skipped), e.g. produced by other tooling
+    #[arg(long, value_name = "FILE")]
+    pub from_file: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub export: ExportArgs,
+}
+
+/// The Plex server, with its history restricted to the viewings of some items
+struct ItemsSource<'a> {
+    /// Server the viewings and metadata are requested from
+    client: &'a PlexClient,
+    /// Rating keys of the items whose viewings are exported
+    rating_keys: Vec<String>,
+}
+
+impl HistorySource for ItemsSource<'_> {
+    fn name(&self) -> String {
+        self.client.name()
+    }
+
+    fn library_sections(&self) -> Result<PlexLibrarySection> {
+        self.client.library_sections()
+    }
+
+    fn history<'a>(&'a self, _library_section_id: &str) -> HistoryIter<'a> {
+        Box::new(
+            self.rating_keys
+                .iter()
+                .flat_map(|rating_key| self.client.item_watch_history_iter(rating_key)),
+        )
+    }
+
+    fn metadata(&self, rating_key: &str) -> Result<PlexMediaItemMetadata> {
+        self.client.metadata(rating_key)
+    }
+}
+
+/// Runs the `export-items` subcommand
+pub fn run(global: &GlobalArgs, args: &ExportItemsArgs) -> Result<()> {
+    if args.export.print_count {
+        console::status_to_stderr();
+    }
+    let started_at = Utc::now();
+    let outcome = export_items(global, args);
+    report_outcome(global, &args.export, "export-items", started_at, &outcome);
+    let report = outcome?;
+
+    status(report.summary());
+    args.export.print_count(&report);
+
+    Ok(())
+}
+
+/// Exports the viewings of the films selected by rating key or title
+///
+/// Only the history of those films is requested, so this is much faster
+/// than a full export on a large history. Rows go through the same filters,
+/// map command and checks as with `export`.
+pub fn export_items(global: &GlobalArgs, args: &ExportItemsArgs) -> Result<RunReport> {
+    let export_args = &args.export;
+    if export_args.mode != Mode::Diary {
+        anyhow::bail!("export-items only supports the diary mode");
+    }
+    if export_args.source.source != Source::Plex {
+        anyhow::bail!("export-items only supports the Plex source");
+    }
+    if export_args.source.include_streaming_history {
+        anyhow::bail!("export-items does not support --include-streaming-history");
+    }
+
+    let mut rating_keys = args.rating_keys.clone();
+    if let Some(path) = &args.from_file {
+        rating_keys.extend(read_rating_keys(path)?);
+    }
+
+    let config = global.load_config()?;
+    let exporter = create_exporter(&config, export_args)?;
+    let client = global.client()?;
+    if !args.titles.is_empty() {
+        let library = select_library(&client, &export_args.library)?;
+        for title in &args.titles {
+            rating_keys.extend(find_title(&client, &library.key, title)?);
+        }
+    }
+    // A film can be given both by rating key and by title
+    let mut seen = HashSet::new();
+    rating_keys.retain(|rating_key| seen.insert(rating_key.clone()));
+
+    let source = ItemsSource {
+        client: &client,
+        rating_keys,
+    };
+    let mut report = RunReport::default();
+    let mut rows = Vec::new();
+    let sources: [Box<dyn HistorySource + '_>; 1] = [Box::new(source)];
+    for_each_source_row(&sources, export_args, &mut report, |row, _| {
+        rows.push(row);
+        Ok(())
+    })?;
+
+    rows.sort_by(ExportRow::output_order);
+    add_reviews(export_args, &mut rows)?;
+    write_rows(global, export_args, exporter, rows, &mut report)?;
+    Ok(report)
+}
+
+/// Reads a file of rating keys, one per line
+fn read_rating_keys(path: &Path) -> Result<Vec<String>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read rating keys: {}", path.display()))?;
+
+    let mut rating_keys = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let rating_key = line.trim();
+        if rating_key.is_empty() || rating_key.starts_with('#') {
+            continue;
+        }
+        if !rating_key.chars().all(|c| c.is_ascii_digit()) {
+            anyhow::bail!(
+                "{} line {}: '{}' is not a rating key",
+                path.display(),
+                index + 1,
+                rating_key
+            );
+        }
+        rating_keys.push(rating_key.to_string());
+    }
+
+    if rating_keys.is_empty() {
+        anyhow::bail!("No rating keys found in {}", path.display());
+    }
+    Ok(rating_keys)
+}
+
+/// Returns the rating keys of the films in a library with exactly the given title
+///
+/// Titles are compared ignoring case, with the original title as well, so
+/// remakes sharing a title are all returned. When no film has the title, the
+/// error lists the closest matches of the server's search.
+fn find_title(client: &PlexClient, library_section_id: &str, title: &str) -> Result<Vec<String>> {
+    let results = client.search_library(library_section_id, title)?.metadata;
+    let is_match = |metadata: &PlexMediaItemMetadata| {
+        metadata.title.eq_ignore_ascii_case(title)
+            || metadata
+                .original_title
+                .as_deref()
+                .is_some_and(|original| original.eq_ignore_ascii_case(title))
+    };
+
+    let rating_keys: Vec<String> = results
+        .iter()
+        .filter(|metadata| is_match(metadata))
+        .filter_map(|metadata| metadata.rating_key.clone())
+        .collect();
+    if rating_keys.is_empty() {
+        let similar = results
+            .iter()
+            .take(5)
+            .map(|metadata| match metadata.year {
+                Some(year) => format!("{} ({})", metadata.title, year),
+                None => metadata.title.clone(),
+            })
+            .collect::<Vec<_>>();
+        if similar.is_empty() {
+            anyhow::bail!("No film titled \"{}\" found in the library", title);
+        }
+        anyhow::bail!(
+            "No film titled \"{}\" found in the library. Similar titles: {}",
+            title,
+            similar.join(", ")
+        );
+    }
+    if rating_keys.len() > 1 {
+        status(format_args!(
+            "Found {} films titled \"{}\"; exporting all of them (use --rating-key to pick one)",
+            rating_keys.len(),
+            title
+        ));
+    }
+    Ok(rating_keys)
+}
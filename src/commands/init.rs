@@ -0,0 +1,287 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use clap::Args;
+use plex_client::plex_tv::{PlexTvClient, PlexTvServer};
+use plex_to_letterboxd::config::{ProfileConfig, DEFAULT_CONFIG_FILE};
+use plex_to_letterboxd::style::{paint, SUCCESS};
+
+use super::login::{client_identifier, log_in};
+use super::GlobalArgs;
+
+/// Output path suggested when none is entered
+const DEFAULT_OUTPUT: &str = "plex_watch_history.csv";
+
+/// Tag Letterboxd entries get when none is configured
+const DEFAULT_TAG: &str = "Imported from Plex";
+
+/// Flags for the `init` subcommand
+#[derive(Args, Debug)]
+pub struct InitArgs {
+    /// Identifier reported to plex.tv when logging in
+    /// Can also be set via PLEX_CLIENT_IDENTIFIER environment variable
+    #[arg(long, env = "PLEX_CLIENT_IDENTIFIER")]
+    pub client_identifier: Option<String>,
+
+    /// How long to wait for the login to be approved, in seconds
+    #[arg(long, default_value_t = 300)]
+    pub timeout: u64,
+}
+
+/// Runs the `init` subcommand
+///
+/// Asks for the server (picked from the account's servers after logging in,
+/// or typed in), the library, the output path and the tags, checking the
+/// server and library along the way. The answers are saved as a profile of
+/// the config file, which becomes its default profile unless it already has
+/// one, so that a plain `export` uses them. The settings of an existing config
+/// file are kept, but not its comments and formatting, which is confirmed
+/// before asking anything else.
+pub fn run(global: &GlobalArgs, args: &InitArgs) -> Result<()> {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        anyhow::bail!("init requires an interactive terminal");
+    }
+
+    let path = global
+        .config
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG_FILE));
+    let mut config = global.read_config()?;
+    println!(
+        "This will save your settings to {}. Press Enter to accept the [default] answers.\n",
+        path.display()
+    );
+    if path.exists()
+        && !confirm(
+            &format!(
+                "{} will be rewritten without its comments and formatting. Continue?",
+                path.display()
+            ),
+            false,
+        )?
+    {
+        anyhow::bail!("Nothing was written");
+    }
+
+    let default_name = global
+        .profile
+        .clone()
+        .or_else(|| config.default_profile.clone())
+        .unwrap_or_else(|| "default".to_string());
+    let name = ask("Profile name", Some(&default_name))?;
+    if config.profiles.contains_key(&name)
+        && !confirm(&format!("Replace the existing profile '{}'?", name), false)?
+    {
+        anyhow::bail!("Nothing was written");
+    }
+
+    let (plex_url, plex_token) = choose_server(args)?;
+
+    println!("\nConnecting to {}...", plex_url);
+    let client = global.build_client(plex_url.clone(), plex_token.clone())?;
+    let sections = client
+        .get_library_sections()
+        .context("Failed to get library sections")?;
+    let libraries: Vec<_> = sections
+        .directory
+        .iter()
+        .filter(|library| library.section_type == "movie")
+        .map(|library| library.title.clone())
+        .collect();
+    if libraries.is_empty() {
+        anyhow::bail!("The server has no movie library");
+    }
+    let library_name = libraries[choose("Movie library", &libraries)?].clone();
+
+    let output_csv = ask(
+        "Output file ({date} and {library} are filled in)",
+        Some(DEFAULT_OUTPUT),
+    )?;
+    let tags: Vec<String> = ask("Letterboxd tags, comma separated", Some(DEFAULT_TAG))?
+        .split(',')
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect();
+
+    config.profiles.insert(
+        name.clone(),
+        ProfileConfig {
+            plex_url: Some(plex_url),
+            plex_token: Some(plex_token),
+            library_name: Some(library_name),
+            output_csv: (output_csv != DEFAULT_OUTPUT).then_some(output_csv),
+            // The default tag needs no setting
+            tags: if tags == [DEFAULT_TAG] {
+                Vec::new()
+            } else {
+                tags
+            },
+            ..Default::default()
+        },
+    );
+    let is_default = *config.default_profile.get_or_insert_with(|| name.clone()) == name;
+
+    let contents = toml::to_string(&config).context("Failed to serialize the config")?;
+    write_config(&path, &contents)
+        .with_context(|| format!("Failed to write config file: {}", path.display()))?;
+
+    println!(
+        "\n{} Saved profile '{}' to {}",
+        paint(SUCCESS, "✓"),
+        name,
+        path.display()
+    );
+    if is_default {
+        println!("Export your watch history with: plex-to-letterboxd export");
+    } else {
+        println!(
+            "Export your watch history with: plex-to-letterboxd export --profile {}",
+            name
+        );
+    }
+    Ok(())
+}
+
+/// Asks for the server URL and token, logging in through plex.tv to list the account's servers
+fn choose_server(args: &InitArgs) -> Result<(String, String)> {
+    if !confirm("Log in to Plex in your browser?", true)? {
+        let url = ask("Plex server URL (e.g., http://192.168.1.100:32400)", None)?;
+        let token = ask("Plex token", None)?;
+        return Ok((url, token));
+    }
+
+    let plex_tv = PlexTvClient::new(client_identifier(args.client_identifier.as_deref()));
+    let token = log_in(&plex_tv, Duration::from_secs(args.timeout))?;
+    println!("{} Logged in\n", paint(SUCCESS, "✓"));
+
+    let servers: Vec<PlexTvServer> = match plex_tv.get_servers(&token) {
+        Ok(servers) => servers
+            .into_iter()
+            .filter(|server| server.preferred_url().is_some())
+            .collect(),
+        Err(err) => {
+            println!("Could not list your servers ({:#})", err);
+            Vec::new()
+        }
+    };
+    let mut choices: Vec<String> = servers
+        .iter()
+        .map(|server| {
+            format!(
+                "{} ({}{})",
+                server.name,
+                server.preferred_url().unwrap_or_default(),
+                if server.owned { "" } else { ", shared" }
+            )
+        })
+        .collect();
+    choices.push("Enter a URL".to_string());
+
+    let choice = choose("Plex server", &choices)?;
+    match servers.get(choice) {
+        Some(server) => Ok((
+            server.preferred_url().unwrap_or_default().to_string(),
+            // Shared servers only accept their own token
+            server.access_token.clone().unwrap_or(token),
+        )),
+        None => Ok((
+            ask("Plex server URL (e.g., http://192.168.1.100:32400)", None)?,
+            token,
+        )),
+    }
+}
+
+/// Prints a prompt and reads the trimmed answer, failing once stdin is closed
+fn read_answer(prompt: &str) -> Result<String> {
+    print!("{}", prompt);
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    if io::stdin().lock().read_line(&mut answer)? == 0 {
+        anyhow::bail!("Setup cancelled");
+    }
+    Ok(answer.trim().to_string())
+}
+
+/// Asks a question, returning the answer or the default for an empty one
+///
+/// Questions without a default are asked again until they are answered.
+fn ask(question: &str, default: Option<&str>) -> Result<String> {
+    loop {
+        let answer = match default {
+            Some(default) => read_answer(&format!("{} [{}]: ", question, default))?,
+            None => read_answer(&format!("{}: ", question))?,
+        };
+        match (answer.is_empty(), default) {
+            (true, Some(default)) => return Ok(default.to_string()),
+            (true, None) => continue,
+            (false, _) => return Ok(answer),
+        }
+    }
+}
+
+/// Asks a yes/no question
+fn confirm(question: &str, default: bool) -> Result<bool> {
+    let hint = if default { "Y/n" } else { "y/N" };
+    loop {
+        let answer = read_answer(&format!("{} [{}]: ", question, hint))?;
+        match answer.to_lowercase().as_str() {
+            "" => return Ok(default),
+            "y" | "yes" => return Ok(true),
+            "n" | "no" => return Ok(false),
+            _ => continue,
+        }
+    }
+}
+
+/// Lists numbered choices and asks for one, returning its index
+///
+/// A single choice is picked without asking.
+fn choose(question: &str, choices: &[String]) -> Result<usize> {
+    if let [choice] = choices {
+        println!("{}: {}", question, choice);
+        return Ok(0);
+    }
+    for (index, choice) in choices.iter().enumerate() {
+        println!("  {}. {}", index + 1, choice);
+    }
+    loop {
+        let answer = ask(question, Some("1"))?;
+        match answer.parse::<usize>() {
+            Ok(number) if (1..=choices.len()).contains(&number) => return Ok(number - 1),
+            _ => println!("Enter a number from 1 to {}", choices.len()),
+        }
+    }
+}
+
+/// Writes the config file, readable by its owner only since it holds a token
+///
+/// The contents go to a temporary file created with those permissions, which
+/// then replaces the config file, so the token is never readable by others
+/// and an interrupted write leaves the previous file intact.
+fn write_config(path: &Path, contents: &str) -> Result<()> {
+    let file_name = path.file_name().context("Not a file path")?;
+    let temp_path = path.with_file_name(format!(".{}.tmp", file_name.to_string_lossy()));
+    // A leftover temporary file would keep its permissions
+    match fs::remove_file(&temp_path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+        _ => {}
+    }
+
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        options.mode(0o600);
+    }
+    let mut file = options.open(&temp_path)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()?;
+    fs::rename(&temp_path, path)?;
+    Ok(())
+}
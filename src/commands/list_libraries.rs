@@ -0,0 +1,18 @@
+use anyhow::{Context, Result};
+
+use super::GlobalArgs;
+
+/// Runs the `list-libraries` subcommand
+pub fn run(global: &GlobalArgs) -> Result<()> {
+    let client = global.client()?;
+    let library_sections = client
+        .get_library_sections()
+        .context("Failed to get library sections")?;
+
+    println!("{:<6} {:<8} TITLE", "ID", "TYPE");
+    for dir in &library_sections.directory {
+        println!("{:<6} {:<8} {}", dir.key, dir.section_type, dir.title);
+    }
+
+    Ok(())
+}
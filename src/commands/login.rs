@@ -0,0 +1,65 @@
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use clap::Args;
+use plex_client::plex_tv::PlexTvClient;
+
+/// Flags for the `login` subcommand
+#[derive(Args, Debug)]
+pub struct LoginArgs {
+    /// Identifier reported to plex.tv for this installation
+    /// Can also be set via PLEX_CLIENT_IDENTIFIER environment variable
+    #[arg(long, env = "PLEX_CLIENT_IDENTIFIER")]
+    pub client_identifier: Option<String>,
+
+    /// How long to wait for the login to be approved, in seconds
+    #[arg(long, default_value_t = 300)]
+    pub timeout: u64,
+}
+
+/// Runs the `login` subcommand
+///
+/// Creates a plex.tv PIN, asks the user to approve it in the browser and
+/// prints the resulting authentication token.
+pub fn run(args: &LoginArgs) -> Result<()> {
+    let plex_tv = PlexTvClient::new(client_identifier(args.client_identifier.as_deref()));
+    let token = log_in(&plex_tv, Duration::from_secs(args.timeout))?;
+
+    println!("\n✓ Logged in. Your Plex token is:\n");
+    println!("  {}\n", token);
+    println!("Use it with --plex-token or set the PLEX_TOKEN environment variable.");
+
+    Ok(())
+}
+
+/// Returns the identifier reported to plex.tv, or a new one for this run when none is configured
+pub fn client_identifier(configured: Option<&str>) -> String {
+    configured.map(str::to_string).unwrap_or_else(|| {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        format!("plex-to-letterboxd-{:x}-{:x}", std::process::id(), nanos)
+    })
+}
+
+/// Asks the user to approve a new plex.tv PIN in the browser, returning the token once approved
+pub fn log_in(plex_tv: &PlexTvClient, timeout: Duration) -> Result<String> {
+    let mut pin = plex_tv.create_pin()?;
+    println!("Open the following URL to log in to Plex:\n");
+    println!("  {}\n", plex_tv.auth_url(&pin));
+    println!("Waiting for approval...");
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(token) = pin.auth_token {
+            return Ok(token);
+        }
+        if Instant::now() >= deadline {
+            anyhow::bail!("Timed out waiting for the login to be approved");
+        }
+        thread::sleep(Duration::from_secs(2));
+        pin = plex_tv.check_pin(&pin)?;
+    }
+}
@@ -0,0 +1,611 @@
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use clap::{Args, ValueEnum};
+use plex_client::client::{
+    CircuitBreaker, ConnectionSettings, Middleware, PlexClient, ProxyAuth, ResponseCache,
+    ResponseCapture,
+};
+use plex_client::library::{PlexLibrarySection, PlexLibrarySectionsDirectory};
+use plex_to_letterboxd::config::{Config, ServerConfig, DEFAULT_CONFIG_FILE};
+use plex_to_letterboxd::console::status;
+use plex_to_letterboxd::redact::Redactor;
+use reqwest::{StatusCode, Url};
+
+/// Browse the watch history and export a selection
+pub mod browse;
+/// Sync periodically and serve a health endpoint
+pub mod daemon;
+/// Compare two exports
+pub mod diff;
+/// Diagnose connection and configuration problems
+pub mod doctor;
+/// Export watch history to a Letterboxd CSV file
+pub mod export;
+/// Export the viewings of specific films
+pub mod export_items;
+/// Write a config file through an interactive setup
+pub mod init;
+/// List the library sections available on the server
+pub mod list_libraries;
+/// Log in to plex.tv to obtain an authentication token
+pub mod login;
+/// Export from raw responses saved with --save-raw-responses
+pub mod replay;
+/// Search the watch history for a film
+pub mod search;
+/// Log in to Simkl to obtain an access token
+pub mod simkl_login;
+/// Print analytics of the watch history
+pub mod stats;
+/// Append newly watched items to an existing export
+pub mod sync;
+/// Log in to Trakt to obtain an access token
+pub mod trakt_login;
+/// Validate the configuration against the server
+pub mod validate;
+/// Add a Letterboxd watchlist to the Plex watchlist
+pub mod watchlist_import;
+
+/// URL of the placeholder server requests are made to when replaying raw responses
+const REPLAY_URL: &str = "http://replay.invalid";
+/// Token of the placeholder server used when replaying raw responses
+const REPLAY_TOKEN: &str = "replay";
+/// Consecutive failed requests that open the circuit breaker by default
+const DEFAULT_CIRCUIT_BREAKER: u32 = 5;
+/// Seconds the circuit breaker stays open by default
+const DEFAULT_CIRCUIT_BREAKER_COOLDOWN: u64 = 60;
+
+/// Flags shared by every subcommand
+#[derive(Args, Debug)]
+pub struct GlobalArgs {
+    /// Plex Media Server URL (e.g., http://192.168.1.100:32400)
+    /// Can also be set via PLEX_URL environment variable
+    #[arg(long, env = "PLEX_URL", global = true)]
+    pub plex_url: Option<String>,
+
+    /// Plex authentication token
+    /// Can also be set via PLEX_TOKEN environment variable
+    #[arg(long, env = "PLEX_TOKEN", global = true)]
+    pub plex_token: Option<String>,
+
+    /// File containing the Plex token (e.g., a Docker secret); "-" reads it from stdin
+    /// Takes precedence over --plex-token. Can also be set via PLEX_TOKEN_FILE environment variable
+    #[arg(long, env = "PLEX_TOKEN_FILE", value_name = "FILE", global = true)]
+    pub plex_token_file: Option<PathBuf>,
+
+    /// Token read from --plex-token-file, cached since stdin can only be read once
+    #[arg(skip)]
+    token_from_file: OnceLock<String>,
+
+    /// Directory of raw responses served instead of the server, set by the `replay` subcommand
+    #[arg(skip)]
+    replay_dir: OnceLock<PathBuf>,
+
+    /// Whether requests fail while the circuit breaker is open, set by the `daemon` subcommand
+    #[arg(skip)]
+    circuit_breaker_fails_fast: AtomicBool,
+
+    /// Number of failed requests to Plex servers, counted for the `daemon` subcommand
+    #[arg(skip)]
+    request_errors: OnceLock<Arc<AtomicU64>>,
+
+    /// Language for localized titles and fields returned by the server (e.g., "en-US")
+    /// Can also be set via PLEX_METADATA_LANGUAGE environment variable
+    #[arg(long, env = "PLEX_METADATA_LANGUAGE", global = true)]
+    pub metadata_language: Option<String>,
+
+    /// Times a failed watch history page is retried before giving up (default: 2)
+    /// Can also be set via PLEX_RETRIES environment variable
+    #[arg(long, env = "PLEX_RETRIES", value_name = "N", global = true)]
+    pub retries: Option<u32>,
+
+    /// Consecutive failed requests to a server after which requests are paused (default: 5);
+    /// 0 disables the circuit breaker. Can also be set via PLEX_CIRCUIT_BREAKER environment variable
+    #[arg(long, env = "PLEX_CIRCUIT_BREAKER", value_name = "N", global = true)]
+    pub circuit_breaker: Option<u32>,
+
+    /// Seconds requests are paused for once the circuit breaker opens (default: 60)
+    #[arg(long, value_name = "SECONDS", global = true)]
+    pub circuit_breaker_cooldown: Option<u64>,
+
+    /// Server account whose watch history is exported (e.g. "1"); can be repeated
+    /// to merge the histories of several accounts, such as the owner and a managed user
+    /// Defaults to the account of the token's user. Can also be set via PLEX_ACCOUNT_ID
+    /// environment variable (comma separated)
+    #[arg(long, env = "PLEX_ACCOUNT_ID", value_delimiter = ',', global = true)]
+    pub account_id: Vec<String>,
+
+    /// Basic auth credentials for a reverse proxy in front of the server, as "user:password"
+    /// Sent in addition to the Plex token. Can also be set via PLEX_BASIC_AUTH environment variable
+    #[arg(long, env = "PLEX_BASIC_AUTH", value_name = "USER:PASSWORD", value_parser = parse_basic_auth, global = true)]
+    pub basic_auth: Option<ProxyAuth>,
+
+    /// Bearer token for a reverse proxy in front of the server (e.g. Authelia)
+    /// Sent in addition to the Plex token. Can also be set via PLEX_BEARER_TOKEN environment variable
+    #[arg(
+        long,
+        env = "PLEX_BEARER_TOKEN",
+        conflicts_with = "basic_auth",
+        global = true
+    )]
+    pub bearer_token: Option<String>,
+
+    /// Seconds an idle connection to the server is kept open for reuse (default: 90)
+    /// Lower it when idle connections are dropped silently, e.g. over flaky Wi-Fi
+    #[arg(long, value_name = "SECONDS", global = true)]
+    pub pool_idle_timeout: Option<u64>,
+
+    /// Maximum number of idle connections kept open per server
+    #[arg(long, value_name = "N", global = true)]
+    pub pool_max_idle_per_host: Option<usize>,
+
+    /// Seconds between TCP keep-alive probes on open connections (disabled by default)
+    #[arg(long, value_name = "SECONDS", global = true)]
+    pub tcp_keepalive: Option<u64>,
+
+    /// Maximum number of redirects followed per request (default: 10); 0 disables redirects
+    #[arg(long, value_name = "N", global = true)]
+    pub max_redirects: Option<usize>,
+
+    /// Print every redirect that is followed, to debug reverse proxy setups
+    #[arg(long, global = true)]
+    pub log_redirects: bool,
+
+    /// Never wait for input: skip --review and export every row from browse, so runs
+    /// from cron or CI cannot hang. Can also be set via PLEX_TO_LETTERBOXD_YES environment variable
+    #[arg(short, long, env = "PLEX_TO_LETTERBOXD_YES", global = true)]
+    pub yes: bool,
+
+    /// Log every request to the server (endpoint, page offset, status and duration) to stderr,
+    /// tagged with an ID per run. Can also be set via PLEX_TO_LETTERBOXD_VERBOSE environment variable
+    #[arg(short, long, env = "PLEX_TO_LETTERBOXD_VERBOSE", global = true)]
+    pub verbose: bool,
+
+    /// How fatal errors are printed on stderr: as text, or as a JSON object with the
+    /// error's kind, message, endpoint and HTTP status for wrapper scripts and UIs
+    #[arg(
+        long,
+        value_enum,
+        default_value_t,
+        env = "PLEX_TO_LETTERBOXD_ERROR_FORMAT",
+        global = true
+    )]
+    pub error_format: ErrorFormat,
+
+    /// Path to the TOML config file (defaults to "plex-to-letterboxd.toml" if present)
+    /// Can also be set via PLEX_TO_LETTERBOXD_CONFIG environment variable
+    #[arg(long, env = "PLEX_TO_LETTERBOXD_CONFIG", global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Profile of the config file to use (e.g., "home"), supplying the server, token,
+    /// library, output path, columns and servers not given on the command line
+    /// Can also be set via PLEX_TO_LETTERBOXD_PROFILE environment variable
+    #[arg(
+        long,
+        env = "PLEX_TO_LETTERBOXD_PROFILE",
+        value_name = "NAME",
+        global = true
+    )]
+    pub profile: Option<String>,
+
+    /// Directory every Plex server response is saved to, so later runs can use --offline
+    /// Can also be set via PLEX_RESPONSE_CACHE environment variable
+    #[arg(long, env = "PLEX_RESPONSE_CACHE", value_name = "DIR", global = true)]
+    pub response_cache: Option<PathBuf>,
+
+    /// Serve every Plex server request from the responses saved in --response-cache,
+    /// without contacting the server
+    #[arg(long, requires = "response_cache", global = true)]
+    pub offline: bool,
+
+    /// Write every raw Plex server response to this directory, with credentials
+    /// redacted, to attach to bug reports about responses that fail to parse
+    #[arg(long, value_name = "DIR", global = true)]
+    pub save_raw_responses: Option<PathBuf>,
+
+    /// Plex server as "url,token"; can be repeated to merge the histories of several servers
+    #[arg(long = "server", value_name = "URL,TOKEN", global = true)]
+    pub servers: Vec<ServerConfig>,
+}
+
+/// How fatal errors are printed
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ErrorFormat {
+    /// A colored, human-readable message
+    #[default]
+    Text,
+    /// A single-line JSON object (see `ErrorReport`)
+    Json,
+}
+
+/// Parses proxy basic auth credentials from "user:password"
+fn parse_basic_auth(s: &str) -> Result<ProxyAuth, String> {
+    match s.split_once(':') {
+        Some((username, password)) if !username.is_empty() => Ok(ProxyAuth::Basic {
+            username: username.to_string(),
+            password: password.to_string(),
+        }),
+        _ => Err("Expected 'user:password'".to_string()),
+    }
+}
+
+impl GlobalArgs {
+    /// Reads the config file with the selected profile applied, or returns the default config when there is none
+    pub fn load_config(&self) -> Result<Config> {
+        let config = self.read_config()?;
+        match self.profile_name(&config) {
+            Some(profile) => config.with_profile(&profile),
+            None => Ok(config),
+        }
+    }
+
+    /// Returns the profile selected with `--profile`, or the config file's `default_profile`
+    pub fn profile_name(&self, config: &Config) -> Option<String> {
+        self.profile
+            .clone()
+            .or_else(|| config.default_profile.clone())
+    }
+
+    /// Reads the config file as written, or returns the default config when there is none
+    pub fn read_config(&self) -> Result<Config> {
+        match &self.config {
+            Some(path) => Config::from_path(path),
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                Config::from_path(DEFAULT_CONFIG_FILE)
+            }
+            None => Ok(Config::default()),
+        }
+    }
+
+    /// Returns the Plex server URL, or an error explaining how to provide it
+    pub fn base_url(&self) -> Result<String> {
+        self.plex_url.clone().context(
+            "Missing required argument: PLEX_URL\n\
+             Please provide --plex-url or set the PLEX_URL environment variable.\n\
+             Example: --plex-url http://192.168.1.100:32400",
+        )
+    }
+
+    /// Reads the token given with `--plex-token-file`, if any
+    fn token_from_file(&self) -> Result<Option<String>> {
+        let Some(path) = &self.plex_token_file else {
+            return Ok(None);
+        };
+        if let Some(token) = self.token_from_file.get() {
+            return Ok(Some(token.clone()));
+        }
+
+        let contents = if path.as_os_str() == "-" {
+            let mut contents = String::new();
+            io::stdin()
+                .read_to_string(&mut contents)
+                .context("Failed to read the Plex token from stdin")?;
+            contents
+        } else {
+            fs::read_to_string(path)
+                .with_context(|| format!("Failed to read Plex token file: {}", path.display()))?
+        };
+
+        let token = self
+            .token_from_file
+            .get_or_init(|| contents.trim().to_string());
+        Ok(Some(token.clone()))
+    }
+
+    /// Returns the Plex token, or an error explaining how to provide it
+    ///
+    /// The token is read from `--plex-token-file` when given, otherwise from `--plex-token`.
+    pub fn token(&self) -> Result<String> {
+        let token = self.token_from_file()?.or_else(|| self.plex_token.clone());
+        let token = token.context(
+            "Missing required argument: PLEX_TOKEN\n\
+             Please provide --plex-token, --plex-token-file or set the PLEX_TOKEN environment variable.\n\
+             To find your token, see: https://support.plex.tv/articles/204059436-finding-an-authentication-token-x-plex-token/",
+        )?;
+
+        if token.is_empty() {
+            anyhow::bail!(
+                "PLEX_TOKEN cannot be empty\n\
+                 Please provide a valid token via --plex-token or set the PLEX_TOKEN environment variable.\n\
+                 To find your token, see: https://support.plex.tv/articles/204059436-finding-an-authentication-token-x-plex-token/"
+            );
+        }
+
+        Ok(token)
+    }
+
+    /// Creates a Plex client from the global flags
+    pub fn client(&self) -> Result<PlexClient> {
+        if self.replay_dir.get().is_some() {
+            return self.build_client(REPLAY_URL.to_string(), REPLAY_TOKEN.to_string());
+        }
+        self.build_client(self.base_url()?, self.token()?)
+    }
+
+    /// Serves every Plex server request from raw responses saved with `--save-raw-responses`
+    ///
+    /// Replaces the configured servers with a single placeholder server, so
+    /// that no request leaves the machine.
+    fn replay_raw_responses(&self, dir: &Path) {
+        let _ = self.replay_dir.set(dir.to_path_buf());
+    }
+
+    /// Fails requests while the circuit breaker is open, instead of pausing
+    ///
+    /// Used by the daemon, which retries at the next cycle anyway.
+    fn fail_fast_on_open_circuit(&self) {
+        self.circuit_breaker_fails_fast
+            .store(true, Ordering::Relaxed);
+    }
+
+    /// Counts the requests to Plex servers that fail, returning the counter
+    ///
+    /// Used by the daemon to report API errors in its metrics. Requests
+    /// answered with an error status and requests without a response both
+    /// count, including each retry.
+    fn count_request_errors(&self) -> Arc<AtomicU64> {
+        Arc::clone(self.request_errors.get_or_init(Default::default))
+    }
+
+    /// Returns the circuit breaker for a new client, unless disabled with `--circuit-breaker 0`
+    fn circuit_breaker(&self) -> Option<CircuitBreaker> {
+        let threshold = self.circuit_breaker.unwrap_or(DEFAULT_CIRCUIT_BREAKER);
+        if threshold == 0 {
+            return None;
+        }
+        let cool_down = Duration::from_secs(
+            self.circuit_breaker_cooldown
+                .unwrap_or(DEFAULT_CIRCUIT_BREAKER_COOLDOWN),
+        );
+        let breaker = CircuitBreaker::new(threshold, cool_down);
+        Some(if self.circuit_breaker_fails_fast.load(Ordering::Relaxed) {
+            breaker.failing_fast()
+        } else {
+            breaker
+        })
+    }
+
+    /// Returns whether Plex server requests are served from saved responses
+    fn is_offline(&self) -> bool {
+        self.offline || self.replay_dir.get().is_some()
+    }
+
+    /// Creates a Plex client for every server to export from
+    ///
+    /// Servers given with `--server` take precedence over the `servers` of the
+    /// config file; without either, the single `--plex-url`/`--plex-token`
+    /// server is used.
+    pub fn clients(&self, config: &Config) -> Result<Vec<PlexClient>> {
+        let servers = if self.servers.is_empty() {
+            &config.servers
+        } else {
+            &self.servers
+        };
+
+        if servers.is_empty() || self.replay_dir.get().is_some() {
+            return Ok(vec![self.client()?]);
+        }
+
+        servers
+            .iter()
+            .map(|server| self.build_client(server.url.clone(), server.token.clone()))
+            .collect()
+    }
+
+    /// Returns every credential given through the global flags or the config file
+    pub fn secrets(&self) -> Vec<String> {
+        let config_servers = self.load_config().map(|c| c.servers).unwrap_or_default();
+        self.plex_token
+            .iter()
+            .cloned()
+            .chain(self.token_from_file().ok().flatten())
+            .chain(self.servers.iter().map(|s| s.token.clone()))
+            .chain(self.bearer_token.clone())
+            .chain(match &self.basic_auth {
+                Some(ProxyAuth::Basic { password, .. }) => Some(password.clone()),
+                _ => None,
+            })
+            .chain(config_servers.into_iter().map(|s| s.token))
+            .collect()
+    }
+
+    /// Creates a redactor removing the global credentials from output
+    pub fn redactor(&self) -> Redactor {
+        Redactor::new(self.secrets())
+    }
+
+    /// Returns the reverse proxy credentials given with `--basic-auth` or `--bearer-token`
+    fn proxy_auth(&self) -> Option<ProxyAuth> {
+        self.basic_auth
+            .clone()
+            .or_else(|| self.bearer_token.clone().map(ProxyAuth::Bearer))
+    }
+
+    /// Creates a Plex client with the settings shared by every server
+    ///
+    /// The server version is checked right away, so that servers older than
+    /// supported fail before any export work starts.
+    fn build_client(&self, base_url: String, token: String) -> Result<PlexClient> {
+        let client = self.configure_client(base_url, token)?;
+        client.check_server_version()?;
+        Ok(client)
+    }
+
+    /// Creates a Plex client with the settings shared by every server, without contacting it
+    fn configure_client(&self, base_url: String, token: String) -> Result<PlexClient> {
+        let mut client =
+            PlexClient::new(base_url, token)?.with_connection_settings(ConnectionSettings {
+                pool_idle_timeout: self.pool_idle_timeout.map(Duration::from_secs),
+                pool_max_idle_per_host: self.pool_max_idle_per_host,
+                tcp_keepalive: self.tcp_keepalive.map(Duration::from_secs),
+                max_redirects: self.max_redirects,
+                log_redirects: self.log_redirects,
+            });
+        if let Some(language) = &self.metadata_language {
+            client = client.with_language(language);
+        }
+        // Commands reading a single history use the first account
+        if let Some(account_id) = self.account_id.first() {
+            client = client.with_account_id(account_id);
+        }
+        if let Some(proxy_auth) = self.proxy_auth() {
+            client = client.with_proxy_auth(proxy_auth);
+        }
+        if let Some(retries) = self.retries {
+            client = client.with_page_retries(retries);
+        }
+        if let Some(breaker) = self.circuit_breaker() {
+            client = client.with_circuit_breaker(breaker);
+        }
+        if let Some(dir) = self.replay_dir.get() {
+            client = client.with_response_cache(ResponseCache::replay_capture(dir)?);
+        } else if let Some(dir) = &self.response_cache {
+            client = client.with_response_cache(if self.offline {
+                ResponseCache::replay(dir)
+            } else {
+                ResponseCache::record(dir)
+            });
+        }
+        if let Some(errors) = self.request_errors.get() {
+            client = client.with_middleware(RequestErrorCounter(Arc::clone(errors)));
+        }
+        if let Some(dir) = &self.save_raw_responses {
+            let capture = self
+                .secrets()
+                .into_iter()
+                .fold(ResponseCapture::new(dir), ResponseCapture::with_secret);
+            client = client.with_response_capture(capture);
+        }
+        Ok(client)
+    }
+}
+
+/// Middleware counting requests that fail or are answered with an error status
+struct RequestErrorCounter(Arc<AtomicU64>);
+
+impl Middleware for RequestErrorCounter {
+    fn on_response(&self, _url: &Url, status: Option<StatusCode>, _elapsed: Duration) {
+        if !status.is_some_and(|status| status.is_success()) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Flags selecting a library section
+#[derive(Args, Debug)]
+pub struct LibraryArgs {
+    /// Library name to filter watch history (e.g., "Movies")
+    /// Defaults to the server's only movie library, when it has exactly one
+    #[arg(long)]
+    pub library_name: Option<String>,
+
+    /// Library section ID to filter watch history (e.g., "1")
+    /// Required when several libraries share the same name
+    #[arg(long, conflicts_with = "library_name")]
+    pub library_id: Option<String>,
+}
+
+/// Formats library sections as "Title (id: key)" for error messages
+fn describe_libraries(directories: &[&PlexLibrarySectionsDirectory]) -> String {
+    directories
+        .iter()
+        .map(|dir| format!("{} (id: {})", dir.title, dir.key))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Finds the library section selected by `--library-id` or `--library-name`
+pub fn select_library(
+    client: &PlexClient,
+    args: &LibraryArgs,
+) -> Result<PlexLibrarySectionsDirectory> {
+    // Get library sections to find the matching library
+    let library_sections = client
+        .get_library_sections()
+        .context("Failed to get library sections")?;
+    select_library_from(&library_sections, args)
+}
+
+/// Finds the library section selected by `--library-id` or `--library-name` among the given sections
+///
+/// Without either flag, the only movie library of the server is used; a
+/// server with no or several movie libraries is an error.
+pub fn select_library_from(
+    library_sections: &PlexLibrarySection,
+    args: &LibraryArgs,
+) -> Result<PlexLibrarySectionsDirectory> {
+    let all_directories = library_sections.directory.iter().collect::<Vec<_>>();
+
+    // Find the directory matching the library ID or name
+    let library_directory = match (&args.library_id, &args.library_name) {
+        (Some(library_id), _) => all_directories
+            .iter()
+            .copied()
+            .find(|dir| &dir.key == library_id)
+            .with_context(|| {
+                format!(
+                    "Library with id '{}' not found. Available libraries: {}",
+                    library_id,
+                    describe_libraries(&all_directories)
+                )
+            })?,
+        (None, Some(library_name)) => {
+            let matches = all_directories
+                .iter()
+                .copied()
+                .filter(|dir| &dir.title == library_name)
+                .collect::<Vec<_>>();
+            match matches.as_slice() {
+                [] => anyhow::bail!(
+                    "Library '{}' not found. Available libraries: {}",
+                    library_name,
+                    describe_libraries(&all_directories)
+                ),
+                [dir] => *dir,
+                _ => anyhow::bail!(
+                    "Multiple libraries are named '{}': {}\n\
+                         Please select one with --library-id.",
+                    library_name,
+                    describe_libraries(&matches)
+                ),
+            }
+        }
+        (None, None) => {
+            let movie_libraries = all_directories
+                .iter()
+                .copied()
+                .filter(|dir| dir.section_type == "movie")
+                .collect::<Vec<_>>();
+            match movie_libraries.as_slice() {
+                [dir] => {
+                    status(format_args!(
+                        "Using the only movie library: {} (id: {})",
+                        dir.title, dir.key
+                    ));
+                    *dir
+                }
+                [] => anyhow::bail!(
+                    concat!(
+                        "The server has no movie library; ",
+                        "please provide --library-name or --library-id. ",
+                        "Available libraries: {}"
+                    ),
+                    describe_libraries(&all_directories)
+                ),
+                _ => anyhow::bail!(
+                    "The server has several movie libraries: {}\n\
+                     Please select one with --library-name or --library-id.",
+                    describe_libraries(&movie_libraries)
+                ),
+            }
+        }
+    };
+
+    Ok(library_directory.clone())
+}
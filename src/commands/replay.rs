@@ -0,0 +1,28 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+
+use super::export::{self, ExportArgs};
+use super::GlobalArgs;
+
+/// Flags for the `replay` subcommand
+#[derive(Args, Debug)]
+pub struct ReplayArgs {
+    /// Directory written by --save-raw-responses
+    pub dir: PathBuf,
+
+    #[command(flatten)]
+    pub export: ExportArgs,
+}
+
+/// Runs the `replay` subcommand
+///
+/// Exports from the responses of an earlier run saved with
+/// `--save-raw-responses` instead of a server, so an export reported in a
+/// bug report can be reproduced exactly. Every flag of `export` applies;
+/// requests that were not captured fail instead of reaching a server.
+pub fn run(global: &GlobalArgs, args: &ReplayArgs) -> Result<()> {
+    global.replay_raw_responses(&args.dir);
+    export::run(global, &args.export)
+}
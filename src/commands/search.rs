@@ -0,0 +1,119 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{Context, Result};
+use clap::Args;
+use plex_client::media_item::PlexMediaItemMetadata;
+use plex_client::source::HistorySource;
+use plex_to_letterboxd::title::matches_search;
+
+use super::{select_library, GlobalArgs, LibraryArgs};
+
+/// Flags for the `search` subcommand
+#[derive(Args, Debug)]
+pub struct SearchArgs {
+    /// Text to search for in the titles of watched films (e.g., "blade runner")
+    pub query: String,
+
+    #[command(flatten)]
+    pub library: LibraryArgs,
+
+    /// Match films with the server's library search instead of comparing
+    /// titles locally, which also finds films by their original title
+    #[arg(long)]
+    pub server_search: bool,
+}
+
+/// Runs the `search` subcommand
+///
+/// Prints every viewing in the watch history of the library whose film
+/// matches the query, with its watch date, IMDb ID and rating key, so a
+/// specific viewing can be checked before exporting.
+pub fn run(global: &GlobalArgs, args: &SearchArgs) -> Result<()> {
+    let client = global.client()?;
+    let library = select_library(&client, &args.library)?;
+
+    // Metadata of the matching films by rating key, when already known
+    let mut films: HashMap<String, Option<PlexMediaItemMetadata>> = HashMap::new();
+    if args.server_search {
+        let results = client.search_library(&library.key, &args.query)?;
+        for metadata in results.metadata {
+            if let Some(rating_key) = metadata.rating_key.clone() {
+                films.insert(rating_key, Some(metadata));
+            }
+        }
+        if films.is_empty() {
+            println!("No films in {} match \"{}\"", library.title, args.query);
+            return Ok(());
+        }
+    }
+
+    // Viewings of each matching film, by rating key
+    let mut viewings: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for item in client.watch_history_iter(&library.key) {
+        let item = item.context("Failed to read the watch history")?;
+        let Some(rating_key) = item.rating_key else {
+            continue;
+        };
+        let matches = if args.server_search {
+            films.contains_key(&rating_key)
+        } else {
+            matches_search(&item.title, &args.query)
+        };
+        if !matches {
+            continue;
+        }
+
+        let watched = match item.viewed_at {
+            Some(viewed_at) => viewed_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            None => "(undated)".to_string(),
+        };
+        viewings.entry(rating_key).or_default().push(watched);
+    }
+
+    let mut count = 0;
+    for (rating_key, dates) in &viewings {
+        // Metadata is only requested for matching films, once each
+        let metadata = match films.remove(rating_key).flatten() {
+            Some(metadata) => metadata,
+            None => client
+                .metadata(rating_key)
+                .with_context(|| format!("Failed to get metadata for rating key {}", rating_key))?,
+        };
+        let imdb_id = metadata
+            .guid
+            .iter()
+            .find_map(|g| g.id.strip_prefix("imdb://"))
+            .unwrap_or("no IMDb ID");
+        let year = metadata
+            .year
+            .map(|y| format!(" ({})", y))
+            .unwrap_or_default();
+
+        println!(
+            "\n{}{}  {}  rating key {}",
+            metadata.title, year, imdb_id, rating_key
+        );
+        for date in dates {
+            println!("  Watched {}", date);
+        }
+        count += dates.len();
+    }
+
+    // Films the server found that were never watched
+    for metadata in films.into_values().flatten() {
+        let year = metadata
+            .year
+            .map(|y| format!(" ({})", y))
+            .unwrap_or_default();
+        println!("\n{}{}  not in the watch history", metadata.title, year);
+    }
+
+    println!(
+        "\n{} viewings of {} films in {} match \"{}\"",
+        count,
+        viewings.len(),
+        library.title,
+        args.query
+    );
+    Ok(())
+}
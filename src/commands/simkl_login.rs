@@ -0,0 +1,48 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use clap::Args;
+use plex_to_letterboxd::simkl::SimklClient;
+
+/// Flags for the `simkl-login` subcommand
+#[derive(Args, Debug)]
+pub struct SimklLoginArgs {
+    /// Client ID of your Simkl API application (https://simkl.com/settings/developer/)
+    /// Can also be set via SIMKL_CLIENT_ID environment variable
+    #[arg(long, env = "SIMKL_CLIENT_ID")]
+    pub simkl_client_id: String,
+}
+
+/// Runs the `simkl-login` subcommand
+///
+/// Creates a Simkl PIN, asks the user to approve it in the browser and
+/// prints the resulting access token.
+pub fn run(args: &SimklLoginArgs) -> Result<()> {
+    let simkl = SimklClient::new(args.simkl_client_id.clone());
+
+    let pin = simkl.create_pin()?;
+    println!("Open the following URL and enter the code to log in to Simkl:\n");
+    println!("  {}", pin.verification_url);
+    println!("  Code: {}\n", pin.user_code);
+    println!("Waiting for approval...");
+
+    let deadline = Instant::now() + Duration::from_secs(pin.expires_in);
+    let access_token = loop {
+        if Instant::now() >= deadline {
+            anyhow::bail!("Timed out waiting for the Simkl login to be approved");
+        }
+        thread::sleep(Duration::from_secs(pin.interval.max(1)));
+        if let Some(access_token) = simkl.poll_pin(&pin)? {
+            break access_token;
+        }
+    };
+
+    println!("\n✓ Logged in. Your Simkl access token is:\n");
+    println!("  {}\n", access_token);
+    println!(
+        "Use it with --simkl-access-token or set the SIMKL_ACCESS_TOKEN environment variable."
+    );
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use anyhow::Result;
+use clap::Args;
+use plex_to_letterboxd::console;
+use plex_to_letterboxd::report::RunReport;
+use plex_to_letterboxd::stats::{Viewing, ViewingStats};
+
+use super::export::{for_each_server_row, ExportArgs};
+use super::GlobalArgs;
+
+/// Flags for the `stats` subcommand
+#[derive(Args, Debug)]
+pub struct StatsArgs {
+    /// Number of most rewatched films and busiest days listed
+    #[arg(long, value_name = "N", default_value_t = 10)]
+    pub top: usize,
+
+    #[command(flatten)]
+    pub export: ExportArgs,
+}
+
+/// Runs the `stats` subcommand
+///
+/// Reads every viewing from the `--archive` database when one is given, or
+/// fetches the history like `export` (with the same filters) otherwise, and
+/// prints films per year and month, the most rewatched films, the busiest
+/// days and the total runtime watched.
+pub fn run(global: &GlobalArgs, args: &StatsArgs) -> Result<()> {
+    let viewings = match &args.export.archive {
+        Some(path) => archived_viewings(path)?,
+        None => fetched_viewings(global, &args.export)?,
+    };
+    print!("{}", ViewingStats::new(&viewings, args.top));
+    Ok(())
+}
+
+/// Fetches the viewings that `export` would write
+fn fetched_viewings(global: &GlobalArgs, args: &ExportArgs) -> Result<Vec<Viewing>> {
+    // Keep stdout for the statistics
+    console::status_to_stderr();
+
+    let config = global.load_config()?;
+    let mut viewings = Vec::new();
+    for_each_server_row(
+        global,
+        &config,
+        args,
+        &mut RunReport::default(),
+        |row, _| {
+            viewings.push(Viewing::from(&row));
+            Ok(())
+        },
+    )?;
+    Ok(viewings)
+}
+
+/// Reads every viewing of the archive
+#[cfg(feature = "sqlite")]
+fn archived_viewings(path: &std::path::Path) -> Result<Vec<Viewing>> {
+    if !path.exists() {
+        anyhow::bail!("Archive not found: {}", path.display());
+    }
+    plex_to_letterboxd::archive::HistoryArchive::open(path)?.viewings()
+}
+
+/// Reads every viewing of the archive
+#[cfg(not(feature = "sqlite"))]
+fn archived_viewings(path: &std::path::Path) -> Result<Vec<Viewing>> {
+    anyhow::bail!(
+        "--archive {} needs a build with the sqlite feature",
+        path.display()
+    )
+}
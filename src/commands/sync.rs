@@ -0,0 +1,165 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use csv::{Reader, WriterBuilder};
+
+use plex_to_letterboxd::console::{self, status};
+use plex_to_letterboxd::output::AtomicFile;
+use plex_to_letterboxd::remote::RemoteOutput;
+use plex_to_letterboxd::report::RunReport;
+use plex_to_letterboxd::row::{Column, ExportRow};
+use plex_to_letterboxd::style::{paint, SUCCESS};
+
+use super::export::{
+    add_reviews, check_rows, for_each_server_row, preflight, report_outcome, review_rows,
+    select_output_library, ExportArgs, Mode, Target,
+};
+use super::GlobalArgs;
+
+/// Runs the `sync` subcommand
+///
+/// Unlike `export`, which rewrites the output file, `sync` keeps the existing
+/// rows and only appends viewings that are not in the file yet (keyed by
+/// imdbID and WatchedDate, or by tmdbID or else Title for films without an
+/// IMDb ID).
+pub fn run(global: &GlobalArgs, args: &ExportArgs) -> Result<()> {
+    if args.print_count {
+        console::status_to_stderr();
+    }
+    let started_at = Utc::now();
+    let outcome = sync(global, args);
+    report_outcome(global, args, "sync", started_at, &outcome);
+    let report = outcome?;
+    let output_file = args.output_file()?;
+
+    status(format_args!(
+        "\n{} to: {}",
+        paint(
+            SUCCESS,
+            format!("✓ Added {} new rows", report.rows_exported)
+        ),
+        output_file
+    ));
+    status("Upload your watch history at: https://letterboxd.com/import/");
+    args.print_count(&report);
+
+    Ok(())
+}
+
+/// Appends the viewings missing from the output file, returning what was added and skipped
+pub fn sync(global: &GlobalArgs, args: &ExportArgs) -> Result<RunReport> {
+    if args.mode != Mode::Diary {
+        anyhow::bail!("sync only supports the diary mode; use export instead");
+    }
+    if args.template.is_some() || args.target != Target::Letterboxd {
+        anyhow::bail!("sync only supports the Letterboxd CSV target; use export instead");
+    }
+    if args.split_by_year {
+        anyhow::bail!("sync appends to a single file and does not support --split-by-year");
+    }
+
+    let config = global.load_config()?;
+    select_output_library(global, &config, args)?;
+    let columns = args.columns(&config);
+    let id_index = columns.iter().position(|c| *c == Column::ImdbId);
+    let date_index = columns.iter().position(|c| *c == Column::WatchedDate);
+    let (Some(id_index), Some(date_index)) = (id_index, date_index) else {
+        anyhow::bail!("sync requires the imdbID and WatchedDate columns");
+    };
+    let tmdb_index = columns.iter().position(|c| *c == Column::TmdbId);
+    let title_index = columns.iter().position(|c| *c == Column::Title);
+    // Films without an IMDb ID are recognized by their TMDb ID, or else their title
+    let film_key = |imdb_id: &str, tmdb_id: &str, title: &str| {
+        if !imdb_id.is_empty() {
+            imdb_id.to_string()
+        } else if tmdb_index.is_some() && !tmdb_id.is_empty() {
+            format!("tmdb://{}", tmdb_id)
+        } else {
+            format!("title:{}", title)
+        }
+    };
+
+    let output_file = &args.output_file()?;
+    if RemoteOutput::parse(output_file)?.is_some() {
+        anyhow::bail!("sync appends to a local file and cannot write to a URL; use export instead");
+    }
+    let exists = Path::new(output_file).exists();
+
+    // Collect the rows already present in the output file
+    let mut existing = HashSet::new();
+    if exists {
+        let mut rdr = Reader::from_path(output_file)
+            .with_context(|| format!("Failed to read output file: {}", output_file))?;
+
+        // Appending only works if the file was written with the same columns
+        let header = rdr.headers()?.iter().collect::<Vec<_>>();
+        if header != columns.iter().map(Column::header).collect::<Vec<_>>() {
+            anyhow::bail!(
+                "The columns of {} ({}) do not match the configured columns; use export to rewrite it",
+                output_file,
+                header.join(", ")
+            );
+        }
+
+        for record in rdr.records() {
+            let record = record?;
+            let field = |index: Option<usize>| {
+                index
+                    .and_then(|index| record.get(index))
+                    .unwrap_or_default()
+            };
+            existing.insert((
+                film_key(field(Some(id_index)), field(tmdb_index), field(title_index)),
+                field(Some(date_index)).to_string(),
+            ));
+        }
+    }
+
+    // Append to a copy of the output file, writing the header only for new
+    // files; the file is only replaced once every row has been written
+    let file = AtomicFile::append(output_file)?;
+    let mut wtr = WriterBuilder::new().has_headers(false).from_writer(file);
+    if !exists {
+        wtr.write_record(columns.iter().map(Column::header))?;
+    }
+
+    let mut report = RunReport::default();
+    let mut new_rows = Vec::new();
+    for_each_server_row(global, &config, args, &mut report, |row, report| {
+        let key = film_key(
+            &row.imdb_id,
+            row.tmdb_id.as_deref().unwrap_or_default(),
+            &args.output_row(&row).title,
+        );
+        if existing.insert((key, row.watched_date.clone())) {
+            new_rows.push(row);
+        } else {
+            report.skip(&row.title, "already in the output file");
+        }
+        Ok(())
+    })?;
+
+    // Append the new rows in a stable order, whatever order the servers returned them in
+    new_rows.sort_by(ExportRow::output_order);
+    add_reviews(args, &mut new_rows)?;
+    let new_rows = review_rows(global, args, new_rows, &mut report)?;
+    check_rows(args, &new_rows)?;
+    if preflight(args, &columns, &new_rows) {
+        return Ok(report);
+    }
+    for row in &new_rows {
+        wtr.write_record(Column::record(&columns, &args.output_row(row)))?;
+        report.exported();
+    }
+
+    // Flush the CSV buffer and move the file into place
+    args.backup_outputs()?;
+    wtr.into_inner()
+        .map_err(|e| e.into_error())
+        .with_context(|| format!("Failed to write output file: {}", output_file))?
+        .commit()?;
+
+    Ok(report)
+}
@@ -0,0 +1,53 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use clap::Args;
+use plex_to_letterboxd::trakt::TraktClient;
+
+/// Flags for the `trakt-login` subcommand
+#[derive(Args, Debug)]
+pub struct TraktLoginArgs {
+    /// Client ID of your Trakt API application (https://trakt.tv/oauth/applications)
+    /// Can also be set via TRAKT_CLIENT_ID environment variable
+    #[arg(long, env = "TRAKT_CLIENT_ID")]
+    pub trakt_client_id: String,
+
+    /// Client secret of your Trakt API application
+    /// Can also be set via TRAKT_CLIENT_SECRET environment variable
+    #[arg(long, env = "TRAKT_CLIENT_SECRET")]
+    pub trakt_client_secret: String,
+}
+
+/// Runs the `trakt-login` subcommand
+///
+/// Starts a Trakt device code login, asks the user to approve it in the
+/// browser and prints the resulting access token.
+pub fn run(args: &TraktLoginArgs) -> Result<()> {
+    let trakt = TraktClient::new(args.trakt_client_id.clone());
+
+    let code = trakt.create_device_code()?;
+    println!("Open the following URL and enter the code to log in to Trakt:\n");
+    println!("  {}", code.verification_url);
+    println!("  Code: {}\n", code.user_code);
+    println!("Waiting for approval...");
+
+    let deadline = Instant::now() + Duration::from_secs(code.expires_in);
+    let token = loop {
+        if Instant::now() >= deadline {
+            anyhow::bail!("Timed out waiting for the Trakt login to be approved");
+        }
+        thread::sleep(Duration::from_secs(code.interval.max(1)));
+        if let Some(token) = trakt.poll_device_token(&code, &args.trakt_client_secret)? {
+            break token;
+        }
+    };
+
+    println!("\n✓ Logged in. Your Trakt access token is:\n");
+    println!("  {}\n", token.access_token);
+    println!(
+        "Use it with --trakt-access-token or set the TRAKT_ACCESS_TOKEN environment variable."
+    );
+
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use anyhow::{Context, Result};
+use clap::Args;
+
+use super::{select_library, GlobalArgs, LibraryArgs};
+
+/// Flags for the `validate` subcommand
+#[derive(Args, Debug)]
+pub struct ValidateArgs {
+    /// Library name to check (e.g., "Movies")
+    #[arg(long)]
+    pub library_name: Option<String>,
+
+    /// Library section ID to check (e.g., "1")
+    #[arg(long, conflicts_with = "library_name")]
+    pub library_id: Option<String>,
+}
+
+/// Runs the `validate` subcommand
+///
+/// Checks that the server accepts the token and, when given, that the library
+/// selection resolves to exactly one section. Fails on the first problem.
+pub fn run(global: &GlobalArgs, args: &ValidateArgs) -> Result<()> {
+    let client = global.client()?;
+    client
+        .get_library_sections()
+        .context("Server rejected the request; check PLEX_URL and PLEX_TOKEN")?;
+
+    if args.library_name.is_some() || args.library_id.is_some() {
+        let library = select_library(
+            &client,
+            &LibraryArgs {
+                library_name: args.library_name.clone(),
+                library_id: args.library_id.clone(),
+            },
+        )?;
+        println!("✓ Library: {} (id: {})", library.title, library.key);
+    }
+
+    println!("✓ Configuration is valid");
+
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+use plex_client::discover::{PlexDiscoverClient, PlexDiscoverItem};
+use plex_to_letterboxd::title::normalize_title;
+use plex_to_letterboxd::watchlist::{read_watchlist, WatchlistEntry};
+
+use super::GlobalArgs;
+
+/// Flags for the `watchlist-import` subcommand
+#[derive(Args, Debug)]
+pub struct WatchlistImportArgs {
+    /// Letterboxd watchlist export (watchlist.csv from https://letterboxd.com/settings/data/)
+    pub watchlist_csv: PathBuf,
+
+    /// Only report which films would be added
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// Picks the search result matching a watchlist entry
+///
+/// Results must have the same title (ignoring case and edition markers); when
+/// the entry has a year, the result's year must be within one year of it.
+fn find_match<'a>(
+    entry: &WatchlistEntry,
+    results: &'a [PlexDiscoverItem],
+) -> Option<&'a PlexDiscoverItem> {
+    let title = normalize_title(&entry.name).title;
+    results.iter().find(|item| {
+        let same_title = normalize_title(&item.title)
+            .title
+            .eq_ignore_ascii_case(&title);
+        let same_year = match (entry.year, item.year) {
+            (Some(wanted), Some(year)) => wanted.abs_diff(year) <= 1,
+            _ => true,
+        };
+        same_title && same_year
+    })
+}
+
+/// Runs the `watchlist-import` subcommand
+///
+/// Adds every film of a Letterboxd watchlist export to the Plex watchlist of
+/// the account the token belongs to.
+pub fn run(global: &GlobalArgs, args: &WatchlistImportArgs) -> Result<()> {
+    let discover = PlexDiscoverClient::new(global.token()?);
+    let entries = read_watchlist(&args.watchlist_csv)?;
+
+    let mut added = 0;
+    let mut not_found = Vec::new();
+    for entry in &entries {
+        let results = discover.search_movies(&entry.name)?;
+        let Some(item) = find_match(entry, &results) else {
+            println!("  Not found on Plex: {}", entry.name);
+            not_found.push(entry.name.as_str());
+            continue;
+        };
+
+        if args.dry_run {
+            println!(
+                "Would add: {} ({})",
+                item.title,
+                item.year.unwrap_or_default()
+            );
+        } else {
+            discover.add_to_watchlist(&item.rating_key)?;
+            println!("Added: {} ({})", item.title, item.year.unwrap_or_default());
+        }
+        added += 1;
+    }
+
+    println!(
+        "\n✓ {} of {} film(s) {} the Plex watchlist",
+        added,
+        entries.len(),
+        if args.dry_run {
+            "would be added to"
+        } else {
+            "added to"
+        }
+    );
+    if !not_found.is_empty() {
+        println!("Not found: {}", not_found.join(", "));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,212 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::row::Column;
+
+/// Name of the config file looked up in the current directory
+pub const DEFAULT_CONFIG_FILE: &str = "plex-to-letterboxd.toml";
+
+/// Settings read from the TOML config file
+///
+/// # Example
+///
+/// ```toml
+/// # Columns written to the CSV file, in order
+/// columns = ["Title", "Year", "imdbID", "WatchedDate", "Rating10"]
+///
+/// # Servers whose histories are merged into one export
+/// [[servers]]
+/// url = "http://192.168.1.100:32400"
+/// token = "your-token-here"
+///
+/// # Settings selected with --profile parents
+/// [profiles.parents]
+/// plex_url = "http://10.0.0.5:32400"
+/// plex_token = "their-token-here"
+/// library_name = "Films"
+/// output_csv = "parents.csv"
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Columns written to the CSV file, in order (defaults to Title, imdbID, WatchedDate, Tags)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub columns: Option<Vec<Column>>,
+
+    /// Servers whose watch histories are merged into one export
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub servers: Vec<ServerConfig>,
+
+    /// Profile used when `--profile` is not given (e.g. the one written by `init`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_profile: Option<String>,
+
+    /// Named setups selected with `--profile`, each overriding the settings above
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, ProfileConfig>,
+}
+
+/// Settings of a named profile, for running one installation against several setups
+///
+/// Every setting is optional. The server, token, library, output path and tags are
+/// used in place of the flags of the same name when those are not given on
+/// the command line; columns and servers replace the top-level ones, and so
+/// does a `plex_url`, since the profile then names a server of its own.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileConfig {
+    /// Plex Media Server URL (`--plex-url`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plex_url: Option<String>,
+    /// Plex authentication token (`--plex-token`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plex_token: Option<String>,
+    /// Library exported (`--library-name`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub library_name: Option<String>,
+    /// Output file path, with the same placeholders as `--output-csv`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_csv: Option<String>,
+    /// Letterboxd tags added to every entry (`--tag`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Columns written to the CSV file, in order
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub columns: Option<Vec<Column>>,
+    /// Servers whose watch histories are merged into one export
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub servers: Vec<ServerConfig>,
+}
+
+/// Connection settings for a Plex Media Server
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ServerConfig {
+    /// Plex Media Server URL (e.g., "http://192.168.1.100:32400")
+    pub url: String,
+    /// Plex authentication token
+    pub token: String,
+}
+
+impl FromStr for ServerConfig {
+    type Err = String;
+
+    /// Parses a server from "url,token"
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use plex_to_letterboxd::config::ServerConfig;
+    ///
+    /// let server: ServerConfig = "http://192.168.1.100:32400,abc123".parse().unwrap();
+    /// assert_eq!(server.url, "http://192.168.1.100:32400");
+    /// assert_eq!(server.token, "abc123");
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(',') {
+            Some((url, token)) if !url.is_empty() && !token.is_empty() => Ok(Self {
+                url: url.trim().to_string(),
+                token: token.trim().to_string(),
+            }),
+            _ => Err(format!("Expected 'url,token', got '{}'", s)),
+        }
+    }
+}
+
+impl Config {
+    /// Reads a config file
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use plex_to_letterboxd::config::Config;
+    /// use plex_to_letterboxd::row::Column;
+    ///
+    /// let config = Config::parse(r#"columns = ["Title", "Year", "Rating10"]"#).unwrap();
+    /// assert_eq!(config.columns(), [Column::Title, Column::Year, Column::Rating10]);
+    /// ```
+    pub fn parse(contents: &str) -> Result<Self> {
+        toml::from_str(contents).context("Invalid config file")
+    }
+
+    /// Reads a config file from disk
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+        Self::parse(&contents).with_context(|| format!("Invalid config file: {}", path.display()))
+    }
+
+    /// Returns a profile by name, or an error listing the defined profiles
+    pub fn profile(&self, name: &str) -> Result<&ProfileConfig> {
+        self.profiles.get(name).with_context(|| {
+            if self.profiles.is_empty() {
+                format!(
+                    "Unknown profile '{}': the config file defines no profiles",
+                    name
+                )
+            } else {
+                format!(
+                    "Unknown profile '{}'. Available profiles: {}",
+                    name,
+                    self.profiles.keys().cloned().collect::<Vec<_>>().join(", ")
+                )
+            }
+        })
+    }
+
+    /// Applies the columns and servers of a profile over the top-level settings
+    ///
+    /// The flags a profile supplies are applied to the command line instead.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use plex_to_letterboxd::config::Config;
+    /// use plex_to_letterboxd::row::Column;
+    ///
+    /// let config = Config::parse(
+    ///     r#"
+    ///     columns = ["Title", "imdbID", "WatchedDate"]
+    ///
+    ///     [profiles.home]
+    ///     plex_url = "http://192.168.1.100:32400"
+    ///
+    ///     [profiles.parents]
+    ///     columns = ["Title", "Year"]
+    ///     [[profiles.parents.servers]]
+    ///     url = "http://10.0.0.5:32400"
+    ///     token = "their-token"
+    ///     "#,
+    /// )?;
+    /// let home = config.clone().with_profile("home")?;
+    /// assert_eq!(home.columns(), [Column::Title, Column::ImdbId, Column::WatchedDate]);
+    ///
+    /// let parents = config.clone().with_profile("parents")?;
+    /// assert_eq!(parents.columns(), [Column::Title, Column::Year]);
+    /// assert_eq!(parents.servers[0].url, "http://10.0.0.5:32400");
+    ///
+    /// assert!(config.with_profile("work").is_err());
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn with_profile(mut self, name: &str) -> Result<Self> {
+        let profile = self.profile(name)?.clone();
+        if profile.columns.is_some() {
+            self.columns = profile.columns;
+        }
+        // A profile's own server replaces the shared ones too
+        if !profile.servers.is_empty() || profile.plex_url.is_some() {
+            self.servers = profile.servers;
+        }
+        Ok(self)
+    }
+
+    /// Returns the configured columns, or the default Letterboxd columns
+    pub fn columns(&self) -> Vec<Column> {
+        self.columns.clone().unwrap_or_else(Column::defaults)
+    }
+}
@@ -0,0 +1,28 @@
+use std::fmt::Display;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether status messages are printed to stderr instead of stdout
+static STATUS_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Prints status messages to stderr from now on, keeping stdout for a result
+///
+/// Used when a script reads the output of a run (e.g. `--print-count`), so
+/// progress, skipped items and summaries do not end up mixed with it.
+pub fn status_to_stderr() {
+    STATUS_TO_STDERR.store(true, Ordering::Relaxed);
+}
+
+/// Prints a status message (progress, skipped items, summaries)
+///
+/// Messages go to stdout unless `status_to_stderr` was called. Styled text
+/// is supported: colors are removed when the stream is not a terminal or
+/// `NO_COLOR` is set.
+pub fn status(message: impl Display) {
+    // Like println!, a closed stream is not worth failing the run for
+    if STATUS_TO_STDERR.load(Ordering::Relaxed) {
+        let _ = writeln!(anstream::stderr(), "{}", message);
+    } else {
+        let _ = writeln!(anstream::stdout(), "{}", message);
+    }
+}
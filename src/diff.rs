@@ -0,0 +1,202 @@
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::row::Column;
+
+/// A row of an export, identified by its imdbID and WatchedDate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffRow {
+    /// IMDb identifier of the film
+    pub imdb_id: String,
+    /// Date the film was watched (YYYY-MM-DD), empty for undated rows
+    pub watched_date: String,
+    /// Title of the film, empty when the export has no Title column
+    pub title: String,
+}
+
+/// A column whose value differs between the two exports of a row
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    /// CSV header of the column
+    pub column: String,
+    /// Value in the old export
+    pub old: String,
+    /// Value in the new export
+    pub new: String,
+}
+
+/// Differences between two exports
+///
+/// Rows are matched by (imdbID, WatchedDate); a film watched twice on the
+/// same day is matched in file order. Only columns present in both files
+/// are compared, and every list is sorted by WatchedDate, then imdbID.
+#[derive(Debug, Clone, Default)]
+pub struct ExportDiff {
+    /// Rows only in the new export
+    pub added: Vec<DiffRow>,
+    /// Rows only in the old export
+    pub removed: Vec<DiffRow>,
+    /// Rows in both exports with different values
+    pub changed: Vec<(DiffRow, Vec<FieldChange>)>,
+    /// Columns only in the new export
+    pub columns_added: Vec<String>,
+    /// Columns only in the old export
+    pub columns_removed: Vec<String>,
+}
+
+impl ExportDiff {
+    /// Returns whether the exports have the same rows and values
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// A CSV export read into memory
+struct ExportFile {
+    /// CSV headers, in file order
+    headers: Vec<String>,
+    /// Records by (WatchedDate, imdbID), in file order
+    records: BTreeMap<(String, String), Vec<Vec<String>>>,
+}
+
+impl ExportFile {
+    /// Reads an export, which must have imdbID and WatchedDate columns
+    fn parse(reader: impl Read) -> Result<Self> {
+        let mut rdr = csv::Reader::from_reader(reader);
+        let headers: Vec<String> = rdr.headers()?.iter().map(str::to_string).collect();
+        let position = |column: Column| {
+            headers
+                .iter()
+                .position(|h| h == column.header())
+                .with_context(|| format!("Missing {} column", column.header()))
+        };
+        let id_index = position(Column::ImdbId)?;
+        let date_index = position(Column::WatchedDate)?;
+
+        let mut records: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for record in rdr.records() {
+            let record: Vec<String> = record?.iter().map(str::to_string).collect();
+            let field = |index: usize| record.get(index).cloned().unwrap_or_default();
+            records
+                .entry((field(date_index), field(id_index)))
+                .or_default()
+                .push(record);
+        }
+
+        Ok(Self { headers, records })
+    }
+
+    /// Returns the value of a column of a record, or an empty string
+    fn get<'a>(&self, record: &'a [String], column: &str) -> &'a str {
+        self.headers
+            .iter()
+            .position(|h| h == column)
+            .and_then(|index| record.get(index))
+            .map_or("", String::as_str)
+    }
+
+    /// Returns the identity of a record
+    fn row(&self, (watched_date, imdb_id): &(String, String), record: &[String]) -> DiffRow {
+        DiffRow {
+            imdb_id: imdb_id.clone(),
+            watched_date: watched_date.clone(),
+            title: self.get(record, Column::Title.header()).to_string(),
+        }
+    }
+}
+
+/// Compares two exports, keyed by (imdbID, WatchedDate)
+///
+/// # Example
+///
+/// ```rust
+/// use plex_to_letterboxd::diff::diff_exports;
+///
+/// let old = "Title,imdbID,WatchedDate,Rating\n\
+///            Heat,tt0113277,2024-01-15,4\n\
+///            Ronin,tt0122690,2024-01-16,3\n";
+/// let new = "Title,imdbID,WatchedDate,Rating\n\
+///            Heat,tt0113277,2024-01-15,4.5\n\
+///            Thief,tt0083190,2024-01-17,4\n";
+///
+/// let diff = diff_exports(old.as_bytes(), new.as_bytes())?;
+/// assert_eq!(diff.added[0].title, "Thief");
+/// assert_eq!(diff.removed[0].title, "Ronin");
+/// let (row, changes) = &diff.changed[0];
+/// assert_eq!(row.imdb_id, "tt0113277");
+/// assert_eq!((changes[0].old.as_str(), changes[0].new.as_str()), ("4", "4.5"));
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn diff_exports(old: impl Read, new: impl Read) -> Result<ExportDiff> {
+    let old = ExportFile::parse(old).context("Invalid old export")?;
+    let new = ExportFile::parse(new).context("Invalid new export")?;
+
+    let mut diff = ExportDiff {
+        columns_added: new
+            .headers
+            .iter()
+            .filter(|h| !old.headers.contains(h))
+            .cloned()
+            .collect(),
+        columns_removed: old
+            .headers
+            .iter()
+            .filter(|h| !new.headers.contains(h))
+            .cloned()
+            .collect(),
+        ..Default::default()
+    };
+    let shared: Vec<&String> = old
+        .headers
+        .iter()
+        .filter(|h| new.headers.contains(h))
+        .collect();
+
+    let no_records = Vec::new();
+    let mut keys: Vec<_> = old.records.keys().chain(new.records.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    for key in keys {
+        let old_records = old.records.get(key).unwrap_or(&no_records);
+        let new_records = new.records.get(key).unwrap_or(&no_records);
+
+        for (old_record, new_record) in old_records.iter().zip(new_records) {
+            let changes: Vec<FieldChange> = shared
+                .iter()
+                .filter_map(|column| {
+                    let (old_value, new_value) =
+                        (old.get(old_record, column), new.get(new_record, column));
+                    (old_value != new_value).then(|| FieldChange {
+                        column: column.to_string(),
+                        old: old_value.to_string(),
+                        new: new_value.to_string(),
+                    })
+                })
+                .collect();
+            if !changes.is_empty() {
+                diff.changed.push((new.row(key, new_record), changes));
+            }
+        }
+        for record in old_records.iter().skip(new_records.len()) {
+            diff.removed.push(old.row(key, record));
+        }
+        for record in new_records.iter().skip(old_records.len()) {
+            diff.added.push(new.row(key, record));
+        }
+    }
+
+    Ok(diff)
+}
+
+/// Compares two export files, keyed by (imdbID, WatchedDate)
+pub fn diff_export_files(old: impl AsRef<Path>, new: impl AsRef<Path>) -> Result<ExportDiff> {
+    let open = |path: &Path| {
+        std::fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))
+    };
+    let (old, new) = (old.as_ref(), new.as_ref());
+    diff_exports(open(old)?, open(new)?)
+        .with_context(|| format!("Failed to compare {} with {}", old.display(), new.display()))
+}
@@ -0,0 +1,89 @@
+use plex_client::client::{CircuitOpenError, TokenRejectedError};
+use serde::Serialize;
+
+use crate::redact::Redactor;
+
+/// A fatal error as a JSON object, for `--error-format json`
+///
+/// `kind` is one of "token_rejected", "circuit_open", "http", "timeout",
+/// "connection", "parse", "io" or "other", from the most specific cause in
+/// the error's chain. `endpoint` is the path of the request that failed and
+/// `http_status` its status, when the error came from a request.
+///
+/// # Example
+///
+/// ```rust
+/// use anyhow::Context;
+/// use plex_client::client::TokenRejectedError;
+/// use plex_to_letterboxd::error_report::ErrorReport;
+/// use plex_to_letterboxd::redact::Redactor;
+///
+/// let error = Err::<(), _>(TokenRejectedError { endpoint: "/library/sections".to_string() })
+///     .context("Failed to list libraries")
+///     .unwrap_err();
+/// let report = ErrorReport::new(&error, &Redactor::default());
+/// assert_eq!(report.kind, "token_rejected");
+/// assert_eq!(report.endpoint.as_deref(), Some("/library/sections"));
+/// assert_eq!(report.http_status, Some(401));
+/// assert!(report.message.starts_with("Failed to list libraries: Plex token rejected"));
+/// ```
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorReport {
+    /// Category of the error
+    pub kind: &'static str,
+    /// The error and its causes, on one line and with credentials redacted
+    pub message: String,
+    /// Path of the request that failed (e.g. "/status/sessions/history/all")
+    pub endpoint: Option<String>,
+    /// HTTP status of the failed request
+    pub http_status: Option<u16>,
+}
+
+impl ErrorReport {
+    /// Describes an error, redacting credentials from its message and endpoint
+    pub fn new(error: &anyhow::Error, redactor: &Redactor) -> Self {
+        let mut report = Self {
+            kind: "other",
+            message: redactor.redact(&format!("{:#}", error)),
+            endpoint: None,
+            http_status: None,
+        };
+
+        // Later causes are more specific, except for the I/O and JSON errors
+        // underneath a failed request
+        for cause in error.chain() {
+            if let Some(error) = cause.downcast_ref::<TokenRejectedError>() {
+                report.kind = "token_rejected";
+                report.endpoint = Some(redactor.redact(&error.endpoint));
+                report.http_status = Some(401);
+            } else if cause.is::<CircuitOpenError>() {
+                report.kind = "circuit_open";
+            } else if let Some(error) = cause.downcast_ref::<reqwest::Error>() {
+                report.kind = if error.is_timeout() {
+                    "timeout"
+                } else if error.is_connect() {
+                    "connection"
+                } else if error.is_decode() {
+                    "parse"
+                } else if error.is_status() {
+                    "http"
+                } else {
+                    "other"
+                };
+                if let Some(url) = error.url() {
+                    // The path only, since the query can carry credentials
+                    report.endpoint = Some(redactor.redact(url.path()));
+                }
+                if let Some(status) = error.status() {
+                    report.http_status = Some(status.as_u16());
+                }
+            } else if report.kind == "other" && cause.is::<serde_json::Error>() {
+                report.kind = "parse";
+            } else if report.kind == "other" && cause.is::<std::io::Error>() {
+                // Unless it is the cause of a failed request, e.g. a refused connection
+                report.kind = "io";
+            }
+        }
+        report
+    }
+}
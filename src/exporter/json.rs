@@ -0,0 +1,51 @@
+use std::io::Write;
+
+use anyhow::Result;
+
+use super::Exporter;
+use crate::output::AtomicFile;
+use crate::row::ExportRow;
+
+/// Writes rows to a file as a JSON array
+///
+/// Rows are streamed to the file as they arrive rather than collected first.
+pub struct JsonExporter {
+    /// Path of the output file
+    path: String,
+    /// Writer of the output file, moved into place when finished
+    writer: AtomicFile,
+    /// Number of rows written so far
+    rows: usize,
+}
+
+impl JsonExporter {
+    /// Starts the output file
+    pub fn create(path: &str) -> Result<Self> {
+        let mut writer = AtomicFile::create(path)?;
+        writer.write_all(b"[")?;
+
+        Ok(Self {
+            path: path.to_string(),
+            writer,
+            rows: 0,
+        })
+    }
+}
+
+impl Exporter for JsonExporter {
+    fn write_row(&mut self, row: &ExportRow) -> Result<()> {
+        if self.rows > 0 {
+            self.writer.write_all(b",")?;
+        }
+        self.writer.write_all(b"\n  ")?;
+        serde_json::to_writer(&mut self.writer, row)?;
+        self.rows += 1;
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<String> {
+        self.writer.write_all(b"\n]\n")?;
+        self.writer.commit()?;
+        Ok(format!("JSON file successfully generated: {}", self.path))
+    }
+}
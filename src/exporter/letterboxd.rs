@@ -0,0 +1,52 @@
+use anyhow::{Context, Result};
+use csv::Writer;
+
+use super::Exporter;
+use crate::output::AtomicFile;
+use crate::row::{Column, ExportRow};
+
+/// Writes rows to a CSV file in Letterboxd's import format
+pub struct LetterboxdCsvExporter {
+    /// Path of the output file
+    path: String,
+    /// Columns written for every row
+    columns: Vec<Column>,
+    /// CSV writer for the output file
+    writer: Writer<AtomicFile>,
+}
+
+impl LetterboxdCsvExporter {
+    /// Starts the output file and writes the header
+    pub fn create(path: &str, columns: Vec<Column>) -> Result<Self> {
+        let mut writer = Writer::from_writer(AtomicFile::create(path)?);
+        writer.write_record(columns.iter().map(Column::header))?;
+
+        Ok(Self {
+            path: path.to_string(),
+            columns,
+            writer,
+        })
+    }
+}
+
+impl Exporter for LetterboxdCsvExporter {
+    fn write_row(&mut self, row: &ExportRow) -> Result<()> {
+        self.writer
+            .write_record(Column::record(&self.columns, row))?;
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> Result<String> {
+        // Flush the CSV buffer and move the file into place
+        self.writer
+            .into_inner()
+            .map_err(|e| e.into_error())
+            .with_context(|| format!("Failed to write output file: {}", self.path))?
+            .commit()?;
+        Ok(format!(
+            "CSV file successfully generated: {}\n\
+             Upload your watch history at: https://letterboxd.com/import/",
+            self.path
+        ))
+    }
+}
@@ -0,0 +1,73 @@
+use anyhow::Result;
+
+use crate::row::ExportRow;
+
+/// JSON exporter
+pub mod json;
+/// Letterboxd diary CSV exporter
+pub mod letterboxd;
+/// Simkl API exporter
+pub mod simkl_api;
+/// Exporter splitting rows into one file per year
+pub mod split;
+/// Handlebars template exporter
+pub mod template;
+/// Trakt CSV exporter
+pub mod trakt;
+/// Trakt API exporter
+pub mod trakt_api;
+/// Exporter uploading its file to S3 or an HTTP/WebDAV server
+pub mod upload;
+
+pub use json::JsonExporter;
+pub use letterboxd::LetterboxdCsvExporter;
+pub use simkl_api::SimklApiExporter;
+pub use split::YearSplitExporter;
+pub use template::TemplateExporter;
+pub use trakt::TraktCsvExporter;
+pub use trakt_api::TraktApiExporter;
+pub use upload::UploadExporter;
+
+/// A destination for exported rows (a CSV file, a JSON file, an API, ...)
+///
+/// Exporters receive every row that made it through the source and the
+/// filters, then are finished once, which is where buffered output is flushed
+/// and output files are moved into place. An exporter dropped without being
+/// finished leaves no output file behind.
+///
+/// # Example
+///
+/// ```rust
+/// use anyhow::Result;
+/// use plex_to_letterboxd::exporter::Exporter;
+/// use plex_to_letterboxd::row::ExportRow;
+///
+/// /// Counts rows instead of writing them anywhere
+/// struct CountingExporter(usize);
+///
+/// impl Exporter for CountingExporter {
+///     fn write_row(&mut self, _row: &ExportRow) -> Result<()> {
+///         self.0 += 1;
+///         Ok(())
+///     }
+///
+///     fn finish(self: Box<Self>) -> Result<String> {
+///         Ok(format!("{} rows", self.0))
+///     }
+/// }
+/// ```
+pub trait Exporter {
+    /// Exports a single row
+    fn write_row(&mut self, row: &ExportRow) -> Result<()>;
+
+    /// Returns the files written so far, when they depend on the rows
+    ///
+    /// Exporters writing to a single known path return nothing; the caller
+    /// already knows where their output goes.
+    fn outputs(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Finishes the export, returning a message describing where the rows went
+    fn finish(self: Box<Self>) -> Result<String>;
+}
@@ -0,0 +1,76 @@
+use anyhow::Result;
+
+use super::Exporter;
+use crate::row::ExportRow;
+use crate::simkl::{SimklClient, SimklHistoryMovie};
+
+/// Number of viewings sent to Simkl per request
+const BATCH_SIZE: usize = 100;
+
+/// Adds rows directly to the user's Simkl watched history
+pub struct SimklApiExporter {
+    /// Authenticated Simkl client
+    client: SimklClient,
+    /// Viewings not yet sent
+    pending: Vec<SimklHistoryMovie>,
+    /// Number of viewings Simkl added
+    added: u64,
+    /// Whether viewings are sent with their full watch time instead of midnight
+    with_time: bool,
+    /// Number of movies Simkl could not match
+    not_found: usize,
+}
+
+impl SimklApiExporter {
+    /// Creates an exporter sending viewings through an authenticated client
+    pub fn new(client: SimklClient) -> Self {
+        Self {
+            client,
+            pending: Vec::with_capacity(BATCH_SIZE),
+            added: 0,
+            not_found: 0,
+            with_time: false,
+        }
+    }
+
+    /// Sends each viewing with its full watch time, when the source records it
+    pub fn with_time(mut self, with_time: bool) -> Self {
+        self.with_time = with_time;
+        self
+    }
+
+    /// Sends the pending viewings
+    fn flush(&mut self) -> Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        let response = self.client.add_history(&self.pending)?;
+        self.added += response.added.movies;
+        self.not_found += response.not_found.movies.len();
+        self.pending.clear();
+        Ok(())
+    }
+}
+
+impl Exporter for SimklApiExporter {
+    fn write_row(&mut self, row: &ExportRow) -> Result<()> {
+        let mut movie = SimklHistoryMovie::from(row);
+        if let Some(watched_at) = row.watched_at().filter(|_| self.with_time) {
+            movie.watched_at = Some(watched_at);
+        }
+        self.pending.push(movie);
+        if self.pending.len() >= BATCH_SIZE {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<String> {
+        self.flush()?;
+        Ok(format!(
+            "Added {} viewing(s) to your Simkl history ({} not found on Simkl)",
+            self.added, self.not_found
+        ))
+    }
+}
@@ -0,0 +1,114 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::Result;
+
+use super::Exporter;
+use crate::row::ExportRow;
+
+/// Creates the exporter writing one of the files of a split export
+pub type ExporterFactory = Box<dyn FnMut(&str) -> Result<Box<dyn Exporter>>>;
+
+/// Writes rows to one file per calendar year of their watch dates
+///
+/// The year is inserted before the extension of the output path, so
+/// "history.csv" becomes "history_2023.csv", "history_2024.csv", and so on;
+/// rows without a watch date go to "history_undated.csv". Each file is
+/// created by the factory when its first row arrives, so only years with
+/// viewings get a file.
+///
+/// # Example
+///
+/// ```rust
+/// use plex_to_letterboxd::exporter::{Exporter, JsonExporter, YearSplitExporter};
+/// use plex_to_letterboxd::row::ExportRow;
+///
+/// let path = std::env::temp_dir().join("split-example.json");
+/// let mut exporter = YearSplitExporter::new(
+///     path.to_str().unwrap(),
+///     Box::new(|path| Ok(Box::new(JsonExporter::create(path)?))),
+/// );
+/// for date in ["2023-12-31", "2024-01-15", "2024-02-10"] {
+///     let row = ExportRow {
+///         title: "Heat".to_string(),
+///         watched_date: date.to_string(),
+///         ..Default::default()
+///     };
+///     exporter.write_row(&row)?;
+/// }
+/// let outputs = exporter.outputs();
+/// assert!(outputs[0].ends_with("split-example_2023.json"));
+/// assert!(outputs[1].ends_with("split-example_2024.json"));
+///
+/// Box::new(exporter).finish()?;
+/// # for output in outputs {
+/// #     std::fs::remove_file(output)?;
+/// # }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct YearSplitExporter {
+    /// Output path the year is inserted into
+    path: String,
+    /// Creates the exporter of each year's file
+    factory: ExporterFactory,
+    /// Exporter and row count of each year's file, by path
+    files: BTreeMap<String, (Box<dyn Exporter>, usize)>,
+}
+
+impl YearSplitExporter {
+    /// Splits the output at `path` by year, creating each file with `factory`
+    pub fn new(path: &str, factory: ExporterFactory) -> Self {
+        Self {
+            path: path.to_string(),
+            factory,
+            files: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the path of the file for a watch date ("YYYY-MM-DD", or empty)
+    fn year_path(&self, watched_date: &str) -> String {
+        let year = match watched_date.get(..4) {
+            Some(year) if year.bytes().all(|b| b.is_ascii_digit()) => year,
+            _ => "undated",
+        };
+        let path = Path::new(&self.path);
+        let stem = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy())
+            .unwrap_or_default();
+        let file_name = match path.extension() {
+            Some(extension) => format!("{}_{}.{}", stem, year, extension.to_string_lossy()),
+            None => format!("{}_{}", stem, year),
+        };
+        path.with_file_name(file_name)
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+impl Exporter for YearSplitExporter {
+    fn write_row(&mut self, row: &ExportRow) -> Result<()> {
+        let path = self.year_path(&row.watched_date);
+        if !self.files.contains_key(&path) {
+            let exporter = (self.factory)(&path)?;
+            self.files.insert(path.clone(), (exporter, 0));
+        }
+        let (exporter, rows) = self.files.get_mut(&path).expect("file was just created");
+        exporter.write_row(row)?;
+        *rows += 1;
+        Ok(())
+    }
+
+    fn outputs(&self) -> Vec<String> {
+        self.files.keys().cloned().collect()
+    }
+
+    fn finish(self: Box<Self>) -> Result<String> {
+        let mut message = "Split the export by watch year:".to_string();
+        for (path, (exporter, rows)) in self.files {
+            exporter.finish()?;
+            message.push_str(&format!("\n  {} ({} rows)", path, rows));
+        }
+        Ok(message)
+    }
+}
@@ -0,0 +1,44 @@
+use std::io::Write;
+
+use anyhow::Result;
+
+use super::Exporter;
+use crate::output::AtomicFile;
+use crate::row::ExportRow;
+use crate::template::RowTemplate;
+
+/// Writes every row rendered through a Handlebars template
+pub struct TemplateExporter {
+    /// Path of the output file
+    path: String,
+    /// Template rendered for every row
+    template: RowTemplate,
+    /// Writer of the output file, moved into place when finished
+    writer: AtomicFile,
+}
+
+impl TemplateExporter {
+    /// Starts the output file
+    pub fn create(path: &str, template: RowTemplate) -> Result<Self> {
+        let writer = AtomicFile::create(path)?;
+
+        Ok(Self {
+            path: path.to_string(),
+            template,
+            writer,
+        })
+    }
+}
+
+impl Exporter for TemplateExporter {
+    fn write_row(&mut self, row: &ExportRow) -> Result<()> {
+        self.writer
+            .write_all(self.template.render(row)?.as_bytes())?;
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> Result<String> {
+        self.writer.commit()?;
+        Ok(format!("File successfully generated: {}", self.path))
+    }
+}
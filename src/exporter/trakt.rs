@@ -0,0 +1,79 @@
+use anyhow::{Context, Result};
+use csv::Writer;
+
+use super::Exporter;
+use crate::output::AtomicFile;
+use crate::row::ExportRow;
+
+/// Header of Trakt's CSV import format
+const HEADER: [&str; 6] = ["type", "title", "year", "imdb_id", "watched_at", "rating"];
+
+/// Writes rows to a CSV file in Trakt's import format
+pub struct TraktCsvExporter {
+    /// Path of the output file
+    path: String,
+    /// CSV writer for the output file
+    writer: Writer<AtomicFile>,
+    /// Whether watched_at holds the full watch time instead of the date
+    with_time: bool,
+}
+
+impl TraktCsvExporter {
+    /// Starts the output file and writes the header
+    pub fn create(path: &str) -> Result<Self> {
+        let mut writer = Writer::from_writer(AtomicFile::create(path)?);
+        writer.write_record(HEADER)?;
+
+        Ok(Self {
+            path: path.to_string(),
+            writer,
+            with_time: false,
+        })
+    }
+
+    /// Writes the full watch time (e.g. "2024-01-15T20:31:07Z") in the watched_at column
+    pub fn with_time(mut self, with_time: bool) -> Self {
+        self.with_time = with_time;
+        self
+    }
+}
+
+impl Exporter for TraktCsvExporter {
+    fn write_row(&mut self, row: &ExportRow) -> Result<()> {
+        let year = row.year.map(|y| y.to_string()).unwrap_or_default();
+        // Trakt ratings are whole numbers from 1 to 10
+        let rating = row
+            .rating
+            .filter(|r| *r > 0.0)
+            .map(|r| (r.round().max(1.0) as u32).to_string())
+            .unwrap_or_default();
+        let watched_at = match row.watched_at() {
+            Some(watched_at) if self.with_time => watched_at,
+            _ => row.watched_date.clone(),
+        };
+
+        self.writer.write_record([
+            "movie",
+            &row.title,
+            &year,
+            &row.imdb_id,
+            &watched_at,
+            &rating,
+        ])?;
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> Result<String> {
+        // Flush the CSV buffer and move the file into place
+        self.writer
+            .into_inner()
+            .map_err(|e| e.into_error())
+            .with_context(|| format!("Failed to write output file: {}", self.path))?
+            .commit()?;
+        Ok(format!(
+            "Trakt CSV file successfully generated: {}\n\
+             Import it at: https://trakt.tv/settings/data",
+            self.path
+        ))
+    }
+}
@@ -0,0 +1,76 @@
+use anyhow::Result;
+
+use super::Exporter;
+use crate::row::ExportRow;
+use crate::trakt::{TraktClient, TraktHistoryMovie};
+
+/// Number of viewings sent to Trakt per request
+const BATCH_SIZE: usize = 100;
+
+/// Adds rows directly to the user's Trakt watched history
+pub struct TraktApiExporter {
+    /// Authenticated Trakt client
+    client: TraktClient,
+    /// Viewings not yet sent
+    pending: Vec<TraktHistoryMovie>,
+    /// Number of viewings Trakt added
+    added: u64,
+    /// Whether viewings are sent with their full watch time instead of midnight
+    with_time: bool,
+    /// Number of movies Trakt could not match
+    not_found: usize,
+}
+
+impl TraktApiExporter {
+    /// Creates an exporter sending viewings through an authenticated client
+    pub fn new(client: TraktClient) -> Self {
+        Self {
+            client,
+            pending: Vec::with_capacity(BATCH_SIZE),
+            added: 0,
+            not_found: 0,
+            with_time: false,
+        }
+    }
+
+    /// Sends each viewing with its full watch time, when the source records it
+    pub fn with_time(mut self, with_time: bool) -> Self {
+        self.with_time = with_time;
+        self
+    }
+
+    /// Sends the pending viewings
+    fn flush(&mut self) -> Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        let response = self.client.add_history(&self.pending)?;
+        self.added += response.added.movies;
+        self.not_found += response.not_found.movies.len();
+        self.pending.clear();
+        Ok(())
+    }
+}
+
+impl Exporter for TraktApiExporter {
+    fn write_row(&mut self, row: &ExportRow) -> Result<()> {
+        let mut movie = TraktHistoryMovie::from(row);
+        if let Some(watched_at) = row.watched_at().filter(|_| self.with_time) {
+            movie.watched_at = watched_at;
+        }
+        self.pending.push(movie);
+        if self.pending.len() >= BATCH_SIZE {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<String> {
+        self.flush()?;
+        Ok(format!(
+            "Added {} viewing(s) to your Trakt history ({} not found on Trakt)",
+            self.added, self.not_found
+        ))
+    }
+}
@@ -0,0 +1,68 @@
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+
+use super::Exporter;
+use crate::remote::RemoteOutput;
+use crate::row::ExportRow;
+
+/// Writes rows to a local staging file with another exporter, then uploads it
+///
+/// The file is only uploaded once the inner exporter has finished it, so a
+/// failed run never replaces the remote file with a partial one. The staging
+/// file is removed afterwards, whether the upload succeeded or not.
+pub struct UploadExporter {
+    /// Exporter writing the staging file
+    inner: Box<dyn Exporter>,
+    /// Local file written by the inner exporter
+    staging_path: PathBuf,
+    /// Where the finished file is uploaded
+    destination: RemoteOutput,
+}
+
+impl UploadExporter {
+    /// Returns the local file a remote output is written to before it is uploaded
+    pub fn staging_path(destination: &RemoteOutput) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "plex-to-letterboxd.{}.{}",
+            std::process::id(),
+            destination.file_name()
+        ))
+    }
+
+    /// Uploads the file written by `inner` at `staging_path` once it is finished
+    ///
+    /// Fails right away when the credentials of the destination are missing.
+    pub fn new(
+        inner: Box<dyn Exporter>,
+        staging_path: PathBuf,
+        destination: RemoteOutput,
+    ) -> Result<Self> {
+        destination.check_credentials()?;
+        Ok(Self {
+            inner,
+            staging_path,
+            destination,
+        })
+    }
+}
+
+impl Exporter for UploadExporter {
+    fn write_row(&mut self, row: &ExportRow) -> Result<()> {
+        self.inner.write_row(row)
+    }
+
+    fn finish(self: Box<Self>) -> Result<String> {
+        self.inner.finish()?;
+        let body = fs::read(&self.staging_path).with_context(|| {
+            format!(
+                "Failed to read staged output: {}",
+                self.staging_path.display()
+            )
+        });
+        let _ = fs::remove_file(&self.staging_path);
+        self.destination.upload(body?)?;
+        Ok(format!("Uploaded the export to {}", self.destination))
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use regex::Regex;
+
+use crate::check::is_imdb_id;
+
+/// Name of the ignore file looked up in the current directory
+pub const DEFAULT_IGNORE_FILE: &str = "plex-to-letterboxd.ignore";
+
+/// A list of films read from a file, such as the ignore file
+///
+/// The file has one entry per line: an IMDb ID ("tt0113277"), a Plex rating
+/// key (digits only) or a title glob where `*` matches any text and `?` any
+/// single character, compared case-insensitively. Blank lines and lines
+/// starting with `#` are skipped.
+///
+/// # Example
+///
+/// ```rust
+/// use plex_to_letterboxd::film_list::FilmList;
+///
+/// let list = FilmList::parse(
+///     "# Watched with the kids\n\
+///      tt0317219\n\
+///      Paw Patrol*\n",
+/// )
+/// .unwrap();
+///
+/// assert_eq!(list.matching_entry(None, Some("tt0317219"), "Cars"), Some("tt0317219"));
+/// assert_eq!(
+///     list.matching_entry(Some("1234"), None, "PAW Patrol: The Movie"),
+///     Some("Paw Patrol*")
+/// );
+/// assert_eq!(list.matching_entry(None, Some("tt0113277"), "Heat"), None);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FilmList {
+    /// IMDb IDs of the list
+    imdb_ids: HashSet<String>,
+    /// Plex rating keys of the list
+    rating_keys: HashSet<String>,
+    /// Title globs, with the regexes they were translated to
+    titles: Vec<(String, Regex)>,
+}
+
+impl FilmList {
+    /// Reads the entries of a list file
+    pub fn parse(contents: &str) -> Result<Self> {
+        let mut list = Self::default();
+
+        for line in contents.lines() {
+            let entry = line.trim();
+            if entry.is_empty() || entry.starts_with('#') {
+                continue;
+            }
+
+            if is_imdb_id(entry) {
+                list.imdb_ids.insert(entry.to_string());
+            } else if entry.chars().all(|c| c.is_ascii_digit()) {
+                list.rating_keys.insert(entry.to_string());
+            } else {
+                list.titles.push((entry.to_string(), glob_regex(entry)?));
+            }
+        }
+
+        Ok(list)
+    }
+
+    /// Reads a list file from disk
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read film list: {}", path.display()))?;
+        Self::parse(&contents).with_context(|| format!("Invalid film list: {}", path.display()))
+    }
+
+    /// Returns the entry of the list matching a film, or `None` when it is not listed
+    ///
+    /// The rating key and IMDb ID are optional, so that films can be looked
+    /// up before their metadata is known.
+    pub fn matching_entry(
+        &self,
+        rating_key: Option<&str>,
+        imdb_id: Option<&str>,
+        title: &str,
+    ) -> Option<&str> {
+        if let Some(rating_key) = rating_key.and_then(|key| self.rating_keys.get(key)) {
+            return Some(rating_key);
+        }
+        if let Some(imdb_id) = imdb_id.and_then(|id| self.imdb_ids.get(id)) {
+            return Some(imdb_id);
+        }
+        self.titles
+            .iter()
+            .find(|(_, regex)| regex.is_match(title))
+            .map(|(glob, _)| glob.as_str())
+    }
+}
+
+/// Translates a title glob into a case-insensitive regex matching whole titles
+fn glob_regex(glob: &str) -> Result<Regex> {
+    let pattern = regex::escape(glob).replace("\\*", ".*").replace("\\?", ".");
+    Regex::new(&format!("(?i)^{}$", pattern))
+        .with_context(|| format!("Invalid title pattern: {}", glob))
+}
@@ -0,0 +1,192 @@
+use plex_client::media_item::{PlexMediaItemMetadata, PlexMediaItemTag};
+use plex_client::watch_history::PlexWatchHistoryItem;
+use regex::Regex;
+
+/// Criteria restricting which watched items are exported
+///
+/// An empty filter (the default) lets every item through.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    /// Only export items carrying at least one of these labels (case-insensitive)
+    pub labels: Vec<String>,
+    /// Only export items in at least one of these collections (case-insensitive)
+    pub collections: Vec<String>,
+    /// Only export items with at least one of these genres (case-insensitive)
+    pub genres: Vec<String>,
+    /// Never export items with any of these genres (case-insensitive)
+    pub excluded_genres: Vec<String>,
+    /// Only export items released in or after this year
+    pub released_after: Option<u32>,
+    /// Only export items released in or before this year
+    pub released_before: Option<u32>,
+    /// Only export items running at least this many minutes (items without a duration are kept)
+    pub min_runtime_minutes: Option<u64>,
+    /// Only export items whose history title matches this pattern
+    pub include_title: Option<Regex>,
+    /// Never export items whose history title matches this pattern
+    pub exclude_title: Option<Regex>,
+    /// Only export viewings on one of these devices, by name (case-insensitive) or ID
+    pub devices: Vec<String>,
+    /// Never export viewings on any of these devices, by name (case-insensitive) or ID
+    pub excluded_devices: Vec<String>,
+    /// Export trailers, behind-the-scenes and other extras instead of skipping them
+    pub include_extras: bool,
+}
+
+impl HistoryFilter {
+    /// Returns why an item is excluded based on its title alone, or `None` when it may be exported
+    ///
+    /// Title filters only need the watch history entry, so they are checked
+    /// before fetching metadata, which skips the metadata request entirely for
+    /// excluded items.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use plex_to_letterboxd::filter::HistoryFilter;
+    /// use regex::Regex;
+    ///
+    /// let filter = HistoryFilter {
+    ///     exclude_title: Some(Regex::new("(?i)trailer").unwrap()),
+    ///     ..Default::default()
+    /// };
+    /// assert!(filter.title_exclusion_reason("Heat (Trailer)").is_some());
+    /// assert!(filter.title_exclusion_reason("Heat").is_none());
+    /// ```
+    pub fn title_exclusion_reason(&self, title: &str) -> Option<String> {
+        if let Some(include) = &self.include_title {
+            if !include.is_match(title) {
+                return Some(format!("title does not match {}", include));
+            }
+        }
+
+        if let Some(exclude) = &self.exclude_title {
+            if exclude.is_match(title) {
+                return Some(format!("title matches {}", exclude));
+            }
+        }
+
+        None
+    }
+
+    /// Returns why a viewing is excluded based on the device it was played on, or `None` when it may be exported
+    ///
+    /// With `devices` set, viewings on an unknown device are excluded; with
+    /// `excluded_devices` set, they are kept.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use plex_client::watch_history::PlexWatchHistoryItem;
+    /// use plex_to_letterboxd::filter::HistoryFilter;
+    ///
+    /// let filter = HistoryFilter {
+    ///     excluded_devices: vec!["living room tv".to_string()],
+    ///     ..Default::default()
+    /// };
+    /// let json = r#"{"title": "Heat", "librarySectionID": "1", "deviceID": 7, "device": "Living Room TV"}"#;
+    /// let item: PlexWatchHistoryItem = serde_json::from_str(json).unwrap();
+    /// assert_eq!(
+    ///     filter.device_exclusion_reason(&item).as_deref(),
+    ///     Some("played on Living Room TV")
+    /// );
+    /// ```
+    pub fn device_exclusion_reason(&self, item: &PlexWatchHistoryItem) -> Option<String> {
+        let played_on = |wanted: &[String]| {
+            wanted.iter().any(|wanted| {
+                item.device
+                    .as_deref()
+                    .is_some_and(|device| device.eq_ignore_ascii_case(wanted))
+                    || item.device_id.is_some_and(|id| id.to_string() == *wanted)
+            })
+        };
+        let device = || match (&item.device, item.device_id) {
+            (Some(device), _) => device.clone(),
+            (None, Some(id)) => format!("device {}", id),
+            (None, None) => "an unknown device".to_string(),
+        };
+
+        if !self.devices.is_empty() && !played_on(&self.devices) {
+            return Some(format!("played on {}", device()));
+        }
+        if played_on(&self.excluded_devices) {
+            return Some(format!("played on {}", device()));
+        }
+        None
+    }
+
+    /// Returns why an item is excluded by the filter, or `None` when it should be exported
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use plex_to_letterboxd::filter::HistoryFilter;
+    ///
+    /// let filter = HistoryFilter {
+    ///     labels: vec!["Criterion".to_string()],
+    ///     ..Default::default()
+    /// };
+    /// let metadata = serde_json::from_str(r#"{"Label": [{"tag": "criterion"}]}"#).unwrap();
+    /// assert_eq!(filter.exclusion_reason(&metadata), None);
+    /// ```
+    pub fn exclusion_reason(&self, metadata: &PlexMediaItemMetadata) -> Option<String> {
+        if !self.include_extras && is_extra(metadata) {
+            let kind = metadata.subtype.as_deref().unwrap_or(&metadata.item_type);
+            return Some(format!("extra ({})", kind));
+        }
+
+        if !self.labels.is_empty() && !has_any_tag(&metadata.label, &self.labels) {
+            return Some(format!("not labelled {}", self.labels.join(" or ")));
+        }
+
+        if !self.collections.is_empty() && !has_any_tag(&metadata.collection, &self.collections) {
+            return Some(format!(
+                "not in collection {}",
+                self.collections.join(" or ")
+            ));
+        }
+
+        if !self.genres.is_empty() && !has_any_tag(&metadata.genre, &self.genres) {
+            return Some(format!("not in genre {}", self.genres.join(" or ")));
+        }
+
+        if has_any_tag(&metadata.genre, &self.excluded_genres) {
+            return Some(format!(
+                "in excluded genre {}",
+                self.excluded_genres.join(" or ")
+            ));
+        }
+
+        if self.released_after.is_some() || self.released_before.is_some() {
+            let Some(year) = metadata.year else {
+                return Some("unknown release year".to_string());
+            };
+            if self.released_after.is_some_and(|after| year < after) {
+                return Some(format!("released in {}", year));
+            }
+            if self.released_before.is_some_and(|before| year > before) {
+                return Some(format!("released in {}", year));
+            }
+        }
+
+        if let (Some(min_runtime), Some(duration)) = (self.min_runtime_minutes, metadata.duration) {
+            let runtime = duration / 60_000;
+            if runtime < min_runtime {
+                return Some(format!("runtime of {} minutes", runtime));
+            }
+        }
+
+        None
+    }
+}
+
+/// Returns whether the item is a trailer, behind-the-scenes clip or other extra
+fn is_extra(metadata: &PlexMediaItemMetadata) -> bool {
+    metadata.item_type == "clip" || metadata.subtype.is_some() || metadata.extra_type.is_some()
+}
+
+/// Returns whether any of the tags matches any of the wanted values (case-insensitive)
+fn has_any_tag(tags: &[PlexMediaItemTag], wanted: &[String]) -> bool {
+    tags.iter()
+        .any(|tag| wanted.iter().any(|w| tag.tag.eq_ignore_ascii_case(w)))
+}
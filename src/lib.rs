@@ -1,11 +1,59 @@
-/// Plex API client module
-pub mod client;
-/// Utility deserializers for Plex API responses
-pub mod deserializers;
-
-/// Library sections types and structures
-pub mod library;
-/// Media item types and structures
-pub mod media_item;
-/// Watch history types and structures
-pub mod watch_history;
+/// SQLite archive of every fetched viewing
+#[cfg(feature = "sqlite")]
+pub mod archive;
+/// Metadata cache persisted between runs
+pub mod cache;
+/// Validation of rows before they are written
+pub mod check;
+/// Config file settings
+pub mod config;
+/// Status messages printed during a run
+pub mod console;
+/// Comparison of two exports
+pub mod diff;
+/// Machine-readable descriptions of fatal errors
+pub mod error_report;
+/// Destinations for exported rows
+pub mod exporter;
+/// Lists of films read from the ignore and only files
+pub mod film_list;
+/// Filters restricting which watched items are exported
+pub mod filter;
+/// Verbose logs of requests, correlated by run
+pub mod logging;
+/// Run summaries sent to notification URLs
+pub mod notify;
+/// Output files written atomically
+pub mod output;
+/// Watch history read from a Plex data export ZIP
+pub mod plex_export;
+/// Removal of credentials from output
+pub mod redact;
+/// Output files uploaded to S3 or an HTTP/WebDAV server
+pub mod remote;
+/// Counts and summaries of exports
+pub mod report;
+/// Reviews imported from a notes file
+pub mod review;
+/// Rows of the Letterboxd CSV file
+pub mod row;
+/// Cron expressions for daemon scheduling
+pub mod schedule;
+/// Simkl API client
+pub mod simkl;
+/// Analytics of a watch history
+pub mod stats;
+/// Colors of terminal output
+pub mod style;
+/// Handlebars templates for custom output formats
+pub mod template;
+/// Title normalization helpers
+pub mod title;
+/// Trakt API client
+pub mod trakt;
+/// External command hook transforming exported rows
+pub mod transform;
+/// Terminal UI for reviewing rows before they are written
+pub mod tui;
+/// Letterboxd watchlist exports
+pub mod watchlist;
@@ -0,0 +1,62 @@
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+/// Prints debug events of this program and the Plex client to stderr
+///
+/// Every request to a server is logged with its endpoint, page offset,
+/// status and duration, inside the span of the run it belongs to. Events of
+/// other crates (e.g. the HTTP stack) are left out.
+pub fn init_verbose() {
+    let targets = Targets::new()
+        .with_target("plex_client", LevelFilter::DEBUG)
+        .with_target("plex_to_letterboxd", LevelFilter::DEBUG);
+    let _ = tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .with(targets)
+        .try_init();
+}
+
+/// Prints the redirects followed by the Plex client to stderr, for `--log-redirects`
+///
+/// Only needed without `--verbose`, whose subscriber prints them already.
+pub fn init_redirects() {
+    let targets = Targets::new().with_target("plex_client", LevelFilter::INFO);
+    let _ = tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .with(targets)
+        .try_init();
+}
+
+/// Prints the warnings of the Plex client to stderr
+///
+/// Used when neither `--verbose` nor `--log-redirects` installs a subscriber,
+/// so that fallbacks the client takes on its own are not silent.
+pub fn init_warnings() {
+    let targets = Targets::new().with_target("plex_client", LevelFilter::WARN);
+    let _ = tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .with(targets)
+        .try_init();
+}
+
+/// Creates the span of a run, with a new ID correlating its log lines
+///
+/// Spans are entered per thread, so threads working for the run must enter
+/// it too (e.g. with `tracing::Span::current()` captured before spawning).
+pub fn run_span() -> tracing::Span {
+    tracing::debug_span!("run", id = %new_run_id())
+}
+
+/// Returns a short ID, unlikely to repeat across runs and processes
+fn new_run_id() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .subsec_nanos();
+    format!("{:08x}", nanos ^ process::id().rotate_left(16))
+}
@@ -1,133 +1,242 @@
-use anyhow::{Context, Result};
-use clap::Parser;
-use csv::Writer;
-use plex_to_letterboxd::client::PlexClient;
+use std::process::ExitCode;
+
+use anyhow::Result;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
+use plex_to_letterboxd::error_report::ErrorReport;
+use plex_to_letterboxd::logging;
+use plex_to_letterboxd::redact::Redactor;
+use plex_to_letterboxd::style::{paint, ERROR};
+
+mod commands;
+
+use commands::daemon::DaemonArgs;
+use commands::diff::DiffArgs;
+use commands::export::ExportArgs;
+use commands::export_items::ExportItemsArgs;
+use commands::init::InitArgs;
+use commands::login::LoginArgs;
+use commands::replay::ReplayArgs;
+use commands::search::SearchArgs;
+use commands::simkl_login::SimklLoginArgs;
+use commands::stats::StatsArgs;
+use commands::trakt_login::TraktLoginArgs;
+use commands::validate::ValidateArgs;
+use commands::watchlist_import::WatchlistImportArgs;
+use commands::{ErrorFormat, GlobalArgs};
 
 /// Export your Plex watch history to a CSV file compatible with Letterboxd's import feature.
 #[derive(Parser, Debug)]
 #[command(name = "plex-to-letterboxd")]
 #[command(about = "Export Plex watch history to Letterboxd-compatible CSV", long_about = None)]
-struct Args {
-    /// Plex Media Server URL (e.g., http://192.168.1.100:32400)
-    /// Can also be set via PLEX_URL environment variable
-    #[arg(long, env = "PLEX_URL")]
-    plex_url: Option<String>,
+struct Cli {
+    #[command(flatten)]
+    global: GlobalArgs,
 
-    /// Plex authentication token
-    /// Can also be set via PLEX_TOKEN environment variable
-    #[arg(long, env = "PLEX_TOKEN")]
-    plex_token: Option<String>,
-
-    /// Library name to filter watch history (e.g., "Movies")
-    #[arg(long, required = true)]
-    library_name: String,
-
-    /// Output CSV file path (defaults to "plex_watch_history.csv")
-    /// Can also be set via OUTPUT_CSV environment variable
-    #[arg(long, default_value = "plex_watch_history.csv", env = "OUTPUT_CSV")]
-    output_csv: String,
+    #[command(subcommand)]
+    command: Command,
 }
 
-fn main() -> Result<()> {
-    let args = Args::parse();
-
-    // Validate required environment variables/arguments
-    let base_url = args.plex_url.context(
-        "Missing required argument: PLEX_URL\n\
-         Please provide --plex-url or set the PLEX_URL environment variable.\n\
-         Example: --plex-url http://192.168.1.100:32400",
-    )?;
-
-    let token = args.plex_token.context(
-        "Missing required argument: PLEX_TOKEN\n\
-         Please provide --plex-token or set the PLEX_TOKEN environment variable.\n\
-         To find your token, see: https://support.plex.tv/articles/204059436-finding-an-authentication-token-x-plex-token/",
-    )?;
+/// Available subcommands
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Export watch history to a Letterboxd CSV file
+    Export(ExportArgs),
+    /// Export the viewings of specific films, by rating key or title
+    ExportItems(ExportItemsArgs),
+    /// List the library sections available on the server
+    ListLibraries,
+    /// Log in to plex.tv to obtain an authentication token
+    Login(LoginArgs),
+    /// Set up a config file interactively: server, login, library, output path and tags
+    Init(InitArgs),
+    /// Check that the server URL, token and library selection are valid
+    Validate(ValidateArgs),
+    /// Append newly watched items to an existing export
+    Sync(ExportArgs),
+    /// Browse, search and filter the watch history, then export a selection
+    Browse(ExportArgs),
+    /// Export from raw responses saved with --save-raw-responses, without a server
+    Replay(ReplayArgs),
+    /// Print the viewings of films matching a title, with their watch dates and IDs
+    Search(SearchArgs),
+    /// Report the rows added, removed and changed between two exports
+    Diff(DiffArgs),
+    /// Print films per month and year, rewatches, busiest days and runtime watched
+    Stats(StatsArgs),
+    /// Diagnose connection and configuration problems
+    Doctor,
+    /// Log in to Trakt to obtain an access token for --target trakt-api
+    TraktLogin(TraktLoginArgs),
+    /// Log in to Simkl to obtain an access token for --target simkl-api
+    SimklLogin(SimklLoginArgs),
+    /// Add the films of a Letterboxd watchlist export to your Plex watchlist
+    WatchlistImport(WatchlistImportArgs),
+    /// Sync periodically, serving a health endpoint for container orchestrators
+    Daemon(DaemonArgs),
+}
 
-    if token.is_empty() {
-        anyhow::bail!(
-            "PLEX_TOKEN cannot be empty\n\
-             Please provide a valid token via --plex-token or set the PLEX_TOKEN environment variable.\n\
-             To find your token, see: https://support.plex.tv/articles/204059436-finding-an-authentication-token-x-plex-token/"
-        );
+impl Command {
+    /// Returns the credentials given through the subcommand's own flags
+    fn secrets(&self) -> Vec<String> {
+        match self {
+            Command::Export(args) | Command::Sync(args) | Command::Browse(args) => args.secrets(),
+            Command::Daemon(args) => args.export.secrets(),
+            Command::ExportItems(args) => args.export.secrets(),
+            Command::Replay(args) => args.export.secrets(),
+            Command::Stats(args) => args.export.secrets(),
+            Command::TraktLogin(args) => vec![args.trakt_client_secret.clone()],
+            _ => Vec::new(),
+        }
     }
+}
 
-    // Create a new Plex client
-    let client = PlexClient::new(base_url, token);
-
-    // Get library sections to find the matching library
-    let library_sections = client
-        .get_library_sections()
-        .context("Failed to get library sections")?;
-
-    // Find the directory matching the library name
-    let library_directory = library_sections
-        .directory
-        .iter()
-        .find(|dir| dir.title == args.library_name)
-        .with_context(|| {
-            format!(
-                "Library '{}' not found. Available libraries: {}",
-                args.library_name,
-                library_sections
-                    .directory
-                    .iter()
-                    .map(|dir| dir.title.as_str())
-                    .collect::<Vec<_>>()
-                    .join(", ")
-            )
-        })?;
-
-    // Extract the location ID from the directory's first location
-    let location_id = library_directory
-        .location
-        .first()
-        .map(|loc| loc.id.to_string())
-        .context("Library directory has no location ID")?;
-
-    // Create CSV writer
-    let output_file = &args.output_csv;
-    let mut wtr = Writer::from_path(output_file)
-        .with_context(|| format!("Failed to create output file: {}", output_file))?;
-
-    // Write CSV header
-    wtr.write_record(&["Title", "imdbID", "WatchedDate", "Tags"])?;
-    let tags = "\"Imported from Plex\"".to_string();
-
-    // Loop over watch history items using paginated iterator
-    // The iterator automatically handles pagination (100 items per request)
-    // Pass the location ID to filter by library section
-    for item_result in client.watch_history_iter(&location_id.to_string()) {
-        let item = item_result?;
-        println!("Processing: {}", item.title);
+impl Command {
+    /// Returns the export flags of the subcommand, when it has them
+    fn export_args_mut(&mut self) -> Option<&mut ExportArgs> {
+        match self {
+            Command::Export(args) | Command::Sync(args) | Command::Browse(args) => Some(args),
+            Command::Daemon(args) => Some(&mut args.export),
+            Command::ExportItems(args) => Some(&mut args.export),
+            Command::Replay(args) => Some(&mut args.export),
+            Command::Stats(args) => Some(&mut args.export),
+            _ => None,
+        }
+    }
 
-        // Use pattern matching to safely extract rating_key
-        let Some(rating_key) = &item.rating_key else {
-            println!("  Skipping {}: missing rating_key or key", item.title);
-            continue;
+    /// Returns the `--library-name` of the subcommand, unless it has none or `--library-id` is given
+    fn library_name_mut(&mut self) -> Option<&mut Option<String>> {
+        let (library_name, library_id) = match self {
+            Command::Search(args) => (&mut args.library.library_name, &args.library.library_id),
+            Command::Validate(args) => (&mut args.library_name, &args.library_id),
+            command => {
+                let library = &mut command.export_args_mut()?.library;
+                (&mut library.library_name, &library.library_id)
+            }
         };
+        library_id.is_none().then_some(library_name)
+    }
+}
 
-        let media_item_metadata = client.get_media_item_metadata(rating_key.clone())?;
-        let guid = media_item_metadata.metadata[0]
-            .guid
-            .first()
-            .map(|g| g.id.as_str().trim_start_matches("imdb://"));
-
-        // Use pattern matching to safely extract guid
-        let Some(guid) = guid else {
-            println!("  Skipping {}: missing guid", item.title);
-            continue;
+impl Cli {
+    /// Fills in the flags supplied by the profile selected in the config file
+    ///
+    /// Flags given on the command line take precedence over the profile,
+    /// which takes precedence over environment variables and defaults.
+    fn apply_profile(&mut self, matches: &ArgMatches) -> Result<()> {
+        // A broken config file is reported by the commands that read it
+        let config = match self.global.read_config() {
+            Ok(config) => config,
+            Err(_) if self.global.profile.is_none() => return Ok(()),
+            Err(err) => return Err(err),
         };
-
-        // Write row to CSV
-        wtr.write_record(&[&item.title, guid, &item.viewed_at, &tags])?;
+        let Some(name) = self.global.profile_name(&config) else {
+            return Ok(());
+        };
+        let profile = config.profile(&name)?;
+        // Global flags are propagated to the subcommand's matches
+        let Some((_, matches)) = matches.subcommand() else {
+            return Ok(());
+        };
+        let on_command_line = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+
+        if let Some(plex_url) = profile
+            .plex_url
+            .as_ref()
+            .filter(|_| !on_command_line("plex_url"))
+        {
+            self.global.plex_url = Some(plex_url.clone());
+        }
+        if !on_command_line("plex_token") && !on_command_line("plex_token_file") {
+            if let Some(plex_token) = &profile.plex_token {
+                self.global.plex_token = Some(plex_token.clone());
+                self.global.plex_token_file = None;
+            }
+        }
+        if let (Some(name), Some(library_name)) =
+            (&profile.library_name, self.command.library_name_mut())
+        {
+            library_name.get_or_insert_with(|| name.clone());
+        }
+        if let (Some(output_csv), Some(args)) =
+            (&profile.output_csv, self.command.export_args_mut())
+        {
+            if !on_command_line("output_csv") {
+                args.output_csv = output_csv.clone();
+            }
+        }
+        if let Some(args) = self.command.export_args_mut() {
+            if args.tags.is_empty() {
+                args.tags = profile.tags.clone();
+            }
+        }
+        Ok(())
     }
+}
 
-    // Flush the writer to ensure all data is written
-    wtr.flush()?;
+/// Runs the selected subcommand
+fn run(cli: &Cli) -> Result<()> {
+    match &cli.command {
+        Command::Export(args) => commands::export::run(&cli.global, args),
+        Command::ExportItems(args) => commands::export_items::run(&cli.global, args),
+        Command::ListLibraries => commands::list_libraries::run(&cli.global),
+        Command::Login(args) => commands::login::run(args),
+        Command::Init(args) => commands::init::run(&cli.global, args),
+        Command::Validate(args) => commands::validate::run(&cli.global, args),
+        Command::Sync(args) => commands::sync::run(&cli.global, args),
+        Command::Browse(args) => commands::browse::run(&cli.global, args),
+        Command::Replay(args) => commands::replay::run(&cli.global, args),
+        Command::Search(args) => commands::search::run(&cli.global, args),
+        Command::Diff(args) => commands::diff::run(args),
+        Command::Stats(args) => commands::stats::run(&cli.global, args),
+        Command::Doctor => commands::doctor::run(&cli.global),
+        Command::TraktLogin(args) => commands::trakt_login::run(args),
+        Command::SimklLogin(args) => commands::simkl_login::run(args),
+        Command::WatchlistImport(args) => commands::watchlist_import::run(&cli.global, args),
+        Command::Daemon(args) => commands::daemon::run(&cli.global, args),
+    }
+}
 
-    println!("\n✓ CSV file successfully generated: {}", output_file);
-    println!("Upload your watch history at: https://letterboxd.com/import/");
+fn main() -> ExitCode {
+    let matches = Cli::command().get_matches();
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    if cli.global.verbose {
+        logging::init_verbose();
+    } else if cli.global.log_redirects {
+        logging::init_redirects();
+    } else {
+        logging::init_warnings();
+    }
 
-    Ok(())
+    // The daemon gives each sync a run of its own
+    let span = match cli.command {
+        Command::Daemon(_) => tracing::Span::none(),
+        _ => logging::run_span(),
+    };
+    let result = span.in_scope(|| {
+        cli.apply_profile(&matches)?;
+        run(&cli)
+    });
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            // Errors can embed request URLs and credentials, which are never printed
+            let mut secrets = cli.global.secrets();
+            secrets.extend(cli.command.secrets());
+            let redactor = Redactor::new(secrets);
+            match cli.global.error_format {
+                ErrorFormat::Text => anstream::eprintln!(
+                    "{} {}",
+                    paint(ERROR, "Error:"),
+                    redactor.redact(&format!("{:?}", err))
+                ),
+                ErrorFormat::Json => eprintln!(
+                    "{}",
+                    serde_json::to_string(&ErrorReport::new(&err, &redactor))
+                        .expect("error reports serialize")
+                ),
+            }
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -0,0 +1,83 @@
+use anyhow::{Context, Result};
+use reqwest::blocking::Client;
+
+use crate::report::RunSummary;
+
+/// Kind of service a notification URL points to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotifyKind {
+    /// Any webhook, receiving the summary as JSON
+    Webhook,
+    /// A Discord webhook, receiving the message as its content
+    Discord,
+    /// An ntfy topic, receiving the message as plain text
+    Ntfy,
+}
+
+impl NotifyKind {
+    /// Guesses the kind of service from the URL
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use plex_to_letterboxd::notify::NotifyKind;
+    ///
+    /// assert_eq!(NotifyKind::from_url("https://discord.com/api/webhooks/1/abc"), NotifyKind::Discord);
+    /// assert_eq!(NotifyKind::from_url("https://ntfy.sh/my-topic"), NotifyKind::Ntfy);
+    /// assert_eq!(NotifyKind::from_url("https://example.com/hook"), NotifyKind::Webhook);
+    /// ```
+    pub fn from_url(url: &str) -> Self {
+        let host = url
+            .split("://")
+            .nth(1)
+            .unwrap_or(url)
+            .split(['/', ':'])
+            .next()
+            .unwrap_or_default();
+
+        if url.contains("/api/webhooks/")
+            && (host.ends_with("discord.com") || host.ends_with("discordapp.com"))
+        {
+            NotifyKind::Discord
+        } else if host.starts_with("ntfy.") {
+            NotifyKind::Ntfy
+        } else {
+            NotifyKind::Webhook
+        }
+    }
+}
+
+/// Sends a run summary to a notification URL
+///
+/// Discord webhooks and ntfy topics receive a readable message; any other URL
+/// receives the summary as JSON.
+pub fn notify(url: &str, summary: &RunSummary) -> Result<()> {
+    let client = Client::new();
+    let request = match NotifyKind::from_url(url) {
+        NotifyKind::Webhook => client.post(url).json(summary),
+        NotifyKind::Discord => client
+            .post(url)
+            .json(&serde_json::json!({ "content": summary.message() })),
+        NotifyKind::Ntfy => client
+            .post(url)
+            .header("Title", "plex-to-letterboxd")
+            .header(
+                "Tags",
+                if summary.success {
+                    "white_check_mark"
+                } else {
+                    "x"
+                },
+            )
+            .body(summary.message()),
+    };
+
+    request
+        .send()
+        .map_err(reqwest::Error::without_url)
+        .context("Failed to send notification")?
+        .error_for_status()
+        .map_err(reqwest::Error::without_url)
+        .context("Notification URL returned an error")?;
+    Ok(())
+}
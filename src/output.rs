@@ -0,0 +1,220 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+
+/// How often buffered output is flushed to the temporary file
+const FLUSH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// An output file that only appears at its path once it is complete
+///
+/// Output goes to a temporary file next to the target (so that both are on
+/// the same file system), flushed every few seconds. `commit` renames it over
+/// the target; if the run fails or is interrupted before that, the temporary
+/// file is removed and any existing file at the target is left untouched, so
+/// a half-written CSV is never uploaded by mistake.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io::Write;
+/// use plex_to_letterboxd::output::AtomicFile;
+///
+/// let path = std::env::temp_dir().join("atomic-file-example.csv");
+/// let mut file = AtomicFile::create(&path)?;
+/// writeln!(file, "Title,imdbID")?;
+/// assert!(!path.exists());
+///
+/// file.commit()?;
+/// assert_eq!(std::fs::read_to_string(&path)?, "Title,imdbID\n");
+/// # std::fs::remove_file(&path)?;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct AtomicFile {
+    /// Path the file is renamed to when committed
+    path: PathBuf,
+    /// Path of the temporary file being written
+    temp_path: PathBuf,
+    /// Buffered writer for the temporary file, taken when committed
+    writer: Option<BufWriter<File>>,
+    /// When buffered output was last flushed
+    last_flush: Instant,
+}
+
+impl AtomicFile {
+    /// Creates an empty temporary file for the given target path
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file_name = path
+            .file_name()
+            .with_context(|| format!("Invalid output file: {}", path.display()))?;
+        let temp_path = path.with_file_name(format!(
+            ".{}.{}.tmp",
+            file_name.to_string_lossy(),
+            std::process::id()
+        ));
+        let file = File::create(&temp_path)
+            .with_context(|| format!("Failed to create output file: {}", path.display()))?;
+
+        Ok(Self {
+            path,
+            temp_path,
+            writer: Some(BufWriter::new(file)),
+            last_flush: Instant::now(),
+        })
+    }
+
+    /// Creates a temporary file starting with the current contents of the target
+    ///
+    /// Used to append to a file without modifying it until the append is complete.
+    pub fn append(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let mut file = Self::create(path)?;
+        if path.exists() {
+            let mut existing = File::open(path)
+                .with_context(|| format!("Failed to read output file: {}", path.display()))?;
+            io::copy(&mut existing, &mut file)
+                .with_context(|| format!("Failed to copy output file: {}", path.display()))?;
+        }
+        Ok(file)
+    }
+
+    /// Flushes the temporary file to disk and renames it over the target
+    pub fn commit(mut self) -> Result<()> {
+        if let Some(writer) = self.writer.take() {
+            let file = writer
+                .into_inner()
+                .map_err(|e| e.into_error())
+                .with_context(|| format!("Failed to write output file: {}", self.path.display()))?;
+            file.sync_all()?;
+        }
+        fs::rename(&self.temp_path, &self.path)
+            .with_context(|| format!("Failed to write output file: {}", self.path.display()))
+    }
+
+    /// Returns the writer of the temporary file
+    fn writer(&mut self) -> &mut BufWriter<File> {
+        self.writer
+            .as_mut()
+            .expect("the writer is only taken when committing")
+    }
+}
+
+impl Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.writer().write(buf)?;
+        if self.last_flush.elapsed() >= FLUSH_INTERVAL {
+            self.flush()?;
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.last_flush = Instant::now();
+        self.writer().flush()
+    }
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        // Close the file, then discard it unless it was renamed into place
+        drop(self.writer.take());
+        let _ = fs::remove_file(&self.temp_path);
+    }
+}
+
+/// Copies a file to "<name>.bak" before it is replaced, so the previous output can be restored
+///
+/// An earlier backup is overwritten. Returns the path of the backup, or
+/// `None` when there is no file to back up.
+///
+/// # Example
+///
+/// ```rust
+/// use plex_to_letterboxd::output::backup;
+///
+/// let path = std::env::temp_dir().join("backup-example.csv");
+/// assert_eq!(backup(&path)?, None);
+///
+/// std::fs::write(&path, "Title,imdbID\n")?;
+/// let backup_path = backup(&path)?.unwrap();
+/// assert!(backup_path.ends_with("backup-example.csv.bak"));
+/// assert_eq!(std::fs::read_to_string(&backup_path)?, "Title,imdbID\n");
+/// # std::fs::remove_file(&path)?;
+/// # std::fs::remove_file(&backup_path)?;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn backup(path: impl AsRef<Path>) -> Result<Option<PathBuf>> {
+    let path = path.as_ref();
+    if !path.is_file() {
+        return Ok(None);
+    }
+
+    let mut backup_path = path.as_os_str().to_owned();
+    backup_path.push(".bak");
+    let backup_path = PathBuf::from(backup_path);
+    fs::copy(path, &backup_path).with_context(|| {
+        format!(
+            "Failed to back up {} to {}",
+            path.display(),
+            backup_path.display()
+        )
+    })?;
+    Ok(Some(backup_path))
+}
+
+/// Fills in the placeholders of an output file name, such as `history_{library}_{date}.csv`
+///
+/// `{date}` becomes the given date as YYYY-MM-DD and `{library}` the library
+/// name, with path separators replaced by underscores. Any other placeholder,
+/// or `{library}` without a library name, is an error.
+///
+/// # Example
+///
+/// ```rust
+/// use chrono::NaiveDate;
+/// use plex_to_letterboxd::output::expand_output_path;
+///
+/// let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+/// let path = expand_output_path("history_{library}_{date}.csv", Some("4K/Movies"), date)?;
+/// assert_eq!(path, "history_4K_Movies_2024-01-15.csv");
+/// assert!(expand_output_path("history_{week}.csv", None, date).is_err());
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn expand_output_path(
+    template: &str,
+    library: Option<&str>,
+    date: NaiveDate,
+) -> Result<String> {
+    let mut path = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        path.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .with_context(|| format!("Unclosed placeholder in output file name: {}", template))?;
+        match &rest[start + 1..start + end] {
+            "date" => path.push_str(&date.format("%Y-%m-%d").to_string()),
+            "library" => {
+                let library = library.with_context(|| {
+                    format!(
+                        "{} uses {{library}}, which requires --library-name or --library-id",
+                        template
+                    )
+                })?;
+                path.push_str(&library.replace(['/', '\\'], "_"));
+            }
+            placeholder => anyhow::bail!(
+                "Unknown placeholder {{{}}} in output file name {}; expected {{date}} or {{library}}",
+                placeholder,
+                template
+            ),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    path.push_str(rest);
+    Ok(path)
+}
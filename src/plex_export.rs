@@ -0,0 +1,399 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use plex_client::deserializers::deserialize_optional_viewed_at;
+use plex_client::library::{PlexLibrarySection, PlexLibrarySectionsDirectory};
+use plex_client::media_item::{PlexMediaItemGuidItem, PlexMediaItemMetadata};
+use plex_client::source::{HistoryIter, HistorySource};
+use plex_client::watch_history::PlexWatchHistoryItem;
+use serde_json::{Map, Value};
+
+/// Library of the viewings that do not name one
+const DEFAULT_LIBRARY: &str = "Plex export";
+
+/// Fields holding the title of a viewing, normalized as by `normalize_key`
+const TITLE_FIELDS: [&str; 1] = ["title"];
+
+/// Words in the names of the files of the export holding the watch history,
+/// normalized as by `normalize_key`
+const HISTORY_FILE_WORDS: [&str; 2] = ["history", "viewing"];
+
+/// Fields holding when an item was viewed, in order of preference
+const VIEWED_AT_FIELDS: [&str; 5] = [
+    "viewedat",
+    "lastviewedat",
+    "watchedat",
+    "vieweddate",
+    "watcheddate",
+];
+
+/// Fields holding the library a viewing belongs to
+const LIBRARY_FIELDS: [&str; 2] = ["librarysectiontitle", "library"];
+
+/// Prefixes of the GUIDs matched on Letterboxd, IMDb first as it is exported
+const GUID_PREFIXES: [&str; 3] = ["imdb://", "tmdb://", "tvdb://"];
+
+/// A viewing read from a Plex data export
+#[derive(Debug, Clone)]
+struct ExportedViewing {
+    /// Index of the library in `PlexExportSource::libraries`
+    library: usize,
+    /// When the item was viewed, if the export has a usable date
+    viewed_at: Option<DateTime<Utc>>,
+    /// Name of the device the item was played on
+    device: Option<String>,
+    /// Metadata of the item, as far as the export records it
+    metadata: PlexMediaItemMetadata,
+}
+
+/// The watch history of a Plex account from its data export ZIP
+///
+/// Plex sends the data of an account, including its watch history, as a ZIP
+/// archive of JSON and CSV files when a data export is requested in the
+/// privacy settings of the account. Only the watch history files are read
+/// (those with "history" or "viewing" in their name, e.g. "Watch
+/// History.json"), so that ratings and watchlist files are not mistaken for
+/// viewings. Every object (or CSV row) of those files with a title and a
+/// viewing date (`viewedAt`, `lastViewedAt`, `watchedAt`, ...) is read as a
+/// viewing. Field names are matched
+/// ignoring case, spaces and underscores, and dates may be Unix timestamps in
+/// seconds or milliseconds, RFC 3339 or "YYYY-MM-DD[ HH:MM:SS]" in UTC.
+///
+/// There is no server to ask for metadata, so the IMDb, TMDb or TVDB IDs of a
+/// viewing come from its `Guid`/`guid` field, or from an `imdbId` field, and
+/// viewings without any are skipped by the export. Viewings are grouped in
+/// libraries by their `librarySectionTitle`, and in one "Plex export" library
+/// when the export does not name them. Rating keys are positions in the
+/// export, so they are only meaningful within a run.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io::Write;
+///
+/// use plex_client::source::HistorySource;
+/// use plex_to_letterboxd::plex_export::PlexExportSource;
+///
+/// let path = std::env::temp_dir().join("plex-export-example.zip");
+/// let mut zip = zip::ZipWriter::new(std::fs::File::create(&path)?);
+/// zip.start_file("Watch History.json", zip::write::SimpleFileOptions::default())?;
+/// zip.write_all(br#"[
+///     {"title": "Heat", "type": "movie", "year": 1995, "viewedAt": 1705350667,
+///      "Guid": [{"id": "tmdb://949"}, {"id": "imdb://tt0113277"}]}
+/// ]"#)?;
+/// // Not a watch history file, so its entry is not a viewing
+/// zip.start_file("Ratings.json", zip::write::SimpleFileOptions::default())?;
+/// zip.write_all(br#"[{"title": "Alien", "lastViewedAt": 1705350667, "userRating": 8}]"#)?;
+/// zip.finish()?;
+///
+/// let source = PlexExportSource::open(&path)?;
+/// let [library] = source.library_sections()?.directory.try_into().unwrap();
+/// assert_eq!(library.title, "Plex export");
+/// assert_eq!(source.history(&library.key).count(), 1);
+///
+/// let item = source.history(&library.key).next().unwrap()?;
+/// assert_eq!(item.viewed_date().unwrap().to_string(), "2024-01-15");
+/// let metadata = source.metadata(item.rating_key.as_deref().unwrap())?;
+/// assert_eq!(metadata.year, Some(1995));
+/// assert_eq!(metadata.guid[0].id, "imdb://tt0113277");
+/// # std::fs::remove_file(path)?;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct PlexExportSource {
+    /// Path of the ZIP archive, for messages
+    path: PathBuf,
+    /// Names of the libraries, whose keys are their positions plus one
+    libraries: Vec<String>,
+    /// Viewings of every library, newest first
+    viewings: Vec<ExportedViewing>,
+}
+
+impl PlexExportSource {
+    /// Reads the viewings of a Plex data export ZIP
+    ///
+    /// Fails when a watch history file of the archive cannot be parsed, or
+    /// when the archive has no viewing at all.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .with_context(|| format!("Failed to open Plex data export: {}", path.display()))?;
+        let mut archive = zip::ZipArchive::new(file)
+            .with_context(|| format!("Not a ZIP archive: {}", path.display()))?;
+
+        let mut libraries = Vec::new();
+        let mut viewings = Vec::new();
+        for index in 0..archive.len() {
+            let mut entry = archive.by_index(index)?;
+            let name = entry.name().to_string();
+            let lowercase = name.to_lowercase();
+            if entry.is_dir() || !is_history_file(&name) {
+                continue;
+            }
+            if lowercase.ends_with(".json") {
+                let mut contents = Vec::new();
+                entry.read_to_end(&mut contents)?;
+                let value: Value = serde_json::from_slice(&contents)
+                    .with_context(|| format!("Failed to parse {} in the export", name))?;
+                collect_viewings(&value, &mut libraries, &mut viewings);
+            } else if lowercase.ends_with(".csv") {
+                let mut reader = csv::Reader::from_reader(entry);
+                let headers = reader.headers()?.clone();
+                for record in reader.records() {
+                    let record = record
+                        .with_context(|| format!("Failed to parse {} in the export", name))?;
+                    let object: Map<String, Value> = headers
+                        .iter()
+                        .zip(record.iter())
+                        .map(|(header, value)| {
+                            (header.to_string(), Value::String(value.to_string()))
+                        })
+                        .collect();
+                    viewings.extend(viewing(&object, &mut libraries));
+                }
+            }
+        }
+
+        if viewings.is_empty() {
+            anyhow::bail!(
+                "No watch history found in {}: expected JSON or CSV files named like \
+                 \"Watch History.json\" with a title and a viewedAt date",
+                path.display()
+            );
+        }
+        // Undated viewings last, as sources list their history newest first
+        viewings.sort_by_key(|viewing| std::cmp::Reverse(viewing.viewed_at));
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            libraries,
+            viewings,
+        })
+    }
+}
+
+impl HistorySource for PlexExportSource {
+    fn name(&self) -> String {
+        format!("Plex export ({})", self.path.display())
+    }
+
+    fn library_sections(&self) -> Result<PlexLibrarySection> {
+        Ok(PlexLibrarySection {
+            directory: self
+                .libraries
+                .iter()
+                .enumerate()
+                .map(|(index, title)| {
+                    // Only libraries of episodes are not movie libraries
+                    let has_movies = self.viewings.iter().any(|viewing| {
+                        viewing.library == index && viewing.metadata.item_type != "episode"
+                    });
+                    PlexLibrarySectionsDirectory {
+                        key: (index + 1).to_string(),
+                        section_type: if has_movies { "movie" } else { "show" }.to_string(),
+                        title: title.clone(),
+                        location: Vec::new(),
+                    }
+                })
+                .collect(),
+        })
+    }
+
+    fn history<'a>(&'a self, library_section_id: &str) -> HistoryIter<'a> {
+        let library_section_id = library_section_id.to_string();
+        Box::new(
+            self.viewings
+                .iter()
+                .enumerate()
+                .filter(move |(_, viewing)| (viewing.library + 1).to_string() == library_section_id)
+                .map(|(index, viewing)| {
+                    Ok(PlexWatchHistoryItem {
+                        title: viewing.metadata.title.clone(),
+                        rating_key: Some(index.to_string()),
+                        library_section_id: (viewing.library + 1).to_string(),
+                        viewed_at: viewing.viewed_at,
+                        device_id: None,
+                        device: viewing.device.clone(),
+                        history_key: None,
+                    })
+                }),
+        )
+    }
+
+    fn metadata(&self, rating_key: &str) -> Result<PlexMediaItemMetadata> {
+        rating_key
+            .parse::<usize>()
+            .ok()
+            .and_then(|index| self.viewings.get(index))
+            .map(|viewing| viewing.metadata.clone())
+            .with_context(|| format!("No item {} in the Plex data export", rating_key))
+    }
+}
+
+/// Reads the viewings among the objects of a JSON document, including nested ones
+fn collect_viewings(
+    value: &Value,
+    libraries: &mut Vec<String>,
+    viewings: &mut Vec<ExportedViewing>,
+) {
+    match value {
+        Value::Array(values) => {
+            for value in values {
+                collect_viewings(value, libraries, viewings);
+            }
+        }
+        Value::Object(object) => match viewing(object, libraries) {
+            Some(viewing) => viewings.push(viewing),
+            None => {
+                for value in object.values() {
+                    collect_viewings(value, libraries, viewings);
+                }
+            }
+        },
+        _ => {}
+    }
+}
+
+/// Returns whether a file of the export holds watch history, judging by its name
+fn is_history_file(name: &str) -> bool {
+    let file_name = normalize_key(name.rsplit('/').next().unwrap_or(name));
+    HISTORY_FILE_WORDS
+        .iter()
+        .any(|word| file_name.contains(word))
+}
+
+/// Lowercases a field name and removes everything but letters and digits
+fn normalize_key(key: &str) -> String {
+    key.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// Reads a viewing from an object with a title and a viewing date field
+///
+/// The library of the viewing is added to `libraries` when it is new.
+fn viewing(object: &Map<String, Value>, libraries: &mut Vec<String>) -> Option<ExportedViewing> {
+    let fields = object
+        .iter()
+        .map(|(key, value)| (normalize_key(key), value))
+        .collect::<HashMap<_, _>>();
+    let field = |names: &[&str]| names.iter().find_map(|name| fields.get(*name).copied());
+    let text = |names: &[&str]| {
+        field(names)
+            .and_then(value_text)
+            .filter(|text| !text.is_empty())
+    };
+
+    let title = text(&TITLE_FIELDS)?;
+    let viewed_at = parse_viewed_at(field(&VIEWED_AT_FIELDS)?);
+
+    let library_title = text(&LIBRARY_FIELDS).unwrap_or_else(|| DEFAULT_LIBRARY.to_string());
+    let library = match libraries.iter().position(|title| *title == library_title) {
+        Some(index) => index,
+        None => {
+            libraries.push(library_title);
+            libraries.len() - 1
+        }
+    };
+
+    let mut guids = Vec::new();
+    if let Some(value) = field(&["guid", "guids"]) {
+        collect_guids(value, &mut guids);
+    }
+    if let Some(imdb_id) = text(&["imdbid", "imdb"]) {
+        guids.push(format!("imdb://{}", imdb_id.trim_start_matches("imdb://")));
+    }
+    guids.retain(|guid| GUID_PREFIXES.iter().any(|prefix| guid.starts_with(prefix)));
+    // Stable, so the GUIDs keep their order within each prefix
+    guids.sort_by_key(|guid| !guid.starts_with("imdb://"));
+    guids.dedup();
+
+    let number = |names: &[&str]| text(names).and_then(|text| text.parse::<f64>().ok());
+    Some(ExportedViewing {
+        library,
+        viewed_at,
+        device: text(&["device", "player", "devicename"]),
+        metadata: PlexMediaItemMetadata {
+            item_type: text(&["type", "mediatype"]).unwrap_or_else(|| "movie".to_string()),
+            rating_key: text(&["ratingkey"]),
+            subtype: None,
+            extra_type: None,
+            title,
+            original_title: text(&["originaltitle"]),
+            edition_title: text(&["editiontitle"]),
+            year: number(&["year"]).map(|year| year as u32),
+            originally_available_at: text(&["originallyavailableat"]),
+            studio: text(&["studio"]),
+            duration: number(&["duration"]).map(|duration| duration as u64),
+            user_rating: number(&["userrating"]).map(|rating| rating as f32),
+            added_at: field(&["addedat"]).and_then(parse_viewed_at),
+            plex_guid: None,
+            guid: guids
+                .into_iter()
+                .map(|id| PlexMediaItemGuidItem { id })
+                .collect(),
+            director: Vec::new(),
+            genre: Vec::new(),
+            collection: Vec::new(),
+            label: Vec::new(),
+        },
+    })
+}
+
+/// Returns a string or number field as text
+fn value_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.trim().to_string()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    }
+}
+
+/// Collects the GUIDs of a `Guid` field: a string, a list of strings or a list of `{"id": ...}`
+fn collect_guids(value: &Value, guids: &mut Vec<String>) {
+    match value {
+        Value::String(text) => guids.extend(
+            text.split([',', ' '])
+                .filter(|guid| !guid.is_empty())
+                .map(str::to_string),
+        ),
+        Value::Array(values) => {
+            for value in values {
+                collect_guids(value, guids);
+            }
+        }
+        Value::Object(object) => {
+            if let Some(id) = object.get("id") {
+                collect_guids(id, guids);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Parses a viewing date: a Unix timestamp, RFC 3339, or a UTC date and time
+fn parse_viewed_at(value: &Value) -> Option<DateTime<Utc>> {
+    if let Ok(Some(viewed_at)) = deserialize_optional_viewed_at(value) {
+        return Some(viewed_at);
+    }
+    let text = value.as_str()?.trim();
+    DateTime::parse_from_rfc3339(text)
+        .map(|viewed_at| viewed_at.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"]
+                .iter()
+                .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+                .map(|viewed_at| viewed_at.and_utc())
+        })
+        .or_else(|| {
+            NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .map(|viewed_at| viewed_at.and_utc())
+        })
+}
@@ -0,0 +1,58 @@
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+/// Placeholder replacing redacted secrets
+pub const REDACTED: &str = "****";
+
+/// Matches credentials passed as URL query parameters (e.g. "X-Plex-Token=abc")
+fn query_secret_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"(?i)\b(x-plex-token|apikey|api_key|api-key|access_token|token)=[^&\s\x22']+")
+            .expect("valid redaction pattern")
+    })
+}
+
+/// Removes credentials from text before it is shown to the user
+///
+/// Every known secret (tokens, API keys) is replaced with `****`, as is the
+/// value of any credential-looking URL query parameter, so secrets that were
+/// not registered are still caught when they appear in a request URL.
+#[derive(Debug, Clone, Default)]
+pub struct Redactor {
+    /// Secrets to redact, longest first so overlapping secrets are fully removed
+    secrets: Vec<String>,
+}
+
+impl Redactor {
+    /// Creates a redactor for the given secrets, ignoring empty ones
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use plex_to_letterboxd::redact::Redactor;
+    ///
+    /// let redactor = Redactor::new(["abc123".to_string()]);
+    /// assert_eq!(
+    ///     redactor.redact("GET http://plex:32400/identity?X-Plex-Token=abc123 failed (token abc123)"),
+    ///     "GET http://plex:32400/identity?X-Plex-Token=**** failed (token ****)"
+    /// );
+    /// ```
+    pub fn new(secrets: impl IntoIterator<Item = String>) -> Self {
+        let mut secrets: Vec<String> = secrets.into_iter().filter(|s| !s.is_empty()).collect();
+        secrets.sort_by_key(|s| std::cmp::Reverse(s.len()));
+        secrets.dedup();
+        Self { secrets }
+    }
+
+    /// Returns the text with every secret replaced by `****`
+    pub fn redact(&self, text: &str) -> String {
+        let text = self.secrets.iter().fold(text.to_string(), |text, secret| {
+            text.replace(secret, REDACTED)
+        });
+        query_secret_pattern()
+            .replace_all(&text, format!("${{1}}={}", REDACTED))
+            .into_owned()
+    }
+}
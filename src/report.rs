@@ -0,0 +1,314 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+
+use crate::console::status;
+use crate::row::ExportRow;
+use crate::style::{paint, SUCCESS, WARNING};
+
+/// Share of rows with an IMDb ID that Letterboxd is expected to match
+const IMDB_MATCH_RATE: f64 = 1.0;
+/// Share of rows with only a TMDb ID that Letterboxd is expected to match
+const TMDB_MATCH_RATE: f64 = 0.95;
+/// Share of rows matched by title and year alone that Letterboxd is expected to match
+const TITLE_MATCH_RATE: f64 = 0.75;
+
+/// Counts of what happened during an export or sync
+///
+/// Skipped items are reported as they happen and counted by reason, so
+/// callers can summarize a run without parsing its output.
+///
+/// # Example
+///
+/// ```rust
+/// use plex_to_letterboxd::report::RunReport;
+///
+/// let mut report = RunReport::default();
+/// report.exported();
+/// report.skip("Heat", "missing guid");
+/// report.skip("Ronin", "missing guid");
+/// report.warn("Solaris", "year 2002 differs from release date 1972-03-20");
+/// assert_eq!(report.rows_exported, 1);
+/// assert_eq!(report.rows_skipped(), 2);
+/// assert_eq!(report.skipped["missing guid"], 2);
+/// assert_eq!(report.warnings.len(), 1);
+/// ```
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RunReport {
+    /// Rows written to the destination
+    pub rows_exported: usize,
+    /// Number of skipped items, by reason
+    pub skipped: BTreeMap<String, usize>,
+    /// Problems found with exported items (e.g. "Solaris: year 2002 differs ...")
+    pub warnings: Vec<String>,
+    /// Files written, when they depend on the rows (e.g. with `--split-by-year`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub outputs: Vec<String>,
+}
+
+impl RunReport {
+    /// Records an exported row
+    pub fn exported(&mut self) {
+        self.rows_exported += 1;
+    }
+
+    /// Reports and records an item that was not exported
+    pub fn skip(&mut self, title: &str, reason: &str) {
+        status(format_args!(
+            "  {} {}: {}",
+            paint(WARNING, "Skipping"),
+            title,
+            reason
+        ));
+        *self.skipped.entry(reason.to_string()).or_default() += 1;
+    }
+
+    /// Reports and records a problem with an item that is still exported
+    pub fn warn(&mut self, title: &str, warning: &str) {
+        status(format_args!(
+            "  {} {}: {}",
+            paint(WARNING, "Warning for"),
+            title,
+            warning
+        ));
+        self.warnings.push(format!("{}: {}", title, warning));
+    }
+
+    /// Returns the total number of skipped items
+    pub fn rows_skipped(&self) -> usize {
+        self.skipped.values().sum()
+    }
+
+    /// Returns the "Exported N rows, skipped M" line printed after a run
+    ///
+    /// The exported count is green and, when items were skipped, the skipped
+    /// count yellow; print it with `anstream` so colors are only shown on
+    /// terminals.
+    pub fn summary(&self) -> String {
+        let skipped = self.rows_skipped();
+        format!(
+            "{}, {}",
+            paint(SUCCESS, format!("Exported {} rows", self.rows_exported)),
+            if skipped > 0 {
+                paint(WARNING, format!("skipped {}", skipped))
+            } else {
+                format!("skipped {}", skipped)
+            }
+        )
+    }
+}
+
+/// Summary of a run, written to `--summary-json` and sent to `--notify-url`
+#[derive(Debug, Clone, Serialize)]
+pub struct RunSummary {
+    /// Subcommand that ran (e.g. "sync")
+    pub command: String,
+    /// Whether the run succeeded
+    pub success: bool,
+    /// Rows exported (or added, for sync)
+    pub rows_exported: usize,
+    /// Items that were not exported
+    pub rows_skipped: usize,
+    /// Number of skipped items, by reason
+    pub skipped: BTreeMap<String, usize>,
+    /// Problems found with exported items
+    pub warnings: Vec<String>,
+    /// Error that stopped the run, if it failed
+    pub error: Option<String>,
+    /// When the run started (RFC 3339)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<String>,
+    /// How long the run took, in seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_seconds: Option<f64>,
+    /// Files written by the run
+    pub outputs: Vec<String>,
+}
+
+impl RunSummary {
+    /// Summarizes a successful run
+    pub fn success(command: &str, report: &RunReport) -> Self {
+        Self {
+            command: command.to_string(),
+            success: true,
+            rows_exported: report.rows_exported,
+            rows_skipped: report.rows_skipped(),
+            skipped: report.skipped.clone(),
+            warnings: report.warnings.clone(),
+            error: None,
+            started_at: None,
+            duration_seconds: None,
+            outputs: Vec::new(),
+        }
+    }
+
+    /// Summarizes a failed run
+    pub fn failure(command: &str, error: String) -> Self {
+        Self {
+            command: command.to_string(),
+            success: false,
+            rows_exported: 0,
+            rows_skipped: 0,
+            skipped: BTreeMap::new(),
+            warnings: Vec::new(),
+            error: Some(error),
+            started_at: None,
+            duration_seconds: None,
+            outputs: Vec::new(),
+        }
+    }
+
+    /// Records when the run started, measuring its duration until now
+    pub fn with_start(mut self, started_at: DateTime<Utc>) -> Self {
+        let duration = Utc::now() - started_at;
+        self.started_at = Some(started_at.to_rfc3339_opts(SecondsFormat::Secs, true));
+        self.duration_seconds = Some(duration.num_milliseconds() as f64 / 1000.0);
+        self
+    }
+
+    /// Records the files written by the run
+    pub fn with_outputs(mut self, outputs: Vec<String>) -> Self {
+        self.outputs = outputs;
+        self
+    }
+
+    /// Returns a one-line, human-readable description of the run
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use plex_to_letterboxd::report::{RunReport, RunSummary};
+    ///
+    /// let mut report = RunReport::default();
+    /// report.exported();
+    /// let summary = RunSummary::success("sync", &report);
+    /// assert_eq!(summary.message(), "plex-to-letterboxd sync: 1 rows exported, 0 skipped");
+    /// ```
+    pub fn message(&self) -> String {
+        match &self.error {
+            Some(error) => format!("plex-to-letterboxd {} failed: {}", self.command, error),
+            None => format!(
+                "plex-to-letterboxd {}: {} rows exported, {} skipped",
+                self.command, self.rows_exported, self.rows_skipped
+            ),
+        }
+    }
+}
+
+/// How Letterboxd's importer is expected to match exported rows
+///
+/// Rows with an IMDb ID match reliably, rows with only a TMDb ID almost
+/// always when the tmdbID column is exported, and the rest fall back on
+/// title and year matching, which often picks the wrong film. The estimated
+/// match rate weighs each group by those expectations; it is a heuristic,
+/// not a promise.
+///
+/// # Example
+///
+/// ```rust
+/// use plex_to_letterboxd::report::MatchEstimate;
+/// use plex_to_letterboxd::row::ExportRow;
+///
+/// let rows = [
+///     ExportRow {
+///         imdb_id: "tt0113277".to_string(),
+///         ..Default::default()
+///     },
+///     ExportRow {
+///         tmdb_id: Some("949".to_string()),
+///         ..Default::default()
+///     },
+///     ExportRow::default(),
+/// ];
+/// let estimate = MatchEstimate::from_rows(&rows);
+/// assert_eq!((estimate.imdb, estimate.tmdb_only, estimate.title_only), (1, 1, 1));
+/// assert_eq!(estimate.match_rate(), 0.9);
+///
+/// // Without the tmdbID column, Letterboxd only sees the title of the second row
+/// let estimate = estimate.with_tmdb_column(false);
+/// assert_eq!(estimate.match_rate(), (1.0 + 0.75 + 0.75) / 3.0);
+/// ```
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MatchEstimate {
+    /// Rows with an IMDb ID
+    pub imdb: usize,
+    /// Rows with a TMDb ID but no IMDb ID
+    pub tmdb_only: usize,
+    /// Rows Letterboxd can only match by title and year
+    pub title_only: usize,
+    /// Whether the tmdbID column is exported, without which rows with only a
+    /// TMDb ID are matched by title and year
+    #[serde(skip)]
+    tmdb_column: bool,
+}
+
+impl MatchEstimate {
+    /// Counts how each row is expected to be matched
+    ///
+    /// Rows are assumed to be exported with the tmdbID column; see
+    /// `with_tmdb_column`.
+    pub fn from_rows(rows: &[ExportRow]) -> Self {
+        let mut estimate = Self {
+            tmdb_column: true,
+            ..Default::default()
+        };
+        for row in rows {
+            if row.imdb_id.starts_with("tt") {
+                estimate.imdb += 1;
+            } else if row.tmdb_id.is_some() || row.imdb_id.starts_with("tmdb://") {
+                estimate.tmdb_only += 1;
+            } else {
+                estimate.title_only += 1;
+            }
+        }
+        estimate
+    }
+
+    /// Sets whether the tmdbID column is exported
+    pub fn with_tmdb_column(mut self, tmdb_column: bool) -> Self {
+        self.tmdb_column = tmdb_column;
+        self
+    }
+
+    /// Returns the number of rows counted
+    pub fn total(&self) -> usize {
+        self.imdb + self.tmdb_only + self.title_only
+    }
+
+    /// Returns the expected share of rows Letterboxd will match, between 0 and 1
+    pub fn match_rate(&self) -> f64 {
+        if self.total() == 0 {
+            return 1.0;
+        }
+        let expected = self.imdb as f64 * IMDB_MATCH_RATE
+            + self.tmdb_only as f64
+                * if self.tmdb_column {
+                    TMDB_MATCH_RATE
+                } else {
+                    TITLE_MATCH_RATE
+                }
+            + self.title_only as f64 * TITLE_MATCH_RATE;
+        expected / self.total() as f64
+    }
+}
+
+impl fmt::Display for MatchEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Rows with an IMDb ID:          {}", self.imdb)?;
+        writeln!(f, "Rows with only a TMDb ID:      {}", self.tmdb_only)?;
+        writeln!(f, "Rows matched by title/year:    {}", self.title_only)?;
+        if self.tmdb_only > 0 && !self.tmdb_column {
+            writeln!(
+                f,
+                "Add the tmdbID column so Letterboxd matches rows with only a TMDb ID by it"
+            )?;
+        }
+        write!(
+            f,
+            "Estimated Letterboxd match rate: {:.0}%",
+            self.match_rate() * 100.0
+        )
+    }
+}
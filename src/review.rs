@@ -0,0 +1,104 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::row::ExportRow;
+
+/// A review kept outside of Plex, from a notes CSV file
+///
+/// A note identifies its film by imdbID, or by Title and WatchedDate when it
+/// has no imdbID. A WatchedDate given with an imdbID restricts the note to the
+/// viewing on that date.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReviewNote {
+    /// IMDb identifier of the film (e.g. "tt1234567")
+    #[serde(rename = "imdbID", default)]
+    pub imdb_id: String,
+    /// Title of the film
+    #[serde(rename = "Title", default)]
+    pub title: String,
+    /// Date of the viewing the review is about (YYYY-MM-DD)
+    #[serde(rename = "WatchedDate", default)]
+    pub watched_date: String,
+    /// Text of the review
+    #[serde(rename = "Review")]
+    pub review: String,
+}
+
+impl ReviewNote {
+    /// Returns whether the note is about the viewing of a row
+    fn matches(&self, row: &ExportRow) -> bool {
+        let same_date = self.watched_date.is_empty() || self.watched_date == row.watched_date;
+        if !self.imdb_id.is_empty() {
+            return self.imdb_id == row.imdb_id && same_date;
+        }
+        !self.title.is_empty()
+            && !self.watched_date.is_empty()
+            && self.title.eq_ignore_ascii_case(&row.title)
+            && same_date
+    }
+}
+
+/// Reviews of a notes file, looked up for each exported row
+#[derive(Debug, Clone, Default)]
+pub struct ReviewNotes {
+    /// Notes in file order
+    notes: Vec<ReviewNote>,
+}
+
+impl ReviewNotes {
+    /// Reads notes from CSV with a `Review` column and `imdbID`, `Title` or
+    /// `WatchedDate` columns identifying the films
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use plex_to_letterboxd::review::ReviewNotes;
+    /// use plex_to_letterboxd::row::ExportRow;
+    ///
+    /// let csv = "imdbID,Title,WatchedDate,Review\n\
+    ///            tt0113277,,,The bank heist still holds up.\n\
+    ///            ,Thief,2023-12-31,Tangerine Dream score.\n";
+    /// let notes = ReviewNotes::parse(csv.as_bytes()).unwrap();
+    ///
+    /// let heat = ExportRow {
+    ///     title: "Heat".to_string(),
+    ///     imdb_id: "tt0113277".to_string(),
+    ///     watched_date: "2024-01-15".to_string(),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(notes.find(&heat), Some("The bank heist still holds up."));
+    ///
+    /// let thief = ExportRow {
+    ///     title: "Thief".to_string(),
+    ///     imdb_id: "tt0083190".to_string(),
+    ///     watched_date: "2024-02-01".to_string(),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(notes.find(&thief), None);
+    /// ```
+    pub fn parse(reader: impl std::io::Read) -> Result<Self> {
+        let notes = csv::Reader::from_reader(reader)
+            .deserialize()
+            .collect::<Result<_, _>>()
+            .context("Invalid review notes")?;
+        Ok(Self { notes })
+    }
+
+    /// Reads a notes CSV file from disk
+    pub fn read(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = std::fs::File::open(path)
+            .with_context(|| format!("Failed to open review notes: {}", path.display()))?;
+        Self::parse(file).with_context(|| format!("Invalid review notes: {}", path.display()))
+    }
+
+    /// Returns the review of a row, from the first note about its viewing
+    pub fn find(&self, row: &ExportRow) -> Option<&str> {
+        self.notes
+            .iter()
+            .find(|note| note.matches(row))
+            .map(|note| note.review.as_str())
+    }
+}
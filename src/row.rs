@@ -0,0 +1,389 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Tags added to every exported row, unless the row has tags of its own
+pub const TAGS: &str = "\"Imported from Plex\"";
+
+/// A single row of the Letterboxd CSV file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportRow {
+    /// Title of the film, with edition markers removed
+    pub title: String,
+    /// IMDb identifier (e.g. "tt1234567")
+    pub imdb_id: String,
+    /// Date the film was watched (YYYY-MM-DD)
+    pub watched_date: String,
+    /// Time the film was watched (HH:MM:SS, UTC), when the source records it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub watched_time: Option<String>,
+    /// Release year of the film
+    #[serde(default)]
+    pub year: Option<u32>,
+    /// Plex user rating on a 0-10 scale
+    #[serde(default)]
+    pub rating: Option<f32>,
+    /// Directors of the film
+    #[serde(default)]
+    pub directors: Vec<String>,
+    /// Whether the entry is marked as liked
+    #[serde(default)]
+    pub liked: bool,
+    /// Review of the viewing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub review: Option<String>,
+    /// Letterboxd tags of the entry, written instead of "Imported from Plex" when set
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Runtime of the film, for `stats`; not part of any output
+    #[serde(skip)]
+    pub runtime: Option<Duration>,
+    /// TMDb ID of the film (e.g. "949"), written to the tmdbID column and
+    /// used to recognize the film across library versions
+    #[serde(skip)]
+    pub tmdb_id: Option<String>,
+}
+
+impl ExportRow {
+    /// Returns the IDs the film is known by: the imdbID column when set, and "tmdb://<id>" when the TMDb ID is known
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use plex_to_letterboxd::row::ExportRow;
+    ///
+    /// let row = ExportRow {
+    ///     imdb_id: "tt0113277".to_string(),
+    ///     tmdb_id: Some("949".to_string()),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(row.film_ids(), ["tt0113277", "tmdb://949"]);
+    /// ```
+    pub fn film_ids(&self) -> Vec<String> {
+        let mut ids = Vec::new();
+        if !self.imdb_id.is_empty() {
+            ids.push(self.imdb_id.clone());
+        }
+        if let Some(tmdb_id) = &self.tmdb_id {
+            let id = format!("tmdb://{}", tmdb_id);
+            if id != self.imdb_id {
+                ids.push(id);
+            }
+        }
+        ids
+    }
+
+    /// Sets the WatchedDate and watch time from when the film was watched
+    ///
+    /// Both are formatted in UTC; an unknown watch time leaves them empty.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use chrono::{TimeZone, Utc};
+    /// use plex_to_letterboxd::row::ExportRow;
+    ///
+    /// let watched_at = Utc.with_ymd_and_hms(2024, 1, 15, 20, 31, 7).unwrap();
+    /// let row = ExportRow::default().with_watched_at(Some(watched_at));
+    /// assert_eq!(row.watched_date, "2024-01-15");
+    /// assert_eq!(row.watched_time.as_deref(), Some("20:31:07"));
+    /// ```
+    pub fn with_watched_at(mut self, watched_at: Option<DateTime<Utc>>) -> Self {
+        self.watched_date = watched_at
+            .map(|at| at.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+        self.watched_time = watched_at.map(|at| at.format("%H:%M:%S").to_string());
+        self
+    }
+
+    /// Returns when the film was watched as an ISO 8601 timestamp in UTC
+    ///
+    /// Viewings whose source only records the date are placed at midnight;
+    /// undated viewings return `None`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use plex_to_letterboxd::row::ExportRow;
+    ///
+    /// let mut row = ExportRow {
+    ///     watched_date: "2024-01-15".to_string(),
+    ///     watched_time: Some("20:31:07".to_string()),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(row.watched_at().as_deref(), Some("2024-01-15T20:31:07Z"));
+    ///
+    /// row.watched_time = None;
+    /// assert_eq!(row.watched_at().as_deref(), Some("2024-01-15T00:00:00Z"));
+    /// ```
+    pub fn watched_at(&self) -> Option<String> {
+        if self.watched_date.is_empty() {
+            return None;
+        }
+        let time = self.watched_time.as_deref().unwrap_or("00:00:00");
+        Some(format!("{}T{}Z", self.watched_date, time))
+    }
+
+    /// Compares rows in output order: by WatchedDate, then Title, then imdbID
+    ///
+    /// Exported files are sorted this way regardless of the order the server
+    /// returned the history in, so repeated exports can be diffed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use plex_to_letterboxd::row::ExportRow;
+    ///
+    /// let row = |title: &str, imdb_id: &str, watched_date: &str| ExportRow {
+    ///     title: title.to_string(),
+    ///     imdb_id: imdb_id.to_string(),
+    ///     watched_date: watched_date.to_string(),
+    ///     ..Default::default()
+    /// };
+    /// let mut rows = vec![
+    ///     row("Ronin", "tt0122690", "2024-01-15"),
+    ///     row("Heat", "tt0113277", "2024-01-15"),
+    ///     row("Thief", "tt0083190", "2023-12-31"),
+    /// ];
+    /// rows.sort_by(ExportRow::output_order);
+    /// let titles: Vec<_> = rows.iter().map(|r| r.title.as_str()).collect();
+    /// assert_eq!(titles, ["Thief", "Heat", "Ronin"]);
+    /// ```
+    pub fn output_order(&self, other: &Self) -> Ordering {
+        (&self.watched_date, &self.title, &self.imdb_id).cmp(&(
+            &other.watched_date,
+            &other.title,
+            &other.imdb_id,
+        ))
+    }
+
+    /// Returns a copy of the row with its free text replaced by hashes
+    ///
+    /// The title, directors and review are each replaced with a short hash,
+    /// so the row no longer reveals what was watched in plain text, while
+    /// IDs, dates, ratings and the structure of the export are kept. Equal
+    /// texts hash to the same value, so rewatches of a film still share a title.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use plex_to_letterboxd::row::ExportRow;
+    ///
+    /// let row = ExportRow {
+    ///     title: "Heat".to_string(),
+    ///     imdb_id: "tt0113277".to_string(),
+    ///     watched_date: "2024-01-15".to_string(),
+    ///     ..Default::default()
+    /// };
+    /// let anonymized = row.anonymized();
+    /// assert_eq!(anonymized.title, "title-a29711b3");
+    /// assert_eq!(anonymized.imdb_id, "tt0113277");
+    /// assert_eq!(anonymized.watched_date, "2024-01-15");
+    /// ```
+    pub fn anonymized(&self) -> Self {
+        Self {
+            title: text_hash("title", &self.title),
+            directors: self
+                .directors
+                .iter()
+                .map(|director| text_hash("director", director))
+                .collect(),
+            review: self
+                .review
+                .as_ref()
+                .map(|review| text_hash("review", review)),
+            ..self.clone()
+        }
+    }
+}
+
+/// Returns a short, stable hash of a text, prefixed with what the text was
+///
+/// Uses 32-bit FNV-1a, which unlike the standard library's hasher gives the
+/// same result with every Rust version, so anonymized exports are stable.
+fn text_hash(kind: &str, text: &str) -> String {
+    let hash = text.bytes().fold(0x811c_9dc5_u32, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    });
+    format!("{}-{:08x}", kind, hash)
+}
+
+/// A column of the Letterboxd CSV file
+///
+/// Column names match the headers understood by Letterboxd's importer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Column {
+    /// Title of the film
+    Title,
+    /// Release year of the film
+    Year,
+    /// IMDb identifier (e.g. "tt1234567")
+    ImdbId,
+    /// TMDb identifier (e.g. "949"), used by Letterboxd for films without an IMDb ID
+    TmdbId,
+    /// Directors of the film, comma separated
+    Directors,
+    /// Date the film was watched (YYYY-MM-DD)
+    WatchedDate,
+    /// When the film was watched, as an ISO 8601 timestamp in UTC
+    WatchedAt,
+    /// Rating on Letterboxd's 0.5-5 star scale
+    Rating,
+    /// Rating on a 1-10 scale
+    Rating10,
+    /// Tags for the entry ("Imported from Plex" unless the row has its own)
+    Tags,
+    /// "true" when the entry is liked
+    Liked,
+    /// Review text of the entry
+    Review,
+}
+
+impl Column {
+    /// All columns, in the order they are listed in help messages
+    pub const ALL: [Column; 12] = [
+        Column::Title,
+        Column::Year,
+        Column::ImdbId,
+        Column::TmdbId,
+        Column::Directors,
+        Column::WatchedDate,
+        Column::WatchedAt,
+        Column::Rating,
+        Column::Rating10,
+        Column::Tags,
+        Column::Liked,
+        Column::Review,
+    ];
+
+    /// Returns the CSV header of the column
+    pub fn header(&self) -> &'static str {
+        match self {
+            Column::Title => "Title",
+            Column::Year => "Year",
+            Column::ImdbId => "imdbID",
+            Column::TmdbId => "tmdbID",
+            Column::Directors => "Directors",
+            Column::WatchedDate => "WatchedDate",
+            Column::WatchedAt => "WatchedAt",
+            Column::Rating => "Rating",
+            Column::Rating10 => "Rating10",
+            Column::Tags => "Tags",
+            Column::Liked => "Liked",
+            Column::Review => "Review",
+        }
+    }
+
+    /// Returns the value of the column for a row
+    pub fn value(&self, row: &ExportRow) -> String {
+        match self {
+            Column::Title => row.title.clone(),
+            Column::Year => row.year.map(|y| y.to_string()).unwrap_or_default(),
+            Column::ImdbId => row.imdb_id.clone(),
+            Column::TmdbId => row.tmdb_id.clone().unwrap_or_default(),
+            Column::Directors => row.directors.join(", "),
+            Column::WatchedDate => row.watched_date.clone(),
+            Column::WatchedAt => row.watched_at().unwrap_or_default(),
+            // Letterboxd ratings go from 0.5 to 5 stars in half-star steps
+            Column::Rating => row
+                .rating
+                .filter(|r| *r > 0.0)
+                .map(|r| ((r.round() / 2.0).max(0.5)).to_string())
+                .unwrap_or_default(),
+            Column::Rating10 => row
+                .rating
+                .filter(|r| *r > 0.0)
+                .map(|r| (r.round().max(1.0) as u32).to_string())
+                .unwrap_or_default(),
+            Column::Tags if row.tags.is_empty() => TAGS.to_string(),
+            Column::Tags => row
+                .tags
+                .iter()
+                .map(|tag| format!("\"{}\"", tag))
+                .collect::<Vec<_>>()
+                .join(", "),
+            Column::Liked => if row.liked { "true" } else { "" }.to_string(),
+            Column::Review => row.review.clone().unwrap_or_default(),
+        }
+    }
+
+    /// Returns the default columns (Title, imdbID, WatchedDate, Tags)
+    pub fn defaults() -> Vec<Column> {
+        vec![
+            Column::Title,
+            Column::ImdbId,
+            Column::WatchedDate,
+            Column::Tags,
+        ]
+    }
+
+    /// Returns the values of the given columns for a row, in order
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use plex_to_letterboxd::row::{Column, ExportRow};
+    ///
+    /// let row = ExportRow {
+    ///     title: "Heat".to_string(),
+    ///     imdb_id: "tt0113277".to_string(),
+    ///     watched_date: "2024-01-15".to_string(),
+    ///     watched_time: Some("20:31:07".to_string()),
+    ///     year: Some(1995),
+    ///     rating: Some(9.0),
+    ///     directors: vec!["Michael Mann".to_string()],
+    ///     liked: true,
+    ///     review: None,
+    ///     tags: Vec::new(),
+    ///     runtime: None,
+    ///     tmdb_id: None,
+    /// };
+    /// let columns = [Column::Title, Column::Year, Column::Rating10, Column::Liked];
+    /// assert_eq!(Column::record(&columns, &row), ["Heat", "1995", "9", "true"]);
+    /// ```
+    pub fn record(columns: &[Column], row: &ExportRow) -> Vec<String> {
+        columns.iter().map(|column| column.value(row)).collect()
+    }
+}
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.header())
+    }
+}
+
+impl FromStr for Column {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Column::ALL
+            .into_iter()
+            .find(|column| column.header().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                format!(
+                    "Unknown column '{}'. Available columns: {}",
+                    s,
+                    Column::ALL.map(|c| c.header()).join(", ")
+                )
+            })
+    }
+}
+
+impl TryFrom<String> for Column {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Column> for String {
+    fn from(column: Column) -> Self {
+        column.header().to_string()
+    }
+}
@@ -0,0 +1,199 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Duration, TimeZone, Timelike};
+
+/// Values allowed in one field of a cron expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CronField {
+    /// Whether each value (indexed from 0) is allowed
+    allowed: Vec<bool>,
+    /// Whether the field was "*" (matters for the day-of-month/day-of-week rule)
+    any: bool,
+}
+
+impl CronField {
+    /// Parses a field such as "*", "5", "1-5", "*/15", "0,30" or "9-17/2"
+    fn parse(field: &str, min: u32, max: u32) -> Result<Self, String> {
+        let mut allowed = vec![false; max as usize + 1];
+
+        for part in field.split(',') {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => {
+                    let step: u32 = step
+                        .parse()
+                        .map_err(|_| format!("Invalid step '{}' in '{}'", step, field))?;
+                    if step == 0 {
+                        return Err(format!("Step cannot be 0 in '{}'", field));
+                    }
+                    (range, step)
+                }
+                None => (part, 1),
+            };
+
+            let parse_value = |value: &str| -> Result<u32, String> {
+                value
+                    .parse()
+                    .ok()
+                    .filter(|v| (min..=max).contains(v))
+                    .ok_or_else(|| format!("Invalid value '{}' (expected {}-{})", value, min, max))
+            };
+            let (start, end) = match range {
+                "*" => (min, max),
+                _ => match range.split_once('-') {
+                    Some((start, end)) => (parse_value(start)?, parse_value(end)?),
+                    // "5/10" means every 10 starting at 5
+                    None if step > 1 => (parse_value(range)?, max),
+                    None => {
+                        let value = parse_value(range)?;
+                        (value, value)
+                    }
+                },
+            };
+            if start > end {
+                return Err(format!("Invalid range '{}'", range));
+            }
+
+            for value in (start..=end).step_by(step as usize) {
+                allowed[value as usize] = true;
+            }
+        }
+
+        Ok(Self {
+            allowed,
+            any: field == "*",
+        })
+    }
+
+    /// Returns whether a value is allowed
+    fn matches(&self, value: u32) -> bool {
+        self.allowed.get(value as usize).copied().unwrap_or(false)
+    }
+}
+
+/// A standard five-field cron expression (minute hour day-of-month month day-of-week)
+///
+/// Supports `*`, lists (`1,15`), ranges (`1-5`) and steps (`*/15`). Day of
+/// week goes from 0 (Sunday) to 7 (Sunday again). As in cron, when both the day of
+/// month and the day of week are restricted, a day matching either runs.
+///
+/// # Example
+///
+/// ```rust
+/// use chrono::{TimeZone, Utc};
+/// use plex_to_letterboxd::schedule::Schedule;
+///
+/// let schedule: Schedule = "0 4 * * *".parse().unwrap();
+/// let now = Utc.with_ymd_and_hms(2024, 1, 15, 12, 30, 0).unwrap();
+/// assert_eq!(
+///     schedule.next_after(&now),
+///     Some(Utc.with_ymd_and_hms(2024, 1, 16, 4, 0, 0).unwrap())
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    /// Minutes (0-59)
+    minute: CronField,
+    /// Hours (0-23)
+    hour: CronField,
+    /// Days of the month (1-31)
+    day_of_month: CronField,
+    /// Months (1-12)
+    month: CronField,
+    /// Days of the week (0-7, Sunday is 0 and 7)
+    day_of_week: CronField,
+}
+
+impl Schedule {
+    /// Returns whether the schedule runs on a given day
+    fn matches_day(&self, time: &impl Datelike) -> bool {
+        let day_of_month = self.day_of_month.matches(time.day());
+        let weekday = time.weekday().num_days_from_sunday();
+        let day_of_week =
+            self.day_of_week.matches(weekday) || (weekday == 0 && self.day_of_week.matches(7));
+
+        match (self.day_of_month.any, self.day_of_week.any) {
+            (true, true) => true,
+            (true, false) => day_of_week,
+            (false, true) => day_of_month,
+            (false, false) => day_of_month || day_of_week,
+        }
+    }
+
+    /// Returns the first time strictly after `after` at which the schedule runs
+    ///
+    /// The schedule is matched against the wall-clock time of `after`'s time
+    /// zone. Times skipped by a daylight saving change never run, and times
+    /// repeated by one run the first time around.
+    ///
+    /// Returns `None` for expressions that never run (e.g. "0 0 31 2 *").
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use chrono::{Local, TimeZone};
+    /// use plex_to_letterboxd::schedule::Schedule;
+    ///
+    /// // Clocks go back at 02:00 on 2024-11-03 and forward at 02:00 on 2025-03-09
+    /// std::env::set_var("TZ", "America/New_York");
+    /// let at = |y, mo, d, h, mi| Local.with_ymd_and_hms(y, mo, d, h, mi, 0).earliest().unwrap();
+    ///
+    /// let daily: Schedule = "0 4 * * *".parse().unwrap();
+    /// assert_eq!(daily.next_after(&at(2024, 11, 2, 4, 0)), Some(at(2024, 11, 3, 4, 0)));
+    /// assert_eq!(daily.next_after(&at(2025, 3, 8, 4, 0)), Some(at(2025, 3, 9, 4, 0)));
+    ///
+    /// // 01:30 happens twice in November and 02:30 not at all in March
+    /// let repeated: Schedule = "30 1 * * *".parse().unwrap();
+    /// let first = at(2024, 11, 3, 1, 30);
+    /// assert_eq!(repeated.next_after(&at(2024, 11, 3, 0, 0)), Some(first));
+    /// assert_eq!(repeated.next_after(&first), Some(at(2024, 11, 4, 1, 30)));
+    /// let skipped: Schedule = "30 2 * * *".parse().unwrap();
+    /// assert_eq!(skipped.next_after(&at(2025, 3, 8, 12, 0)), Some(at(2025, 3, 10, 2, 30)));
+    /// ```
+    pub fn next_after<Tz: TimeZone>(&self, after: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let zone = after.timezone();
+        // Start at the next whole minute
+        let start = after.naive_local().with_second(0)?.with_nanosecond(0)?;
+        let mut time = start + Duration::minutes(1);
+
+        // Every schedule that runs at all runs within about four years (leap days)
+        let limit = time + Duration::days(366 * 4 + 1);
+        while time < limit {
+            if !self.month.matches(time.month()) || !self.matches_day(&time) {
+                time = (time.date() + Duration::days(1)).and_hms_opt(0, 0, 0)?;
+            } else if !self.hour.matches(time.hour()) {
+                time = (time + Duration::hours(1)).with_minute(0)?;
+            } else if !self.minute.matches(time.minute()) {
+                time += Duration::minutes(1);
+            } else {
+                // Skip times that don't exist, and the second pass of a repeated hour
+                match zone.from_local_datetime(&time).earliest() {
+                    Some(run) if run > *after => return Some(run),
+                    _ => time += Duration::minutes(1),
+                }
+            }
+        }
+        None
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        let [minute, hour, day_of_month, month, day_of_week] = fields[..] else {
+            return Err(format!(
+                "Expected 5 fields (minute hour day-of-month month day-of-week), got '{}'",
+                s
+            ));
+        };
+
+        Ok(Self {
+            minute: CronField::parse(minute, 0, 59)?,
+            hour: CronField::parse(hour, 0, 23)?,
+            day_of_month: CronField::parse(day_of_month, 1, 31)?,
+            month: CronField::parse(month, 1, 12)?,
+            day_of_week: CronField::parse(day_of_week, 0, 7)?,
+        })
+    }
+}
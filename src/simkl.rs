@@ -0,0 +1,213 @@
+use anyhow::{Context, Result};
+use reqwest::blocking::{Client, RequestBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::row::ExportRow;
+
+/// Base URL of the Simkl API
+const SIMKL_API_URL: &str = "https://api.simkl.com";
+
+/// PIN used to link this application to a Simkl account
+#[derive(Debug, Clone, Deserialize)]
+pub struct SimklPin {
+    /// Code the user enters at `verification_url`, also used to poll for the token
+    pub user_code: String,
+    /// URL the user opens to approve the login
+    pub verification_url: String,
+    /// Seconds until the PIN expires
+    pub expires_in: u64,
+    /// Seconds to wait between polls
+    pub interval: u64,
+}
+
+/// Response of polling a PIN
+#[derive(Debug, Clone, Deserialize)]
+struct SimklPinStatus {
+    /// "OK" once approved, "KO" while pending
+    result: String,
+    /// Access token, set once approved
+    access_token: Option<String>,
+}
+
+/// IDs identifying a movie on Simkl
+#[derive(Debug, Clone, Serialize)]
+pub struct SimklIds {
+    /// IMDb ID (e.g., "tt0113277"), left out for films without one
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub imdb: String,
+    /// TMDb ID (e.g., 949), if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tmdb: Option<u64>,
+}
+
+/// A movie viewing sent to `/sync/history`
+#[derive(Debug, Clone, Serialize)]
+pub struct SimklHistoryMovie {
+    /// Movie title
+    pub title: String,
+    /// Release year, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub year: Option<u32>,
+    /// When the movie was watched, as an ISO 8601 timestamp (omitted for undated viewings)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub watched_at: Option<String>,
+    /// IDs used by Simkl to match the movie
+    pub ids: SimklIds,
+}
+
+impl From<&ExportRow> for SimklHistoryMovie {
+    /// Converts an exported row
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use plex_to_letterboxd::row::ExportRow;
+    /// use plex_to_letterboxd::simkl::SimklHistoryMovie;
+    ///
+    /// let row = ExportRow {
+    ///     title: "Heat".to_string(),
+    ///     imdb_id: "tt0113277".to_string(),
+    ///     watched_date: "2024-01-15".to_string(),
+    ///     ..Default::default()
+    /// };
+    /// let movie = SimklHistoryMovie::from(&row);
+    /// assert_eq!(movie.watched_at.as_deref(), Some("2024-01-15T00:00:00Z"));
+    /// ```
+    fn from(row: &ExportRow) -> Self {
+        Self {
+            title: row.title.clone(),
+            year: row.year,
+            watched_at: (!row.watched_date.is_empty())
+                .then(|| format!("{}T00:00:00Z", row.watched_date)),
+            ids: SimklIds {
+                imdb: row.imdb_id.clone(),
+                tmdb: row.tmdb_id.as_deref().and_then(|id| id.parse().ok()),
+            },
+        }
+    }
+}
+
+/// Counts returned by `/sync/history`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SimklSyncCounts {
+    /// Number of movies added
+    #[serde(default)]
+    pub movies: u64,
+}
+
+/// Items Simkl could not match
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SimklNotFound {
+    /// Movies that were not found
+    #[serde(default)]
+    pub movies: Vec<serde_json::Value>,
+}
+
+/// Response of `/sync/history`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SimklSyncResponse {
+    /// Viewings added to the history
+    #[serde(default)]
+    pub added: SimklSyncCounts,
+    /// Items Simkl could not match
+    #[serde(default)]
+    pub not_found: SimklNotFound,
+}
+
+/// Client for the Simkl API
+///
+/// Logs in with Simkl's PIN flow and adds viewings to the user's watched history.
+pub struct SimklClient {
+    /// Client ID of the Simkl API application
+    client_id: String,
+    /// OAuth access token, required for `add_history`
+    access_token: Option<String>,
+    /// HTTP client for making requests
+    client: Client,
+}
+
+impl SimklClient {
+    /// Creates a new SimklClient
+    ///
+    /// # Arguments
+    ///
+    /// * `client_id` - Client ID of a Simkl API application (https://simkl.com/settings/developer/)
+    pub fn new(client_id: String) -> Self {
+        Self {
+            client_id,
+            access_token: None,
+            client: Client::new(),
+        }
+    }
+
+    /// Sets the OAuth access token used for authenticated requests
+    pub fn with_access_token(mut self, access_token: impl Into<String>) -> Self {
+        self.access_token = Some(access_token.into());
+        self
+    }
+
+    /// Starts a PIN login
+    ///
+    /// The user approves the login by entering `user_code` at `verification_url`,
+    /// after which `poll_pin` returns the access token.
+    pub fn create_pin(&self) -> Result<SimklPin> {
+        let request = self
+            .client
+            .get(format!("{}/oauth/pin", SIMKL_API_URL))
+            .query(&[("client_id", &self.client_id)]);
+
+        self.request(request)
+            .send()
+            .context("Failed to send request to Simkl")?
+            .error_for_status()
+            .context("Failed to create Simkl PIN")?
+            .json()
+            .context("Failed to parse response from Simkl")
+    }
+
+    /// Checks a PIN, returning `None` while the login is still pending
+    pub fn poll_pin(&self, pin: &SimklPin) -> Result<Option<String>> {
+        let request = self
+            .client
+            .get(format!("{}/oauth/pin/{}", SIMKL_API_URL, pin.user_code))
+            .query(&[("client_id", &self.client_id)]);
+
+        let status: SimklPinStatus = self
+            .request(request)
+            .send()
+            .context("Failed to send request to Simkl")?
+            .error_for_status()
+            .context("Failed to check Simkl PIN")?
+            .json()
+            .context("Failed to parse response from Simkl")?;
+
+        Ok(status.access_token.filter(|_| status.result == "OK"))
+    }
+
+    /// Adds movie viewings to the user's watched history
+    pub fn add_history(&self, movies: &[SimklHistoryMovie]) -> Result<SimklSyncResponse> {
+        let access_token = self
+            .access_token
+            .as_deref()
+            .context("A Simkl access token is required to add history")?;
+
+        let request = self
+            .client
+            .post(format!("{}/sync/history", SIMKL_API_URL))
+            .bearer_auth(access_token)
+            .json(&serde_json::json!({ "movies": movies }));
+
+        self.request(request)
+            .send()
+            .context("Failed to send request to Simkl")?
+            .error_for_status()
+            .context("Simkl rejected the watched history")?
+            .json()
+            .context("Failed to parse response from Simkl")
+    }
+
+    /// Adds the headers every Simkl API request requires
+    fn request(&self, request: RequestBuilder) -> RequestBuilder {
+        request.header("simkl-api-key", &self.client_id)
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::time::Duration;
+
+use chrono::{Datelike, NaiveDate};
+
+use crate::row::ExportRow;
+
+/// Widest bar drawn in the charts
+const BAR_WIDTH: usize = 30;
+
+/// Days of the week, in the order of `ViewingStats::per_weekday`
+const WEEKDAYS: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+/// A viewing counted by `ViewingStats`
+#[derive(Debug, Clone, Default)]
+pub struct Viewing {
+    /// Title of the film
+    pub title: String,
+    /// IMDb identifier, which tells rewatches apart from films sharing a title
+    pub imdb_id: Option<String>,
+    /// Release year of the film
+    pub year: Option<u32>,
+    /// Date the film was watched, if known
+    pub watched_date: Option<NaiveDate>,
+    /// Runtime of the film, if known
+    pub runtime: Option<Duration>,
+}
+
+impl From<&ExportRow> for Viewing {
+    fn from(row: &ExportRow) -> Self {
+        Self {
+            title: row.title.clone(),
+            imdb_id: Some(row.imdb_id.clone()).filter(|id| !id.is_empty()),
+            year: row.year,
+            watched_date: NaiveDate::parse_from_str(&row.watched_date, "%Y-%m-%d").ok(),
+            runtime: row.runtime,
+        }
+    }
+}
+
+/// Analytics of a watch history
+///
+/// # Example
+///
+/// ```rust
+/// use chrono::NaiveDate;
+/// use plex_to_letterboxd::stats::{Viewing, ViewingStats};
+///
+/// let viewing = |title: &str, date: &str| Viewing {
+///     title: title.to_string(),
+///     watched_date: NaiveDate::parse_from_str(date, "%Y-%m-%d").ok(),
+///     runtime: Some(std::time::Duration::from_secs(90 * 60)),
+///     ..Default::default()
+/// };
+/// let stats = ViewingStats::new(
+///     &[
+///         viewing("Heat", "2024-01-13"),
+///         viewing("Heat", "2024-02-10"),
+///         viewing("Thief", "2024-02-10"),
+///     ],
+///     10,
+/// );
+/// assert_eq!(stats.films, 2);
+/// assert_eq!(stats.per_month[&(2024, 2)], 2);
+/// assert_eq!(stats.most_rewatched[0], ("Heat".to_string(), 2));
+/// assert_eq!(stats.busiest_days[0].1, 2);
+/// assert_eq!(stats.runtime.as_secs(), 3 * 90 * 60);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ViewingStats {
+    /// Number of viewings
+    pub viewings: usize,
+    /// Number of different films watched
+    pub films: usize,
+    /// Earliest and latest watch dates
+    pub date_range: Option<(NaiveDate, NaiveDate)>,
+    /// Viewings per year
+    pub per_year: BTreeMap<i32, usize>,
+    /// Viewings per (year, month)
+    pub per_month: BTreeMap<(i32, u32), usize>,
+    /// Films watched more than once, with their viewings, most first
+    pub most_rewatched: Vec<(String, usize)>,
+    /// Dates with more than one viewing, most first
+    pub busiest_days: Vec<(NaiveDate, usize)>,
+    /// Viewings per day of the week, from Monday
+    pub per_weekday: [usize; 7],
+    /// Total runtime of the viewings with a known runtime
+    pub runtime: Duration,
+    /// Viewings whose runtime is unknown
+    pub without_runtime: usize,
+}
+
+impl ViewingStats {
+    /// Computes the analytics of some viewings, keeping `top` rewatched films and busiest days
+    pub fn new(viewings: &[Viewing], top: usize) -> Self {
+        let mut stats = Self {
+            viewings: viewings.len(),
+            ..Default::default()
+        };
+        let mut per_film: HashMap<String, (String, usize)> = HashMap::new();
+        let mut per_day: HashMap<NaiveDate, usize> = HashMap::new();
+
+        for viewing in viewings {
+            let key = viewing
+                .imdb_id
+                .clone()
+                .unwrap_or_else(|| viewing.title.to_lowercase());
+            let name = match viewing.year {
+                Some(year) => format!("{} ({})", viewing.title, year),
+                None => viewing.title.clone(),
+            };
+            per_film.entry(key).or_insert((name, 0)).1 += 1;
+
+            match viewing.runtime {
+                Some(runtime) => stats.runtime += runtime,
+                None => stats.without_runtime += 1,
+            }
+
+            let Some(date) = viewing.watched_date else {
+                continue;
+            };
+            *stats.per_year.entry(date.year()).or_default() += 1;
+            *stats
+                .per_month
+                .entry((date.year(), date.month()))
+                .or_default() += 1;
+            *per_day.entry(date).or_default() += 1;
+            stats.per_weekday[date.weekday().num_days_from_monday() as usize] += 1;
+            stats.date_range = Some(match stats.date_range {
+                Some((first, last)) => (first.min(date), last.max(date)),
+                None => (date, date),
+            });
+        }
+
+        stats.films = per_film.len();
+        stats.most_rewatched = per_film
+            .into_values()
+            .filter(|(_, count)| *count > 1)
+            .collect();
+        stats
+            .most_rewatched
+            .sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        stats.most_rewatched.truncate(top);
+
+        stats.busiest_days = per_day
+            .into_iter()
+            .filter(|(_, count)| *count > 1)
+            .collect();
+        stats
+            .busiest_days
+            .sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        stats.busiest_days.truncate(top);
+
+        stats
+    }
+}
+
+impl fmt::Display for ViewingStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Viewings: {} ({} films)", self.viewings, self.films)?;
+        if let Some((first, last)) = self.date_range {
+            write!(f, ", {} to {}", first, last)?;
+        }
+        writeln!(f)?;
+        write!(f, "Runtime watched: {}", format_runtime(self.runtime))?;
+        if self.without_runtime > 0 {
+            write!(f, " ({} viewings without a runtime)", self.without_runtime)?;
+        }
+        writeln!(f)?;
+
+        if !self.per_year.is_empty() {
+            writeln!(f, "\nFilms per year:")?;
+            write_bars(f, self.per_year.iter().map(|(y, n)| (y.to_string(), *n)))?;
+        }
+        if !self.per_month.is_empty() {
+            writeln!(f, "\nFilms per month:")?;
+            let months = self
+                .per_month
+                .iter()
+                .map(|((year, month), n)| (format!("{}-{:02}", year, month), *n));
+            write_bars(f, months)?;
+        }
+        if !self.most_rewatched.is_empty() {
+            writeln!(f, "\nMost rewatched:")?;
+            for (name, count) in &self.most_rewatched {
+                writeln!(f, "  {:>3}×  {}", count, name)?;
+            }
+        }
+        if !self.busiest_days.is_empty() {
+            writeln!(f, "\nBusiest days:")?;
+            for (date, count) in &self.busiest_days {
+                writeln!(f, "  {} ({})  {}", date, date.format("%a"), count)?;
+            }
+        }
+        if self.per_weekday.iter().any(|n| *n > 0) {
+            writeln!(f, "\nViewings per weekday:")?;
+            let weekdays = WEEKDAYS
+                .iter()
+                .zip(self.per_weekday)
+                .map(|(weekday, n)| (weekday.to_string(), n));
+            write_bars(f, weekdays)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes labelled counts with bars scaled to the largest count
+fn write_bars(
+    f: &mut fmt::Formatter<'_>,
+    counts: impl Iterator<Item = (String, usize)> + Clone,
+) -> fmt::Result {
+    let max = counts.clone().map(|(_, n)| n).max().unwrap_or(0).max(1);
+    let width = counts
+        .clone()
+        .map(|(label, _)| label.len())
+        .max()
+        .unwrap_or(0);
+    for (label, n) in counts {
+        let bar = "█".repeat((n * BAR_WIDTH).div_ceil(max));
+        let line = format!("  {:<width$}  {:>4}  {}", label, n, bar, width = width);
+        writeln!(f, "{}", line.trim_end())?;
+    }
+    Ok(())
+}
+
+/// Formats a runtime as days, hours and minutes (e.g. "2 d 4 h 12 min")
+fn format_runtime(runtime: Duration) -> String {
+    let minutes = runtime.as_secs() / 60;
+    let (days, hours, minutes) = (minutes / (24 * 60), minutes / 60 % 24, minutes % 60);
+    match (days, hours) {
+        (0, 0) => format!("{} min", minutes),
+        (0, _) => format!("{} h {} min", hours, minutes),
+        _ => format!("{} d {} h {} min", days, hours, minutes),
+    }
+}
@@ -0,0 +1,27 @@
+use std::fmt::Display;
+
+use anstyle::{AnsiColor, Style};
+
+/// Style of exported rows and successful steps
+pub const SUCCESS: Style = AnsiColor::Green.on_default();
+/// Style of skipped items and warnings
+pub const WARNING: Style = AnsiColor::Yellow.on_default();
+/// Style of errors and failed steps
+pub const ERROR: Style = AnsiColor::Red.on_default();
+
+/// Wraps text in the ANSI codes of a style
+///
+/// Styled text must be printed with `anstream::println!` (or `eprintln!`),
+/// which removes the codes when `NO_COLOR` is set or the output is not a
+/// terminal, so logs piped to files stay clean.
+///
+/// # Example
+///
+/// ```rust
+/// use plex_to_letterboxd::style::{paint, SUCCESS};
+///
+/// assert_eq!(paint(SUCCESS, "done"), "\u{1b}[32mdone\u{1b}[0m");
+/// ```
+pub fn paint(style: Style, text: impl Display) -> String {
+    format!("{}{}{:#}", style, text, style)
+}
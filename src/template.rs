@@ -0,0 +1,69 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use handlebars::Handlebars;
+
+use crate::row::ExportRow;
+
+/// Name under which the row template is registered
+const TEMPLATE_NAME: &str = "row";
+
+/// Handlebars template rendering each exported row
+///
+/// The template is rendered once per row with the row's fields in scope
+/// (`{{title}}`, `{{imdb_id}}`, `{{watched_date}}`, `{{year}}`, `{{rating}}`,
+/// `{{directors}}`), and the outputs are
+/// concatenated. This allows arbitrary text outputs such as markdown lists:
+///
+/// ```text
+/// - [{{title}}](https://www.imdb.com/title/{{imdb_id}}/) watched on {{watched_date}}
+/// ```
+#[derive(Clone)]
+pub struct RowTemplate {
+    /// Registry holding the compiled template
+    registry: Handlebars<'static>,
+}
+
+impl RowTemplate {
+    /// Compiles a template from a string
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use plex_to_letterboxd::row::ExportRow;
+    /// use plex_to_letterboxd::template::RowTemplate;
+    ///
+    /// let template = RowTemplate::new("* {{title}} ({{watched_date}})\n").unwrap();
+    /// let row = ExportRow {
+    ///     title: "Heat".to_string(),
+    ///     imdb_id: "tt0113277".to_string(),
+    ///     watched_date: "2024-01-15".to_string(),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(template.render(&row).unwrap(), "* Heat (2024-01-15)\n");
+    /// ```
+    pub fn new(template: &str) -> Result<Self> {
+        let mut registry = Handlebars::new();
+        // Output is plain text, not HTML
+        registry.register_escape_fn(handlebars::no_escape);
+        registry
+            .register_template_string(TEMPLATE_NAME, template)
+            .context("Failed to compile template")?;
+        Ok(Self { registry })
+    }
+
+    /// Compiles a template from a file (e.g. "diary.hbs")
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let template = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read template: {}", path.display()))?;
+        Self::new(&template).with_context(|| format!("Invalid template: {}", path.display()))
+    }
+
+    /// Renders the template for a single row
+    pub fn render(&self, row: &ExportRow) -> Result<String> {
+        self.registry
+            .render(TEMPLATE_NAME, row)
+            .with_context(|| format!("Failed to render template for row: {}", row.title))
+    }
+}
@@ -0,0 +1,185 @@
+/// Keywords that mark a trailing parenthetical/bracketed group as edition information
+const EDITION_KEYWORDS: &[&str] = &[
+    "cut",
+    "edition",
+    "extended",
+    "remastered",
+    "restored",
+    "theatrical",
+    "unrated",
+    "uncut",
+    "version",
+    "imax",
+    "anniversary",
+];
+
+/// A media item title split into the clean title and its edition marker
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NormalizedTitle {
+    /// Title with any edition markers removed (e.g. "Blade Runner")
+    pub title: String,
+    /// Edition information extracted from the title (e.g. "Final Cut")
+    pub edition: Option<String>,
+}
+
+/// Splits edition markers out of a Plex title
+///
+/// Plex titles can carry edition information, either as a Plex edition tag
+/// (`{edition-Final Cut}`) or as a trailing parenthetical/bracketed group
+/// (`(Director's Cut)`, `[Extended Edition]`). These markers break Letterboxd's
+/// title matching, so they are moved into a separate `edition` field.
+///
+/// Trailing groups are only treated as editions when they contain an edition
+/// keyword, so titles like "Solaris (1972)" are left untouched.
+///
+/// # Example
+///
+/// ```rust
+/// use plex_to_letterboxd::title::normalize_title;
+///
+/// let normalized = normalize_title("Blade Runner {edition-Final Cut}");
+/// assert_eq!(normalized.title, "Blade Runner");
+/// assert_eq!(normalized.edition.as_deref(), Some("Final Cut"));
+///
+/// let normalized = normalize_title("Aliens (Director's Cut)");
+/// assert_eq!(normalized.title, "Aliens");
+/// assert_eq!(normalized.edition.as_deref(), Some("Director's Cut"));
+/// ```
+pub fn normalize_title(title: &str) -> NormalizedTitle {
+    let mut clean = title.trim().to_string();
+    let mut editions = Vec::new();
+
+    // Plex edition tags can appear anywhere in the title
+    while let Some(start) = clean.find("{edition-") {
+        let Some(len) = clean[start..].find('}') else {
+            break;
+        };
+        let edition = clean[start + "{edition-".len()..start + len]
+            .trim()
+            .to_string();
+        if !edition.is_empty() {
+            editions.push(edition);
+        }
+        clean.replace_range(start..start + len + 1, "");
+        clean = clean.split_whitespace().collect::<Vec<_>>().join(" ");
+    }
+
+    // Trailing "(...)" or "[...]" groups mentioning an edition keyword
+    while let Some((open, close)) = match clean.chars().last() {
+        Some(')') => Some(('(', ')')),
+        Some(']') => Some(('[', ']')),
+        _ => None,
+    } {
+        let Some(start) = clean.rfind(open) else {
+            break;
+        };
+        let inner = clean[start + 1..clean.len() - close.len_utf8()].trim();
+        let lowercase = inner.to_lowercase();
+        let is_edition = lowercase
+            .split(|c: char| !c.is_alphanumeric())
+            .any(|word| EDITION_KEYWORDS.contains(&word));
+        if !is_edition {
+            break;
+        }
+        editions.insert(0, inner.to_string());
+        clean.truncate(start);
+        clean = clean
+            .trim_end()
+            .trim_end_matches(['-', ':'])
+            .trim_end()
+            .to_string();
+    }
+
+    NormalizedTitle {
+        title: clean,
+        edition: (!editions.is_empty()).then(|| editions.join(", ")),
+    }
+}
+
+impl NormalizedTitle {
+    /// Adds the edition set in Plex (its `editionTitle` field) to the title's edition
+    ///
+    /// Plex keeps editions apart from the title, but titles named before the
+    /// edition was set may still end with it, also without an edition keyword
+    /// (e.g. "Seven Samurai (Criterion)"); such a suffix is removed from the
+    /// title too.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use plex_to_letterboxd::title::normalize_title;
+    ///
+    /// let normalized = normalize_title("Seven Samurai (Criterion)").with_edition_title(Some("Criterion"));
+    /// assert_eq!(normalized.title, "Seven Samurai");
+    /// assert_eq!(normalized.edition.as_deref(), Some("Criterion"));
+    ///
+    /// let normalized = normalize_title("Aliens (Director's Cut)").with_edition_title(Some("Director's Cut"));
+    /// assert_eq!(normalized.title, "Aliens");
+    /// assert_eq!(normalized.edition.as_deref(), Some("Director's Cut"));
+    /// ```
+    pub fn with_edition_title(mut self, edition_title: Option<&str>) -> Self {
+        let Some(edition) = edition_title
+            .map(str::trim)
+            .filter(|edition| !edition.is_empty())
+        else {
+            return self;
+        };
+
+        let suffixes = [
+            format!("({})", edition),
+            format!("[{}]", edition),
+            format!(" - {}", edition),
+            format!(": {}", edition),
+        ];
+        for suffix in &suffixes {
+            let Some(start) = self.title.len().checked_sub(suffix.len()) else {
+                continue;
+            };
+            if start > 0
+                && self.title.is_char_boundary(start)
+                && self.title[start..].eq_ignore_ascii_case(suffix)
+            {
+                self.title.truncate(start);
+                self.title = self.title.trim_end().to_string();
+                break;
+            }
+        }
+
+        let known = self.edition.as_deref().is_some_and(|editions| {
+            editions
+                .split(", ")
+                .any(|known| known.eq_ignore_ascii_case(edition))
+        });
+        if !known {
+            self.edition = Some(match self.edition.take() {
+                Some(editions) => format!("{}, {}", edition, editions),
+                None => edition.to_string(),
+            });
+        }
+        self
+    }
+}
+
+/// Returns whether a title contains every word of a search, ignoring case and punctuation
+///
+/// # Example
+///
+/// ```rust
+/// use plex_to_letterboxd::title::matches_search;
+///
+/// assert!(matches_search("Blade Runner 2049", "blade runner"));
+/// assert!(matches_search("Spider-Man: Into the Spider-Verse", "spider man verse"));
+/// assert!(!matches_search("Blade", "blade runner"));
+/// ```
+pub fn matches_search(title: &str, search: &str) -> bool {
+    let words = |text: &str| -> Vec<String> {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    };
+    let title = words(title).join(" ");
+    words(search)
+        .iter()
+        .all(|word| title.contains(word.as_str()))
+}
@@ -0,0 +1,244 @@
+use anyhow::{Context, Result};
+use reqwest::blocking::{Client, RequestBuilder};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use crate::row::ExportRow;
+
+/// Base URL of the Trakt API
+const TRAKT_API_URL: &str = "https://api.trakt.tv";
+
+/// Version of the Trakt API this client speaks
+const TRAKT_API_VERSION: &str = "2";
+
+/// Code used to link this application to a Trakt account
+#[derive(Debug, Clone, Deserialize)]
+pub struct TraktDeviceCode {
+    /// Code used to poll for the access token
+    pub device_code: String,
+    /// Code the user enters at `verification_url`
+    pub user_code: String,
+    /// URL the user opens to approve the login
+    pub verification_url: String,
+    /// Seconds until the codes expire
+    pub expires_in: u64,
+    /// Seconds to wait between polls
+    pub interval: u64,
+}
+
+/// OAuth tokens returned once the user has approved a device code
+#[derive(Debug, Clone, Deserialize)]
+pub struct TraktToken {
+    /// Token sent as `Authorization: Bearer` on authenticated requests
+    pub access_token: String,
+    /// Token used to obtain a new access token once it expires
+    pub refresh_token: String,
+    /// Seconds until the access token expires
+    pub expires_in: u64,
+}
+
+/// IDs identifying a movie on Trakt
+#[derive(Debug, Clone, Serialize)]
+pub struct TraktIds {
+    /// IMDb ID (e.g., "tt0113277"), left out for films without one
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub imdb: String,
+    /// TMDb ID (e.g., 949), if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tmdb: Option<u64>,
+}
+
+/// A movie viewing sent to `/sync/history`
+#[derive(Debug, Clone, Serialize)]
+pub struct TraktHistoryMovie {
+    /// Movie title
+    pub title: String,
+    /// Release year, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub year: Option<u32>,
+    /// When the movie was watched, as an ISO 8601 timestamp or "released"
+    pub watched_at: String,
+    /// IDs used by Trakt to match the movie
+    pub ids: TraktIds,
+}
+
+impl From<&ExportRow> for TraktHistoryMovie {
+    /// Converts an exported row, using the release date for undated viewings
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use plex_to_letterboxd::row::ExportRow;
+    /// use plex_to_letterboxd::trakt::TraktHistoryMovie;
+    ///
+    /// let row = ExportRow {
+    ///     title: "Heat".to_string(),
+    ///     imdb_id: "tt0113277".to_string(),
+    ///     watched_date: "2024-01-15".to_string(),
+    ///     ..Default::default()
+    /// };
+    /// let movie = TraktHistoryMovie::from(&row);
+    /// assert_eq!(movie.watched_at, "2024-01-15T00:00:00.000Z");
+    /// assert_eq!(movie.ids.imdb, "tt0113277");
+    /// ```
+    fn from(row: &ExportRow) -> Self {
+        let watched_at = if row.watched_date.is_empty() {
+            "released".to_string()
+        } else {
+            format!("{}T00:00:00.000Z", row.watched_date)
+        };
+
+        Self {
+            title: row.title.clone(),
+            year: row.year,
+            watched_at,
+            ids: TraktIds {
+                imdb: row.imdb_id.clone(),
+                tmdb: row.tmdb_id.as_deref().and_then(|id| id.parse().ok()),
+            },
+        }
+    }
+}
+
+/// Counts returned by `/sync/history`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TraktSyncCounts {
+    /// Number of movies added or affected
+    #[serde(default)]
+    pub movies: u64,
+}
+
+/// Items Trakt could not match
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TraktNotFound {
+    /// Movies that were not found
+    #[serde(default)]
+    pub movies: Vec<serde_json::Value>,
+}
+
+/// Response of `/sync/history`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TraktSyncResponse {
+    /// Viewings added to the history
+    #[serde(default)]
+    pub added: TraktSyncCounts,
+    /// Items Trakt could not match
+    #[serde(default)]
+    pub not_found: TraktNotFound,
+}
+
+/// Client for the Trakt API
+///
+/// Logs in with the OAuth device code flow and adds viewings to the user's
+/// watched history.
+pub struct TraktClient {
+    /// Client ID of the Trakt API application
+    client_id: String,
+    /// OAuth access token, required for `add_history`
+    access_token: Option<String>,
+    /// HTTP client for making requests
+    client: Client,
+}
+
+impl TraktClient {
+    /// Creates a new TraktClient
+    ///
+    /// # Arguments
+    ///
+    /// * `client_id` - Client ID of a Trakt API application (https://trakt.tv/oauth/applications)
+    pub fn new(client_id: String) -> Self {
+        Self {
+            client_id,
+            access_token: None,
+            client: Client::new(),
+        }
+    }
+
+    /// Sets the OAuth access token used for authenticated requests
+    pub fn with_access_token(mut self, access_token: impl Into<String>) -> Self {
+        self.access_token = Some(access_token.into());
+        self
+    }
+
+    /// Starts a device code login
+    ///
+    /// The user approves the login by entering `user_code` at `verification_url`,
+    /// after which `poll_device_token` returns the access token.
+    pub fn create_device_code(&self) -> Result<TraktDeviceCode> {
+        let request = self
+            .client
+            .post(format!("{}/oauth/device/code", TRAKT_API_URL))
+            .json(&serde_json::json!({ "client_id": self.client_id }));
+
+        self.request(request)
+            .send()
+            .context("Failed to send request to Trakt")?
+            .error_for_status()
+            .context("Failed to create Trakt device code")?
+            .json()
+            .context("Failed to parse response from Trakt")
+    }
+
+    /// Checks a device code, returning `None` while the login is still pending
+    pub fn poll_device_token(
+        &self,
+        code: &TraktDeviceCode,
+        client_secret: &str,
+    ) -> Result<Option<TraktToken>> {
+        let request = self
+            .client
+            .post(format!("{}/oauth/device/token", TRAKT_API_URL))
+            .json(&serde_json::json!({
+                "code": code.device_code,
+                "client_id": self.client_id,
+                "client_secret": client_secret,
+            }));
+
+        let response = self
+            .request(request)
+            .send()
+            .context("Failed to send request to Trakt")?;
+
+        match response.status() {
+            // Pending, or polling too quickly
+            StatusCode::BAD_REQUEST | StatusCode::TOO_MANY_REQUESTS => Ok(None),
+            StatusCode::GONE => anyhow::bail!("The Trakt device code expired"),
+            StatusCode::IM_A_TEAPOT => anyhow::bail!("The Trakt login was denied"),
+            _ => response
+                .error_for_status()
+                .context("Failed to check Trakt device code")?
+                .json()
+                .map(Some)
+                .context("Failed to parse response from Trakt"),
+        }
+    }
+
+    /// Adds movie viewings to the user's watched history
+    pub fn add_history(&self, movies: &[TraktHistoryMovie]) -> Result<TraktSyncResponse> {
+        let access_token = self
+            .access_token
+            .as_deref()
+            .context("A Trakt access token is required to add history")?;
+
+        let request = self
+            .client
+            .post(format!("{}/sync/history", TRAKT_API_URL))
+            .bearer_auth(access_token)
+            .json(&serde_json::json!({ "movies": movies }));
+
+        self.request(request)
+            .send()
+            .context("Failed to send request to Trakt")?
+            .error_for_status()
+            .context("Trakt rejected the watched history")?
+            .json()
+            .context("Failed to parse response from Trakt")
+    }
+
+    /// Adds the headers every Trakt API request requires
+    fn request(&self, request: RequestBuilder) -> RequestBuilder {
+        request
+            .header("trakt-api-version", TRAKT_API_VERSION)
+            .header("trakt-api-key", &self.client_id)
+    }
+}
@@ -0,0 +1,80 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result};
+
+use crate::row::ExportRow;
+
+/// External command that can modify or drop each exported row
+///
+/// The command receives the row as a JSON object on stdin, e.g.
+/// `{"title":"Heat","imdb_id":"tt0113277","watched_date":"2024-01-15"}`,
+/// and prints the (possibly modified) row as JSON on stdout. Printing nothing
+/// drops the row. The command runs through the platform shell, so it may
+/// include arguments.
+#[derive(Debug, Clone)]
+pub struct MapCommand {
+    /// Command line to run for every row
+    command: String,
+}
+
+impl MapCommand {
+    /// Creates a new MapCommand from a shell command line (e.g. "./my-script --fix-titles")
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+        }
+    }
+
+    /// Runs the command for a row, returning the modified row or `None` if it was dropped
+    pub fn apply(&self, row: &ExportRow) -> Result<Option<ExportRow>> {
+        let input = serde_json::to_vec(row).context("Failed to serialize row")?;
+
+        let mut child = shell(&self.command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Failed to run map command: {}", self.command))?;
+
+        // Write the row and close stdin so the command sees end of input
+        child
+            .stdin
+            .take()
+            .context("Failed to open map command stdin")?
+            .write_all(&input)
+            .context("Failed to write row to map command")?;
+
+        let output = child
+            .wait_with_output()
+            .context("Failed to wait for map command")?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "Map command exited with {} for row: {}",
+                output.status,
+                row.title
+            );
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if stdout.trim().is_empty() {
+            return Ok(None);
+        }
+
+        let row = serde_json::from_str(&stdout)
+            .with_context(|| format!("Map command returned invalid JSON: {}", stdout.trim()))?;
+        Ok(Some(row))
+    }
+}
+
+/// Builds a command that runs a command line through the platform shell
+fn shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    }
+}
@@ -0,0 +1,682 @@
+use std::io;
+
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Cell, Paragraph, Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::row::{Column, ExportRow};
+
+/// Number of rows moved by Page Up and Page Down
+const PAGE_SIZE: usize = 20;
+
+/// Keys listed at the bottom of the review screen
+const REVIEW_HELP: &str =
+    "↑/↓ move  space include/exclude  t title  d date  r rating  w write  q cancel";
+
+/// Keys listed at the bottom of the browse screen
+const BROWSE_HELP: &str =
+    "↑/↓ move  / search  f filter  space select  a/u select/unselect shown  e export  q quit";
+
+/// A field of a row that can be edited during the review
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    /// Title of the film
+    Title,
+    /// Date the film was watched (YYYY-MM-DD, or empty when unknown)
+    WatchedDate,
+    /// Rating in Letterboxd stars (0.5-5 in half-star steps, or empty for none)
+    Rating,
+}
+
+impl Field {
+    /// Returns the name of the field shown while editing it
+    pub fn name(&self) -> &'static str {
+        match self {
+            Field::Title => "Title",
+            Field::WatchedDate => "WatchedDate",
+            Field::Rating => "Rating (stars)",
+        }
+    }
+
+    /// Returns the current value of the field, as it is edited
+    pub fn value(&self, row: &ExportRow) -> String {
+        match self {
+            Field::Title => row.title.clone(),
+            Field::WatchedDate => row.watched_date.clone(),
+            Field::Rating => Column::Rating.value(row),
+        }
+    }
+}
+
+/// Rows pending export, with the changes made while reviewing them
+///
+/// Every row starts out included. The terminal UI of [`review`] is built on
+/// top of this, so the same edits can be made without a terminal.
+///
+/// # Example
+///
+/// ```rust
+/// use plex_to_letterboxd::row::ExportRow;
+/// use plex_to_letterboxd::tui::{Field, RowReview};
+///
+/// let row = |title: &str| ExportRow {
+///     title: title.to_string(),
+///     watched_date: "2024-01-15".to_string(),
+///     ..Default::default()
+/// };
+/// let mut review = RowReview::new(vec![row("Heat"), row("Paw Patrol: The Movie")]);
+///
+/// review.toggle(1);
+/// review.edit(0, Field::Rating, "4.5").unwrap();
+/// assert!(review.edit(0, Field::WatchedDate, "2024-13-01").is_err());
+///
+/// let (included, excluded) = review.into_rows();
+/// assert_eq!(included[0].rating, Some(9.0));
+/// assert_eq!(excluded[0].title, "Paw Patrol: The Movie");
+/// ```
+#[derive(Debug, Clone)]
+pub struct RowReview {
+    /// Rows under review, with any edits applied
+    rows: Vec<ExportRow>,
+    /// Whether each row is still included in the export
+    included: Vec<bool>,
+}
+
+impl RowReview {
+    /// Starts a review of the given rows, all included
+    pub fn new(rows: Vec<ExportRow>) -> Self {
+        let included = vec![true; rows.len()];
+        Self { rows, included }
+    }
+
+    /// Returns the rows under review
+    pub fn rows(&self) -> &[ExportRow] {
+        &self.rows
+    }
+
+    /// Returns whether a row is included in the export
+    pub fn is_included(&self, index: usize) -> bool {
+        self.included[index]
+    }
+
+    /// Returns the number of excluded rows
+    pub fn excluded_count(&self) -> usize {
+        self.included.iter().filter(|included| !**included).count()
+    }
+
+    /// Includes an excluded row, or excludes an included one
+    pub fn toggle(&mut self, index: usize) {
+        self.included[index] = !self.included[index];
+    }
+
+    /// Sets a field of a row, rejecting values Letterboxd would not accept
+    pub fn edit(&mut self, index: usize, field: Field, value: &str) -> Result<()> {
+        let value = value.trim();
+        let row = &mut self.rows[index];
+        match field {
+            Field::Title => {
+                if value.is_empty() {
+                    anyhow::bail!("the title cannot be empty");
+                }
+                row.title = value.to_string();
+            }
+            Field::WatchedDate => {
+                if !value.is_empty() {
+                    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                        .with_context(|| format!("'{}' is not a YYYY-MM-DD date", value))?;
+                }
+                row.watched_date = value.to_string();
+            }
+            Field::Rating => {
+                row.rating = if value.is_empty() {
+                    None
+                } else {
+                    let stars: f32 = value
+                        .parse()
+                        .with_context(|| format!("'{}' is not a number of stars", value))?;
+                    if !(0.5..=5.0).contains(&stars) || (stars * 2.0).fract() != 0.0 {
+                        anyhow::bail!("ratings go from 0.5 to 5 stars in half-star steps");
+                    }
+                    Some(stars * 2.0)
+                };
+            }
+        }
+        Ok(())
+    }
+
+    /// Splits the rows into the included and the excluded ones
+    pub fn into_rows(self) -> (Vec<ExportRow>, Vec<ExportRow>) {
+        let (included, excluded): (Vec<_>, Vec<_>) = self
+            .rows
+            .into_iter()
+            .zip(self.included)
+            .partition(|(_, included)| *included);
+        (
+            included.into_iter().map(|(row, _)| row).collect(),
+            excluded.into_iter().map(|(row, _)| row).collect(),
+        )
+    }
+}
+
+/// A field being edited, with the text typed so far
+struct Editor {
+    /// Field being edited
+    field: Field,
+    /// Text typed so far
+    input: String,
+}
+
+/// State of the review screen
+struct ReviewApp {
+    /// Rows and the changes made to them
+    review: RowReview,
+    /// Selected row and scroll position of the table
+    table: TableState,
+    /// Field being edited, if any
+    editor: Option<Editor>,
+    /// Error of the last edit, shown until the next key press
+    message: Option<String>,
+}
+
+impl ReviewApp {
+    /// Handles key presses until the rows are written (`true`) or the review is cancelled
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<bool> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            self.message = None;
+
+            let selected = self.table.selected().unwrap_or_default();
+            if let Some(editor) = &mut self.editor {
+                match key.code {
+                    KeyCode::Char(c) => editor.input.push(c),
+                    KeyCode::Backspace => {
+                        editor.input.pop();
+                    }
+                    KeyCode::Enter => {
+                        let (field, input) = (editor.field, editor.input.clone());
+                        match self.review.edit(selected, field, &input) {
+                            Ok(()) => self.editor = None,
+                            Err(e) => self.message = Some(format!("{:#}", e)),
+                        }
+                    }
+                    KeyCode::Esc => self.editor = None,
+                    _ => {}
+                }
+                continue;
+            }
+
+            if navigate(&mut self.table, key.code, self.review.rows().len()) {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char(' ') => self.review.toggle(selected),
+                KeyCode::Char('t') => self.start_editing(selected, Field::Title),
+                KeyCode::Char('d') => self.start_editing(selected, Field::WatchedDate),
+                KeyCode::Char('r') => self.start_editing(selected, Field::Rating),
+                KeyCode::Char('w') => return Ok(true),
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(false),
+                _ => {}
+            }
+        }
+    }
+
+    /// Starts editing a field of a row, starting from its current value
+    fn start_editing(&mut self, index: usize, field: Field) {
+        let input = field.value(&self.review.rows()[index]);
+        self.editor = Some(Editor { field, input });
+    }
+
+    /// Draws the table of rows, the edit or error line and the key help
+    fn draw(&mut self, frame: &mut Frame) {
+        let [table_area, status_area, help_area] = Layout::vertical([
+            Constraint::Min(3),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let rows = self.review.rows().iter().enumerate().map(|(index, row)| {
+            let included = self.review.is_included(index);
+            let style = if included {
+                Style::new()
+            } else {
+                Style::new().add_modifier(Modifier::DIM | Modifier::CROSSED_OUT)
+            };
+            table_row(row, included).style(style)
+        });
+        let title = format!(
+            " Review {} rows ({} excluded) ",
+            self.review.rows().len(),
+            self.review.excluded_count()
+        );
+        let table = rows_table(rows, title);
+        frame.render_stateful_widget(table, table_area, &mut self.table);
+
+        let status = match (&self.editor, &self.message) {
+            (_, Some(message)) => Line::from(message.as_str()).red(),
+            (Some(editor), None) => Line::from(format!(
+                "{}: {}_  (enter to save, esc to discard)",
+                editor.field.name(),
+                editor.input
+            )),
+            (None, None) => Line::default(),
+        };
+        frame.render_widget(Paragraph::new(status), status_area);
+        frame.render_widget(Paragraph::new(REVIEW_HELP).dim(), help_area);
+    }
+}
+
+/// Returns the table row showing an export row, with a checkbox in front
+fn table_row(row: &ExportRow, checked: bool) -> Row<'static> {
+    Row::new([
+        Cell::from(if checked { "[x]" } else { "[ ]" }),
+        Cell::from(row.watched_date.clone()),
+        Cell::from(row.title.clone()),
+        Cell::from(Column::Year.value(row)),
+        Cell::from(Column::Rating.value(row)),
+        Cell::from(row.imdb_id.clone()),
+    ])
+}
+
+/// Returns the table of export rows shared by the review and browse screens
+fn rows_table<'a>(rows: impl IntoIterator<Item = Row<'a>>, title: String) -> Table<'a> {
+    let header = Row::new(["", "WatchedDate", "Title", "Year", "Rating", "imdbID"]).bold();
+    Table::new(
+        rows,
+        [
+            Constraint::Length(3),
+            Constraint::Length(11),
+            Constraint::Fill(1),
+            Constraint::Length(4),
+            Constraint::Length(6),
+            Constraint::Length(12),
+        ],
+    )
+    .header(header)
+    .block(Block::bordered().title(title))
+    .row_highlight_style(Style::new().reversed())
+    .highlight_symbol("> ")
+}
+
+/// Moves the selection of a table for the navigation keys, returning whether the key was one
+fn navigate(table: &mut TableState, key: KeyCode, len: usize) -> bool {
+    let selected = table.selected().unwrap_or_default();
+    let last = len.saturating_sub(1);
+    match key {
+        KeyCode::Down | KeyCode::Char('j') => table.select_next(),
+        KeyCode::Up | KeyCode::Char('k') => table.select_previous(),
+        KeyCode::PageDown => table.select(Some((selected + PAGE_SIZE).min(last))),
+        KeyCode::PageUp => table.select(Some(selected.saturating_sub(PAGE_SIZE))),
+        KeyCode::Home => table.select_first(),
+        KeyCode::End => table.select(Some(last)),
+        _ => return false,
+    }
+    true
+}
+
+/// Shows the rows in a terminal UI where they can be excluded or edited
+///
+/// Returns the review once the user writes the rows, or `None` when they
+/// cancel it. The terminal is restored before returning, also on errors.
+pub fn review(rows: Vec<ExportRow>) -> Result<Option<RowReview>> {
+    let mut app = ReviewApp {
+        review: RowReview::new(rows),
+        table: TableState::default().with_selected(Some(0)),
+        editor: None,
+        message: None,
+    };
+
+    let mut terminal = ratatui::try_init().context("Failed to start the review screen")?;
+    let outcome = app.run(&mut terminal);
+    ratatui::restore();
+
+    let write = outcome.context("Failed to read input on the review screen")?;
+    Ok(write.then_some(app.review))
+}
+
+/// Which rows the browse screen shows, besides those matching the search
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BrowseFilter {
+    /// Every row
+    #[default]
+    All,
+    /// Rows with a rating
+    Rated,
+    /// Rows without a rating
+    Unrated,
+    /// Rows selected for export
+    Selected,
+}
+
+impl BrowseFilter {
+    /// Returns the filter that follows this one when cycling through them
+    pub fn next(self) -> Self {
+        match self {
+            BrowseFilter::All => BrowseFilter::Rated,
+            BrowseFilter::Rated => BrowseFilter::Unrated,
+            BrowseFilter::Unrated => BrowseFilter::Selected,
+            BrowseFilter::Selected => BrowseFilter::All,
+        }
+    }
+
+    /// Returns the name of the filter shown on the browse screen
+    pub fn name(&self) -> &'static str {
+        match self {
+            BrowseFilter::All => "all",
+            BrowseFilter::Rated => "rated",
+            BrowseFilter::Unrated => "unrated",
+            BrowseFilter::Selected => "selected",
+        }
+    }
+
+    /// Returns whether a row passes the filter
+    fn matches(&self, row: &ExportRow, selected: bool) -> bool {
+        match self {
+            BrowseFilter::All => true,
+            BrowseFilter::Rated => row.rating.is_some(),
+            BrowseFilter::Unrated => row.rating.is_none(),
+            BrowseFilter::Selected => selected,
+        }
+    }
+}
+
+/// Watch history being browsed, with the search, filter and selected rows
+///
+/// The search matches rows whose title, imdbID, WatchedDate or year contain
+/// every word of it, ignoring case. The terminal UI of [`browse`] is built on
+/// top of this.
+///
+/// # Example
+///
+/// ```rust
+/// use plex_to_letterboxd::row::ExportRow;
+/// use plex_to_letterboxd::tui::{BrowseFilter, HistoryBrowser};
+///
+/// let row = |title: &str, year: u32, rating: Option<f32>| ExportRow {
+///     title: title.to_string(),
+///     year: Some(year),
+///     rating,
+///     ..Default::default()
+/// };
+/// let mut browser = HistoryBrowser::new(vec![
+///     row("Heat", 1995, Some(9.0)),
+///     row("Thief", 1981, None),
+///     row("Heat", 1986, None),
+/// ]);
+///
+/// browser.set_search("heat 1995");
+/// assert_eq!(browser.visible(), [0]);
+///
+/// browser.set_search("");
+/// browser.set_filter(BrowseFilter::Unrated);
+/// assert_eq!(browser.visible(), [1, 2]);
+/// browser.select_visible(true);
+///
+/// let selection = browser.into_selection();
+/// assert_eq!(selection.len(), 2);
+/// assert_eq!(selection[0].title, "Thief");
+/// ```
+#[derive(Debug, Clone)]
+pub struct HistoryBrowser {
+    /// Rows of the history, in output order
+    rows: Vec<ExportRow>,
+    /// Whether each row is selected for export
+    selected: Vec<bool>,
+    /// Words every shown row has to contain
+    search: String,
+    /// Filter applied on top of the search
+    filter: BrowseFilter,
+    /// Indices of the rows matching the search and filter
+    visible: Vec<usize>,
+}
+
+impl HistoryBrowser {
+    /// Starts browsing the given rows, showing all of them with none selected
+    pub fn new(rows: Vec<ExportRow>) -> Self {
+        let mut browser = Self {
+            selected: vec![false; rows.len()],
+            rows,
+            search: String::new(),
+            filter: BrowseFilter::default(),
+            visible: Vec::new(),
+        };
+        browser.refresh();
+        browser
+    }
+
+    /// Returns all rows, including those not shown
+    pub fn rows(&self) -> &[ExportRow] {
+        &self.rows
+    }
+
+    /// Returns the indices of the rows matching the search and filter
+    pub fn visible(&self) -> &[usize] {
+        &self.visible
+    }
+
+    /// Returns the current search
+    pub fn search(&self) -> &str {
+        &self.search
+    }
+
+    /// Shows only the rows matching a search
+    pub fn set_search(&mut self, search: &str) {
+        self.search = search.to_string();
+        self.refresh();
+    }
+
+    /// Returns the current filter
+    pub fn filter(&self) -> BrowseFilter {
+        self.filter
+    }
+
+    /// Shows only the rows passing a filter
+    pub fn set_filter(&mut self, filter: BrowseFilter) {
+        self.filter = filter;
+        self.refresh();
+    }
+
+    /// Returns whether a row is selected for export
+    pub fn is_selected(&self, index: usize) -> bool {
+        self.selected[index]
+    }
+
+    /// Returns the number of rows selected for export
+    pub fn selected_count(&self) -> usize {
+        self.selected.iter().filter(|selected| **selected).count()
+    }
+
+    /// Selects an unselected row, or unselects a selected one
+    pub fn toggle(&mut self, index: usize) {
+        self.selected[index] = !self.selected[index];
+        if self.filter == BrowseFilter::Selected {
+            self.refresh();
+        }
+    }
+
+    /// Selects or unselects every row currently shown
+    pub fn select_visible(&mut self, selected: bool) {
+        for &index in &self.visible {
+            self.selected[index] = selected;
+        }
+        if self.filter == BrowseFilter::Selected {
+            self.refresh();
+        }
+    }
+
+    /// Returns the selected rows, in output order
+    pub fn into_selection(self) -> Vec<ExportRow> {
+        self.rows
+            .into_iter()
+            .zip(self.selected)
+            .filter_map(|(row, selected)| selected.then_some(row))
+            .collect()
+    }
+
+    /// Recomputes the rows shown after the search, filter or selection changed
+    fn refresh(&mut self) {
+        let words: Vec<String> = self
+            .search
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect();
+        self.visible = (0..self.rows.len())
+            .filter(|&index| {
+                let row = &self.rows[index];
+                let text = format!(
+                    "{} {} {} {}",
+                    row.title,
+                    row.imdb_id,
+                    row.watched_date,
+                    Column::Year.value(row)
+                )
+                .to_lowercase();
+                self.filter.matches(row, self.selected[index])
+                    && words.iter().all(|word| text.contains(word.as_str()))
+            })
+            .collect();
+    }
+}
+
+/// State of the browse screen
+struct BrowseApp {
+    /// History, search and selection
+    browser: HistoryBrowser,
+    /// Selected line and scroll position of the table
+    table: TableState,
+    /// Whether keys are typed into the search
+    searching: bool,
+    /// Hint shown until the next key press
+    message: Option<String>,
+}
+
+impl BrowseApp {
+    /// Handles key presses until the selection is exported (`true`) or the user quits
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<bool> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            self.message = None;
+
+            if self.searching {
+                let mut search = self.browser.search().to_string();
+                match key.code {
+                    KeyCode::Char(c) => search.push(c),
+                    KeyCode::Backspace => {
+                        search.pop();
+                    }
+                    KeyCode::Enter => self.searching = false,
+                    KeyCode::Esc => {
+                        search.clear();
+                        self.searching = false;
+                    }
+                    _ => {}
+                }
+                if search != self.browser.search() {
+                    self.browser.set_search(&search);
+                    self.table.select_first();
+                }
+                continue;
+            }
+
+            if navigate(&mut self.table, key.code, self.browser.visible().len()) {
+                continue;
+            }
+            let current = self
+                .table
+                .selected()
+                .and_then(|line| self.browser.visible().get(line).copied());
+            match key.code {
+                KeyCode::Char(' ') => {
+                    if let Some(index) = current {
+                        self.browser.toggle(index);
+                    }
+                }
+                KeyCode::Char('/') => self.searching = true,
+                KeyCode::Char('f') => {
+                    self.browser.set_filter(self.browser.filter().next());
+                    self.table.select_first();
+                }
+                KeyCode::Char('a') => self.browser.select_visible(true),
+                KeyCode::Char('u') => self.browser.select_visible(false),
+                KeyCode::Char('e') if self.browser.selected_count() == 0 => {
+                    self.message = Some("Select rows to export with space first".to_string());
+                }
+                KeyCode::Char('e') => return Ok(true),
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(false),
+                _ => {}
+            }
+        }
+    }
+
+    /// Draws the table of rows, the search or hint line and the key help
+    fn draw(&mut self, frame: &mut Frame) {
+        let [table_area, status_area, help_area] = Layout::vertical([
+            Constraint::Min(3),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let browser = &self.browser;
+        let rows = browser
+            .visible()
+            .iter()
+            .map(|&index| table_row(&browser.rows()[index], browser.is_selected(index)));
+        let title = format!(
+            " History: {} of {} shown ({}), {} selected ",
+            browser.visible().len(),
+            browser.rows().len(),
+            browser.filter().name(),
+            browser.selected_count()
+        );
+        frame.render_stateful_widget(rows_table(rows, title), table_area, &mut self.table);
+
+        let status = match &self.message {
+            Some(message) => Line::from(message.as_str()).red(),
+            None if self.searching => Line::from(format!("/{}_", browser.search())),
+            None if !browser.search().is_empty() => {
+                Line::from(format!("Search: {}", browser.search()))
+            }
+            None => Line::default(),
+        };
+        frame.render_widget(Paragraph::new(status), status_area);
+        frame.render_widget(Paragraph::new(BROWSE_HELP).dim(), help_area);
+    }
+}
+
+/// Shows the rows in a terminal UI where they can be searched, filtered and selected
+///
+/// Returns the selected rows once the user exports them, or `None` when they
+/// quit. The terminal is restored before returning, also on errors.
+pub fn browse(rows: Vec<ExportRow>) -> Result<Option<Vec<ExportRow>>> {
+    let mut app = BrowseApp {
+        browser: HistoryBrowser::new(rows),
+        table: TableState::default().with_selected(Some(0)),
+        searching: false,
+        message: None,
+    };
+
+    let mut terminal = ratatui::try_init().context("Failed to start the browse screen")?;
+    let outcome = app.run(&mut terminal);
+    ratatui::restore();
+
+    let export = outcome.context("Failed to read input on the browse screen")?;
+    Ok(export.then(|| app.browser.into_selection()))
+}
@@ -0,0 +1,48 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+/// A film of a Letterboxd watchlist export (`watchlist.csv`)
+#[derive(Debug, Clone, Deserialize)]
+pub struct WatchlistEntry {
+    /// Date the film was added to the watchlist (YYYY-MM-DD)
+    #[serde(rename = "Date", default)]
+    pub date: String,
+    /// Title of the film
+    #[serde(rename = "Name")]
+    pub name: String,
+    /// Release year of the film
+    #[serde(rename = "Year", default)]
+    pub year: Option<u32>,
+    /// Letterboxd URL of the film
+    #[serde(rename = "Letterboxd URI", default)]
+    pub letterboxd_uri: String,
+}
+
+/// Reads the films of a Letterboxd watchlist export
+///
+/// # Example
+///
+/// ```rust
+/// use plex_to_letterboxd::watchlist::parse_watchlist;
+///
+/// let csv = "Date,Name,Year,Letterboxd URI\n2024-01-15,Heat,1995,https://boxd.it/29QE\n";
+/// let entries = parse_watchlist(csv.as_bytes()).unwrap();
+/// assert_eq!(entries[0].name, "Heat");
+/// assert_eq!(entries[0].year, Some(1995));
+/// ```
+pub fn parse_watchlist(reader: impl std::io::Read) -> Result<Vec<WatchlistEntry>> {
+    csv::Reader::from_reader(reader)
+        .deserialize()
+        .collect::<Result<_, _>>()
+        .context("Invalid Letterboxd watchlist")
+}
+
+/// Reads a Letterboxd watchlist export from disk
+pub fn read_watchlist(path: impl AsRef<Path>) -> Result<Vec<WatchlistEntry>> {
+    let path = path.as_ref();
+    let file = std::fs::File::open(path)
+        .with_context(|| format!("Failed to open watchlist: {}", path.display()))?;
+    parse_watchlist(file).with_context(|| format!("Invalid watchlist: {}", path.display()))
+}